@@ -0,0 +1,99 @@
+# Deutsch
+
+game-title = ColorWheel Bump
+help =
+    Tippen/Klicken/Leertaste zum Springen.
+    Sammle die Sechsecke, um
+    das Farbrad zu durchlaufen
+    und das markierte Feld zu erreichen.
+
+## Titelbildschirm
+
+menu-start = Beliebige Taste drücken oder tippen zum Spielen
+menu-campaign = Kampagne
+menu-keys = Links/Rechts: Schwierigkeit    -/+: Tempo    [/]: Sound    ;/': Musik    R: Routenvorschau    V: Effekte    M: Minikarte    K: Audiohinweise    L: Sprache    C: Kampagne
+menu-speed = Tempo { $percent } %
+menu-sound = Sound { $percent } %
+menu-music = Musik { $percent } %
+menu-route-preview = Routenvorschau: { $state }
+menu-effects = Effekte: { $level }
+menu-minimap = Minikarte: { $state }
+menu-audio-cues = Audiohinweise: { $state }
+menu-language = Sprache: { $name }
+setting-on = an
+setting-off = aus
+
+difficulty-relaxed = Entspannt
+difficulty-normal = Normal
+difficulty-hard = Schwer
+difficulty-insane = Wahnsinnig
+
+effects-reduced-motion = Weniger Bewegung
+effects-low = Gering
+effects-full = Voll
+
+## Im Spiel
+
+hud-target = Ziel { $color }
+hud-multiplier = Multiplikator x{ $multiplier }
+hud-best = Bestwert { $score } ({ $difficulty })
+hud-time = Zeit { $seconds } s
+hud-time-left = Übrig { $seconds } s
+hud-hits = Treffer { $hits }/{ $max }
+hud-falls = Stürze { $falls }/{ $max }
+hud-speed = Tempo x{ $speed }
+hud-route =
+    { $steps ->
+        [one] 1 Schritt
+       *[other] { $steps } Schritte
+    }
+
+## Kampagne
+
+campaign-title = Kampagne
+campaign-keys = Klicken oder 1-9 drücken zum Spielen    Esc: zurück
+campaign-locked = { $number }. Gesperrt
+campaign-level-new = { $number }. { $name }    ---
+campaign-level-record = { $number }. { $name }    { $stars }    { $time } s ({ $difficulty })
+campaign-cleared = { $name } geschafft mit { $stars } von 3 Sternen in { $time } s
+campaign-failed = { $name } gescheitert: { $reason }
+lose-time-up = Zeit abgelaufen
+lose-too-many-hits = zu viele Treffer
+lose-too-many-falls = zu viele Stürze
+
+## Farben des Farbrads
+
+color-orange = Orange
+color-yellow-orange = Gelborange
+color-yellow = Gelb
+color-yellow-green = Gelbgrün
+color-green = Grün
+color-blue-green = Blaugrün
+color-blue = Blau
+color-blue-purple = Blauviolett
+color-purple = Violett
+color-red-purple = Rotviolett
+color-red = Rot
+color-red-orange = Rotorange
+
+## Level-Editor
+
+editor-title = Bearbeite { $name }    { $time }s    { $brush }
+editor-renaming = { $name }_ (Enter: umbenennen  Esc: abbrechen)
+editor-crystal = Kristall { $color } { $kind }
+editor-platform = Plattform { $kind }
+editor-barrier = Schranke { $color }
+editor-colors = Start { $start }    Ziele { $targets }
+editor-no-color = Keine
+editor-keys =
+    1-3: Werkzeug  Q/E: Farbe  Tab: Art  Mausrad/BildAuf/BildAb: Zeit  Klick: setzen  Rechtsklick: entfernen
+    T: Ziel hinzufügen  Rücktaste: Ziel entfernen  C: Startfarbe  P: ab Cursor testen
+    N: umbenennen  Strg+Z/Strg+Y: rückgängig/wiederholen  Strg+S: speichern  Esc: beenden
+crystal-normal = Normal
+crystal-fast = Schnell
+crystal-slow = Langsam
+platform-solid = Fest
+platform-one-way = Einseitig
+platform-crumbling = Bröckelnd
+platform-conveyor = Förderband
+platform-damaging = Schädlich
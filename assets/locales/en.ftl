@@ -0,0 +1,100 @@
+# English, also used for anything missing from the other languages.
+# Indented lines continue a message on a new line.
+
+game-title = ColorWheel Bump
+help =
+    Touch/Click/Space to jump.
+    Collect the hexes to
+    traverse the colorwheel
+    and reach the highlighted segment.
+
+## Title screen
+
+menu-start = Press any key or tap to play
+menu-campaign = Campaign
+menu-keys = Left/Right: difficulty    -/+: game speed    [/]: sound    ;/': music    R: route preview    V: effects    M: minimap    K: audio cues    L: language    C: campaign
+menu-speed = Speed { $percent }%
+menu-sound = Sound { $percent }%
+menu-music = Music { $percent }%
+menu-route-preview = Route preview: { $state }
+menu-effects = Effects: { $level }
+menu-minimap = Minimap: { $state }
+menu-audio-cues = Audio cues: { $state }
+menu-language = Language: { $name }
+setting-on = on
+setting-off = off
+
+difficulty-relaxed = Relaxed
+difficulty-normal = Normal
+difficulty-hard = Hard
+difficulty-insane = Insane
+
+effects-reduced-motion = Reduced motion
+effects-low = Low
+effects-full = Full
+
+## In game
+
+hud-target = Target { $color }
+hud-multiplier = Multiplier x{ $multiplier }
+hud-best = Best { $score } on { $difficulty }
+hud-time = Time { $seconds }s
+hud-time-left = Left { $seconds }s
+hud-hits = Hits { $hits }/{ $max }
+hud-falls = Falls { $falls }/{ $max }
+hud-speed = Speed x{ $speed }
+hud-route =
+    { $steps ->
+        [one] 1 step
+       *[other] { $steps } steps
+    }
+
+## Campaign
+
+campaign-title = Campaign
+campaign-keys = Click or press 1-9 to play    Esc: back
+campaign-locked = { $number }. Locked
+campaign-level-new = { $number }. { $name }    ---
+campaign-level-record = { $number }. { $name }    { $stars }    { $time }s ({ $difficulty })
+campaign-cleared = { $name } cleared with { $stars } of 3 stars in { $time }s
+campaign-failed = { $name } failed: { $reason }
+lose-time-up = out of time
+lose-too-many-hits = too many hits
+lose-too-many-falls = too many falls
+
+## Colors of the colorwheel
+
+color-orange = Orange
+color-yellow-orange = Yellow-orange
+color-yellow = Yellow
+color-yellow-green = Yellow-green
+color-green = Green
+color-blue-green = Blue-green
+color-blue = Blue
+color-blue-purple = Blue-purple
+color-purple = Purple
+color-red-purple = Red-purple
+color-red = Red
+color-red-orange = Red-orange
+
+## Level editor
+
+editor-title = Editing { $name }    { $time }s    { $brush }
+editor-renaming = { $name }_ (Enter: rename  Esc: cancel)
+editor-crystal = Crystal { $color } { $kind }
+editor-platform = Platform { $kind }
+editor-barrier = Barrier { $color }
+editor-colors = Start { $start }    Targets { $targets }
+editor-no-color = None
+editor-keys =
+    1-3: tool  Q/E: color  Tab: kind  Wheel/PgUp/PgDn: time  Click: place  Right click: remove
+    T: add target  Backspace: remove target  C: start color  P: play-test from cursor
+    N: rename  Ctrl+Z/Ctrl+Y: undo/redo  Ctrl+S: save  Esc: exit
+crystal-normal = Normal
+crystal-fast = Fast
+crystal-slow = Slow
+platform-solid = Solid
+platform-one-way = One-way
+platform-crumbling = Crumbling
+platform-conveyor = Conveyor
+platform-damaging = Damaging
@@ -0,0 +1,99 @@
+# Español
+
+game-title = ColorWheel Bump
+help =
+    Toca/Haz clic/Espacio para saltar.
+    Recoge los hexágonos para
+    recorrer la rueda de colores
+    y llegar al segmento resaltado.
+
+## Pantalla de título
+
+menu-start = Pulsa cualquier tecla o toca para jugar
+menu-campaign = Campaña
+menu-keys = Izq./Der.: dificultad    -/+: velocidad    [/]: sonido    ;/': música    R: vista de ruta    V: efectos    M: minimapa    K: pistas de audio    L: idioma    C: campaña
+menu-speed = Velocidad { $percent } %
+menu-sound = Sonido { $percent } %
+menu-music = Música { $percent } %
+menu-route-preview = Vista de ruta: { $state }
+menu-effects = Efectos: { $level }
+menu-minimap = Minimapa: { $state }
+menu-audio-cues = Pistas de audio: { $state }
+menu-language = Idioma: { $name }
+setting-on = sí
+setting-off = no
+
+difficulty-relaxed = Relajado
+difficulty-normal = Normal
+difficulty-hard = Difícil
+difficulty-insane = Demencial
+
+effects-reduced-motion = Movimiento reducido
+effects-low = Bajos
+effects-full = Completos
+
+## En juego
+
+hud-target = Objetivo { $color }
+hud-multiplier = Multiplicador x{ $multiplier }
+hud-best = Récord { $score } en { $difficulty }
+hud-time = Tiempo { $seconds } s
+hud-time-left = Quedan { $seconds } s
+hud-hits = Golpes { $hits }/{ $max }
+hud-falls = Caídas { $falls }/{ $max }
+hud-speed = Velocidad x{ $speed }
+hud-route =
+    { $steps ->
+        [one] 1 paso
+       *[other] { $steps } pasos
+    }
+
+## Campaña
+
+campaign-title = Campaña
+campaign-keys = Haz clic o pulsa 1-9 para jugar    Esc: volver
+campaign-locked = { $number }. Bloqueado
+campaign-level-new = { $number }. { $name }    ---
+campaign-level-record = { $number }. { $name }    { $stars }    { $time } s ({ $difficulty })
+campaign-cleared = { $name } superado con { $stars } de 3 estrellas en { $time } s
+campaign-failed = { $name } fallido: { $reason }
+lose-time-up = se acabó el tiempo
+lose-too-many-hits = demasiados golpes
+lose-too-many-falls = demasiadas caídas
+
+## Colores de la rueda
+
+color-orange = Naranja
+color-yellow-orange = Amarillo anaranjado
+color-yellow = Amarillo
+color-yellow-green = Amarillo verdoso
+color-green = Verde
+color-blue-green = Verde azulado
+color-blue = Azul
+color-blue-purple = Azul violáceo
+color-purple = Violeta
+color-red-purple = Rojo violáceo
+color-red = Rojo
+color-red-orange = Rojo anaranjado
+
+## Editor de niveles
+
+editor-title = Editando { $name }    { $time }s    { $brush }
+editor-renaming = { $name }_ (Intro: renombrar  Esc: cancelar)
+editor-crystal = Cristal { $color } { $kind }
+editor-platform = Plataforma { $kind }
+editor-barrier = Barrera { $color }
+editor-colors = Inicio { $start }    Objetivos { $targets }
+editor-no-color = Ninguno
+editor-keys =
+    1-3: herramienta  Q/E: color  Tab: tipo  Rueda/RePág/AvPág: tiempo  Clic: colocar  Clic derecho: quitar
+    T: añadir objetivo  Retroceso: quitar objetivo  C: color inicial  P: probar desde el cursor
+    N: renombrar  Ctrl+Z/Ctrl+Y: deshacer/rehacer  Ctrl+S: guardar  Esc: salir
+crystal-normal = Normal
+crystal-fast = Rápido
+crystal-slow = Lento
+platform-solid = Sólida
+platform-one-way = De un sentido
+platform-crumbling = Quebradiza
+platform-conveyor = Cinta
+platform-damaging = Dañina
@@ -0,0 +1,182 @@
+use std::{
+    fs::File,
+    io::{self, Write},
+};
+
+use colorwheel_bump::{
+    balance::{run_game, RunStats, Summary},
+    bot::BotDifficulty,
+    bump::MapConfig,
+    core::crystal::{CombineRule, SpawnWeights},
+    difficulty::Difficulty,
+};
+
+const USAGE: &str = "Usage: balance-sim [--runs N] [--seconds S] [--seed N] \
+[--difficulty easy|normal|hard] [--preset relaxed|normal|hard|insane] [--out FILE]";
+
+struct Options {
+    runs: u64,
+    seconds: f64,
+    seed: u64,
+    difficulty: BotDifficulty,
+    preset: Difficulty,
+    out: Option<String>,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        runs: 50,
+        seconds: 120.,
+        seed: 0,
+        difficulty: BotDifficulty::Normal,
+        preset: Difficulty::Normal,
+        out: None,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {}", arg));
+        match arg.as_str() {
+            "--runs" => options.runs = value()?.parse().map_err(|e| format!("{}", e))?,
+            "--seconds" => options.seconds = value()?.parse().map_err(|e| format!("{}", e))?,
+            "--seed" => options.seed = value()?.parse().map_err(|e| format!("{}", e))?,
+            "--difficulty" => {
+                options.difficulty = match value()?.as_str() {
+                    "easy" => BotDifficulty::Easy,
+                    "normal" => BotDifficulty::Normal,
+                    "hard" => BotDifficulty::Hard,
+                    other => return Err(format!("Unknown difficulty {}", other)),
+                }
+            }
+            "--preset" => {
+                options.preset = match value()?.as_str() {
+                    "relaxed" => Difficulty::Relaxed,
+                    "normal" => Difficulty::Normal,
+                    "hard" => Difficulty::Hard,
+                    "insane" => Difficulty::Insane,
+                    other => return Err(format!("Unknown preset {}", other)),
+                }
+            }
+            "--out" => options.out = Some(value()?),
+            "--help" | "-h" => return Err(USAGE.to_string()),
+            other => return Err(format!("Unknown argument {}\n{}", other, USAGE)),
+        }
+    }
+
+    Ok(options)
+}
+
+/// The points in the parameter sweep.
+fn variants() -> Vec<MapConfig> {
+    let weights = [
+        SpawnWeights::PRIMARY_ONLY,
+        SpawnWeights {
+            primary: 0.6,
+            secondary: 0.3,
+            tertiary: 0.1,
+        },
+    ];
+
+    let combine_rules = [CombineRule::Step, CombineRule::DoubleStep, CombineRule::Adopt];
+
+    let mut variants = Vec::new();
+    for crystal_count in [16, 32, 48] {
+        for crystal_linvel in [4., 6., 8.] {
+            for spawn_weights in weights {
+                for combine_rule in combine_rules {
+                    variants.push(MapConfig {
+                        crystal_count,
+                        crystal_linvel,
+                        spawn_weights,
+                        combine_rule,
+                        ..MapConfig::default()
+                    });
+                }
+            }
+        }
+    }
+    variants
+}
+
+fn main() {
+    let options = match parse_options() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+
+    let variants = variants();
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    eprintln!(
+        "Running {} variants x {} runs on {} threads",
+        variants.len(),
+        options.runs,
+        threads
+    );
+
+    // Each game owns its own App, so variants can simply be spread over threads
+    let chunk_size = variants.len().div_ceil(threads);
+    let mut summaries: Vec<Option<Summary>> = vec![None; variants.len()];
+    std::thread::scope(|scope| {
+        for (chunk_index, chunk) in summaries.chunks_mut(chunk_size).enumerate() {
+            let variants = &variants;
+            let options = &options;
+            scope.spawn(move || {
+                for (i, summary) in chunk.iter_mut().enumerate() {
+                    let variant = &variants[chunk_index * chunk_size + i];
+                    let runs: Vec<RunStats> = (0..options.runs)
+                        .map(|run| {
+                            run_game(
+                                variant,
+                                options.preset,
+                                options.difficulty,
+                                options.seed + run,
+                                options.seconds,
+                            )
+                        })
+                        .collect();
+                    *summary = Some(Summary::from_runs(&runs));
+                }
+            });
+        }
+    });
+
+    let mut out: Box<dyn Write> = match &options.out {
+        Some(path) => Box::new(File::create(path).expect("Unable to create output file")),
+        None => Box::new(io::stdout()),
+    };
+    writeln!(
+        out,
+        "preset,crystal_count,crystal_linvel,spawn_weights,combine_rule,runs,\
+score_mean,score_p10,score_p50,score_p90,time_to_target_mean,collected_mean,wasted_mean"
+    )
+    .unwrap();
+    for (variant, summary) in variants.iter().zip(summaries) {
+        let summary = summary.unwrap_or_default();
+        // The preset scales and overrides some of the swept values, so write what was played
+        let map_config = options.preset.apply(variant);
+        let weights = map_config.spawn_weights;
+        writeln!(
+            out,
+            "{:?},{},{},{}/{}/{},{:?},{},{:.3},{},{},{},{:.3},{:.3},{:.3}",
+            options.preset,
+            map_config.crystal_count,
+            map_config.crystal_linvel,
+            weights.primary,
+            weights.secondary,
+            weights.tertiary,
+            map_config.combine_rule,
+            summary.runs,
+            summary.score_mean,
+            summary.score_p10,
+            summary.score_p50,
+            summary.score_p90,
+            summary.time_to_target_mean,
+            summary.collected_mean,
+            summary.wasted_mean,
+        )
+        .unwrap();
+    }
+}
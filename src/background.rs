@@ -0,0 +1,266 @@
+//! The backdrop behind the map: a tinted gradient, slow drifting shapes and the grid,
+//! each a single entity scrolled with parallax. Colors come from `BackgroundTheme`.
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bevy_prototype_lyon::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    bump::{CurrentColor, MapConfig},
+    camera::{CameraFollow, GameCamera},
+    core::timestep::GameClock,
+    tween::{Lerp, Tween},
+};
+
+/// Rows in the gradient texture, stretched over the whole map.
+const GRADIENT_STEPS: u32 = 64;
+/// Seconds the backdrop takes to shift toward a new color.
+const TINT_TIME: f32 = 1.;
+
+/// How the backdrop looks. Swap it out to retheme the game.
+#[derive(Debug, Clone)]
+pub struct BackgroundTheme {
+    pub gradient_top: Color,
+    pub gradient_bottom: Color,
+    pub shape_color: Color,
+    pub shape_count: usize,
+    /// How fast the shapes scroll compared to the grid, 0 for not at all.
+    pub shape_parallax: f32,
+    /// How far every layer is pulled toward the player's color, between 0 and 1.
+    pub tint_strength: f32,
+}
+
+impl Default for BackgroundTheme {
+    fn default() -> Self {
+        BackgroundTheme {
+            gradient_top: Color::rgb(0.14, 0.14, 0.17),
+            gradient_bottom: Color::rgb(0.06, 0.06, 0.07),
+            shape_color: Color::rgba(0.5, 0.5, 0.5, 0.25),
+            shape_count: 24,
+            shape_parallax: 0.3,
+            tint_strength: 0.15,
+        }
+    }
+}
+
+/// A layer of the backdrop. Its contents repeat every `tile_width`, so it can be scrolled
+/// forever by moving it less than a tile.
+#[derive(Component)]
+struct BackgroundLayer {
+    /// How fast it scrolls and follows the camera, 1 for moving with the map.
+    parallax: f32,
+    tile_width: f32,
+    origin_x: f32,
+}
+
+#[derive(Component)]
+struct Gradient;
+#[derive(Component)]
+struct Grid;
+#[derive(Component)]
+struct Shapes;
+
+pub struct BackgroundPlugin;
+
+impl Plugin for BackgroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BackgroundTheme>()
+            .add_system(build_background)
+            .add_system(scroll_background.after(CameraFollow))
+            .add_system(tint_background);
+    }
+}
+
+/// Builds the layers to fit the map, and rebuilds them when the map changes size.
+#[allow(clippy::type_complexity)]
+fn build_background(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    layers: Query<Entity, Or<(With<Gradient>, With<BackgroundLayer>)>>,
+    mut built_size: Local<Option<Vec2>>,
+    map_config: Res<MapConfig>,
+    theme: Res<BackgroundTheme>,
+) {
+    let size = map_config.map_size;
+    if *built_size == Some(size) {
+        return;
+    }
+    *built_size = Some(size);
+    for entity in &layers {
+        commands.entity(entity).despawn_recursive();
+    }
+    let hsize = size / 2.;
+
+    // Gradient, from a texture one pixel wide stretched over the map and its borders
+    let pixels = (0..GRADIENT_STEPS)
+        .flat_map(|row| {
+            let t = row as f32 / (GRADIENT_STEPS - 1) as f32;
+            let top = Vec4::from(theme.gradient_top.as_rgba_f32());
+            let bottom = Vec4::from(theme.gradient_bottom.as_rgba_f32());
+            top.lerp(bottom, t)
+                .to_array()
+                .map(|channel| (channel.clamp(0., 1.) * 255.).round() as u8)
+        })
+        .collect();
+    let gradient = images.add(Image::new(
+        Extent3d {
+            width: 1,
+            height: GRADIENT_STEPS,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pixels,
+        TextureFormat::Rgba8UnormSrgb,
+    ));
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: gradient,
+            sprite: Sprite {
+                custom_size: Some(size + Vec2::splat(4.)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0., 0., 0.),
+            ..default()
+        })
+        .insert(Gradient);
+
+    // Distant shapes, repeated over a few tiles of the map's width so the layer can slide a
+    // whole tile and shapes overlapping a tile's edge still show on both sides
+    let mut rng = StdRng::seed_from_u64(0x0c010f);
+    let mut shapes = GeometryBuilder::new();
+    let tile_width = size.x;
+    let placements: Vec<_> = (0..theme.shape_count)
+        .map(|_| {
+            let center = Vec2::new(
+                rng.gen::<f32>() * tile_width,
+                rng.gen::<f32>() * size.y - hsize.y,
+            );
+            (center, rng.gen_range(0.8..3.))
+        })
+        .collect();
+    for tile in -1..2 {
+        for (center, radius) in &placements {
+            shapes = shapes.add(&shapes::RegularPolygon {
+                sides: 6,
+                feature: shapes::RegularPolygonFeature::Radius(*radius),
+                center: *center + Vec2::X * tile as f32 * tile_width,
+            });
+        }
+    }
+    commands
+        .spawn_bundle(shapes.build(
+            DrawMode::Stroke(StrokeMode::new(theme.shape_color, 0.08)),
+            Transform::from_xyz(-hsize.x, 0., 2.),
+        ))
+        .insert(BackgroundLayer {
+            parallax: theme.shape_parallax,
+            tile_width,
+            origin_x: -hsize.x,
+        })
+        .insert(Shapes);
+
+    // Grid, one column wider than the map so it can slide a whole cell
+    let mut grid = GeometryBuilder::new();
+    for i in 0..=size.y as i32 {
+        let y = i as f32 - hsize.y;
+        grid = grid.add(&shapes::Line(Vec2::new(0., y), Vec2::new(size.x + 1., y)));
+    }
+    for i in 0..=size.x as i32 + 1 {
+        grid = grid.add(&shapes::Line(
+            Vec2::new(i as f32, -hsize.y),
+            Vec2::new(i as f32, hsize.y),
+        ));
+    }
+    commands
+        .spawn_bundle(grid.build(
+            DrawMode::Stroke(StrokeMode::new(
+                map_config.grid_color,
+                map_config.grid_width,
+            )),
+            Transform::from_xyz(-hsize.x, 0., 10.),
+        ))
+        .insert(BackgroundLayer {
+            parallax: 1.,
+            tile_width: 1.,
+            origin_x: -hsize.x,
+        })
+        .insert(Grid);
+}
+
+/// How far left of its origin a layer is drawn, between 0 and a whole tile. Layers with a
+/// `parallax` under 1 scroll slower and are dragged along with the camera, so they look
+/// further away.
+pub fn layer_offset(camera_x: f32, scroll: f32, parallax: f32, tile_width: f32) -> f32 {
+    let shift = camera_x * (1. - parallax) - scroll * parallax;
+    tile_width - shift.rem_euclid(tile_width)
+}
+
+fn scroll_background(
+    mut layers: Query<(&mut Transform, &BackgroundLayer)>,
+    cameras: Query<&Transform, (With<GameCamera>, Without<BackgroundLayer>)>,
+    clock: Res<GameClock>,
+    map_config: Res<MapConfig>,
+) {
+    let camera_x = cameras
+        .iter()
+        .next()
+        .map_or(0., |camera| camera.translation.x);
+    let scroll = clock.elapsed as f32 * map_config.background_speed;
+
+    for (mut transform, layer) in &mut layers {
+        transform.translation.x =
+            layer.origin_x - layer_offset(camera_x, scroll, layer.parallax, layer.tile_width);
+    }
+}
+
+/// Shifts every layer a little toward the player's color.
+#[allow(clippy::type_complexity)]
+fn tint_background(
+    mut gradients: Query<(&mut Sprite, ChangeTrackers<Gradient>)>,
+    mut outlines: Query<(&mut DrawMode, Option<&Grid>), Or<(With<Grid>, With<Shapes>)>>,
+    mut tint: Local<Option<Tween<Color>>>,
+    current_color: Res<CurrentColor>,
+    theme: Res<BackgroundTheme>,
+    map_config: Res<MapConfig>,
+    time: Res<Time>,
+) {
+    let now = time.seconds_since_startup();
+    // How far toward the player's color, as a blend from white
+    let target = match current_color.0 {
+        Some(color) => Color::WHITE.lerp(&color.to_color(), theme.tint_strength),
+        None => Color::WHITE,
+    };
+
+    let tint = tint.get_or_insert_with(|| Tween::at(Color::WHITE, TINT_TIME));
+    if tint.to != target {
+        tint.retarget(target, now);
+    } else if tint.is_finished(now)
+        && !theme.is_changed()
+        && !map_config.is_changed()
+        // Rebuilt layers start out untinted
+        && !gradients.iter().any(|(_, gradient)| gradient.is_added())
+    {
+        return;
+    }
+
+    let value = tint.value(now);
+    let tinted = |base: Color| {
+        let [red, green, blue, alpha] = base.as_rgba_f32();
+        let [tint_red, tint_green, tint_blue, _] = value.as_rgba_f32();
+        Color::rgba(red * tint_red, green * tint_green, blue * tint_blue, alpha)
+    };
+
+    for (mut sprite, _) in &mut gradients {
+        sprite.color = value;
+    }
+    for (mut draw_mode, grid) in &mut outlines {
+        let stroke = match grid {
+            Some(_) => StrokeMode::new(tinted(map_config.grid_color), map_config.grid_width),
+            None => StrokeMode::new(tinted(theme.shape_color), 0.08),
+        };
+        *draw_mode = DrawMode::Stroke(stroke);
+    }
+}
@@ -0,0 +1,121 @@
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+
+use crate::{
+    bot::BotDifficulty,
+    bump::{GameRng, MapConfig, TargetColor},
+    core::{
+        event::{CrystalCollected, TargetReached},
+        timestep::GameClock,
+    },
+    difficulty::{BaseMapConfig, Difficulty},
+    harness::Simulation,
+};
+
+/// What happened during one bot-played game.
+#[derive(Debug, Clone, Default)]
+pub struct RunStats {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub score: u32,
+    pub collected: u32,
+    /// Crystals that didn't bring the player any closer to the target.
+    pub wasted: u32,
+    /// Seconds taken to reach each target, in order.
+    pub time_to_target: Vec<f64>,
+}
+
+/// Plays a full game with a bot for `seconds` of game time, with `difficulty` applied to `map_config`.
+pub fn run_game(
+    map_config: &MapConfig,
+    difficulty: Difficulty,
+    bot_difficulty: BotDifficulty,
+    seed: u64,
+    seconds: f64,
+) -> RunStats {
+    let mut app = crate::headless_app();
+    app.insert_resource(GameRng::from_seed(seed))
+        .insert_resource(BaseMapConfig(map_config.clone()))
+        .insert_resource(difficulty);
+    let mut sim = Simulation::from_app(app);
+    sim.add_bot(bot_difficulty);
+
+    let mut stats = RunStats {
+        seed,
+        difficulty,
+        ..default()
+    };
+    let mut collected_reader = ManualEventReader::<CrystalCollected>::default();
+    let mut target_reader = ManualEventReader::<TargetReached>::default();
+    let mut last_target_time = 0.;
+
+    while sim.world().resource::<GameClock>().elapsed < seconds {
+        let target = sim.world().resource::<TargetColor>().0;
+        sim.step();
+
+        let world = sim.world();
+        let elapsed = world.resource::<GameClock>().elapsed;
+
+        for event in collected_reader.iter(world.resource::<Events<CrystalCollected>>()) {
+            stats.collected += 1;
+            let before = event.from.map_or(i8::MAX, |c| c.steps_to(&target).abs());
+            if event.to.steps_to(&target).abs() >= before {
+                stats.wasted += 1;
+            }
+        }
+
+        for event in target_reader.iter(world.resource::<Events<TargetReached>>()) {
+            stats.score = event.score;
+            stats.time_to_target.push(elapsed - last_target_time);
+            last_target_time = elapsed;
+        }
+    }
+
+    stats
+}
+
+/// Distribution of results over many runs with the same settings.
+#[derive(Debug, Clone, Default)]
+pub struct Summary {
+    pub runs: usize,
+    pub score_mean: f64,
+    pub score_p10: u32,
+    pub score_p50: u32,
+    pub score_p90: u32,
+    pub time_to_target_mean: f64,
+    pub collected_mean: f64,
+    pub wasted_mean: f64,
+}
+
+impl Summary {
+    pub fn from_runs(runs: &[RunStats]) -> Self {
+        if runs.is_empty() {
+            return Self::default();
+        }
+
+        let n = runs.len() as f64;
+        let mut scores: Vec<u32> = runs.iter().map(|run| run.score).collect();
+        scores.sort_unstable();
+        let percentile = |p: f64| scores[((scores.len() - 1) as f64 * p).round() as usize];
+
+        let times: Vec<f64> = runs
+            .iter()
+            .flat_map(|run| run.time_to_target.iter().copied())
+            .collect();
+        let time_to_target_mean = if times.is_empty() {
+            f64::NAN
+        } else {
+            times.iter().sum::<f64>() / times.len() as f64
+        };
+
+        Self {
+            runs: runs.len(),
+            score_mean: scores.iter().map(|score| *score as f64).sum::<f64>() / n,
+            score_p10: percentile(0.1),
+            score_p50: percentile(0.5),
+            score_p90: percentile(0.9),
+            time_to_target_mean,
+            collected_mean: runs.iter().map(|run| run.collected as f64).sum::<f64>() / n,
+            wasted_mean: runs.iter().map(|run| run.wasted as f64).sum::<f64>() / n,
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use bevy::{math::Vec3Swizzles, prelude::*};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    bump::{BumpSystem, MapConfig, MovementMode, TargetColor},
+    core::{
+        crystal::{CombineRule, Crystal, CrystalColor},
+        player::Player,
+        timestep::{FixedUpdateAppExt, GameClock},
+    },
+};
+
+/// How far the player can be expected to travel vertically per second when planning intercepts.
+const BOT_VERTICAL_SPEED: f32 = 5.;
+/// Only the closest crystals ahead are considered, keeping planning cheap at any depth.
+const BOT_CANDIDATES: usize = 8;
+/// Half the height of the player, so near misses still count as reachable.
+const BOT_REACH: f32 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BotDifficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl BotDifficulty {
+    /// Seconds between decisions.
+    pub fn reaction_delay(self) -> f64 {
+        match self {
+            BotDifficulty::Easy => 0.6,
+            BotDifficulty::Normal => 0.3,
+            BotDifficulty::Hard => 0.1,
+        }
+    }
+
+    /// Number of crystals planned ahead when picking the next one.
+    pub fn planning_depth(self) -> usize {
+        match self {
+            BotDifficulty::Easy => 1,
+            BotDifficulty::Normal => 2,
+            BotDifficulty::Hard => 3,
+        }
+    }
+}
+
+/// Drives the `Player` it is attached to in place of input.
+#[derive(Component)]
+pub struct Bot {
+    pub difficulty: BotDifficulty,
+    pub chasing: Option<Entity>,
+    next_decision: f64,
+}
+
+impl Bot {
+    pub fn new(difficulty: BotDifficulty) -> Self {
+        Self {
+            difficulty,
+            chasing: None,
+            next_decision: 0.,
+        }
+    }
+}
+
+pub struct BotPlugin;
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_fixed_system(bot_control.before(BumpSystem::Movement));
+    }
+}
+
+/// A crystal the player could meet, as seen at decision time.
+struct Candidate {
+    entity: Entity,
+    color: CrystalColor,
+    position: Vec2,
+    intercept_time: f32,
+}
+
+/// How much closer to `target` a color is than `from`. Reaching the target is worth extra.
+fn progress(from: Option<CrystalColor>, to: CrystalColor, target: CrystalColor) -> f32 {
+    let max_distance = 7.;
+    let before = from.map_or(max_distance, |c| c.steps_to(&target).abs() as f32);
+    let after = to.steps_to(&target).abs() as f32;
+
+    if after == 0. {
+        before + 5.
+    } else {
+        before - after
+    }
+}
+
+/// Best total progress achievable by collecting up to `depth` more crystals, in intercept order.
+fn plan(
+    candidates: &[Candidate],
+    rule: CombineRule,
+    color: Option<CrystalColor>,
+    target: CrystalColor,
+    position: Vec2,
+    time: f32,
+    depth: usize,
+) -> Option<(usize, f32)> {
+    if depth == 0 {
+        return None;
+    }
+
+    let mut best: Option<(usize, f32)> = None;
+    for (i, candidate) in candidates.iter().enumerate() {
+        let dt = candidate.intercept_time - time;
+        let reachable =
+            (candidate.position.y - position.y).abs() <= BOT_VERTICAL_SPEED * dt + BOT_REACH;
+        if dt <= 0. || !reachable {
+            continue;
+        }
+
+        let next_color = rule.apply(color, candidate.color);
+        // Sooner progress is worth more than the same progress later
+        let mut value = progress(color, next_color, target) / (1. + dt);
+        if next_color != target {
+            if let Some((_, future)) = plan(
+                &candidates[i + 1..],
+                rule,
+                Some(next_color),
+                target,
+                candidate.position,
+                candidate.intercept_time,
+                depth - 1,
+            ) {
+                value += future.max(0.) * 0.8;
+            }
+        }
+
+        if best.is_none_or(|(_, best_value)| value > best_value) {
+            best = Some((i, value));
+        }
+    }
+
+    best
+}
+
+#[allow(clippy::type_complexity)]
+fn bot_control(
+    mut bots: Query<(&Transform, &Velocity, &mut Player, &mut Bot), Without<Crystal>>,
+    crystals: Query<(Entity, &Transform, &Velocity, &Crystal), Without<Player>>,
+    target_color: Res<TargetColor>,
+    movement_mode: Res<MovementMode>,
+    map_config: Res<MapConfig>,
+    clock: Res<GameClock>,
+) {
+    for (transform, velocity, mut player, mut bot) in &mut bots {
+        let position = transform.translation.xy();
+
+        // Once the chased crystal is gone, wait for the next decision like a person would
+        let chased_is_valid = bot
+            .chasing
+            .and_then(|entity| crystals.get(entity).ok())
+            .is_some_and(|(_, _, _, crystal)| !crystal.collected);
+        if !chased_is_valid {
+            bot.chasing = None;
+        }
+
+        if clock.elapsed >= bot.next_decision {
+            bot.next_decision = clock.elapsed + bot.difficulty.reaction_delay();
+
+            let closing_speed = match *movement_mode {
+                MovementMode::Flap => 0.,
+                MovementMode::Free => 4.,
+            };
+            let mut candidates: Vec<Candidate> = crystals
+                .iter()
+                .filter(|(_, _, _, crystal)| !crystal.collected)
+                .filter_map(|(entity, crystal_transform, crystal_velocity, crystal)| {
+                    let crystal_position = crystal_transform.translation.xy();
+                    let speed = -crystal_velocity.linvel.x + closing_speed;
+                    let dx = crystal_position.x - position.x;
+                    if dx < 0. || speed <= 0. {
+                        return None;
+                    }
+                    Some(Candidate {
+                        entity,
+                        color: crystal.crystal_color,
+                        position: crystal_position,
+                        intercept_time: dx / speed,
+                    })
+                })
+                .collect();
+            candidates.sort_by(|a, b| a.intercept_time.total_cmp(&b.intercept_time));
+            candidates.truncate(BOT_CANDIDATES);
+
+            bot.chasing = plan(
+                &candidates,
+                map_config.combine_rule,
+                player.color,
+                target_color.0,
+                position,
+                0.,
+                bot.difficulty.planning_depth(),
+            )
+            .filter(|(_, value)| *value > 0.)
+            .map(|(i, _)| candidates[i].entity);
+        }
+
+        // Steer toward the chosen crystal, or hover in the middle of the map while waiting
+        let goal = bot
+            .chasing
+            .and_then(|entity| crystals.get(entity).ok())
+            .map_or(Vec2::new(position.x, 0.), |(_, crystal_transform, _, _)| {
+                crystal_transform.translation.xy()
+            });
+
+        // Flap whenever we'd otherwise end up below the goal shortly. Decided on every step,
+        // as input is held for all the steps of a frame
+        let predicted_y = position.y + velocity.linvel.y * 0.25;
+        player.movement_dir.y = if predicted_y < goal.y { 1. } else { 0. };
+
+        if *movement_mode == MovementMode::Free {
+            player.movement_dir.x = ((goal.x - position.x) / 2.).clamp(-1., 1.);
+        }
+    }
+}
@@ -1,17 +1,48 @@
+use crate::hud::HudConfig;
+use crate::locale::Localized;
+use crate::obstacle::ObstacleConfig;
+use crate::tween::{Ease, Tween};
 use crate::core::{
-    crystal::{Crystal, CrystalBundle, CrystalColor},
-    platform::PlatformBundle,
+    crystal::{CombineRule, Crystal, CrystalBundle, CrystalColor, Placed, SpawnWeights},
+    event::{ColorChanged, CrystalCollected, PlayerOutOfBounds, ResetGame, TargetReached},
+    platform::{Platform, PlatformBundle, PlatformKind},
     player::{Player, PlayerBundle},
+    timestep::{FixedUpdateAppExt, GameClock},
 };
-use bevy::{math::Vec3Swizzles, prelude::*};
+use bevy::{ecs::system::SystemParam, math::Vec3Swizzles, prelude::*};
 use bevy_prototype_lyon::prelude::*;
 use bevy_rapier2d::prelude::*;
-use rand::seq::IteratorRandom;
-use std::f32::consts::SQRT_2;
+use bevy::reflect::TypeUuid;
+use rand::{rngs::StdRng, seq::IteratorRandom, Rng, SeedableRng};
+use serde::Deserialize;
+use std::{collections::VecDeque, f32::consts::SQRT_2, marker::PhantomData};
 use strum::IntoEnumIterator;
 
+/// Seconds the player takes to blend into a new color.
+const PLAYER_BLEND_TIME: f32 = 0.25;
+/// The most points a single target can score.
+const MAX_MULTIPLIER: u32 = 5;
+/// How fast a flapping player drifts back home after being pushed, in speed per unit away.
+const FLAP_HOME_RATE: f32 = 3.;
+
+/// Gameplay tuning. Loaded from `assets/tuning/default.tuning.ron` when running with assets,
+/// missing fields fall back to these defaults.
+#[derive(Debug, Clone, Deserialize, TypeUuid)]
+#[uuid = "46b64e41-9cae-4f0c-881f-a24240fdeac7"]
+#[serde(default)]
 pub struct MapConfig {
     pub map_size: Vec2,
+    pub crystal_count: usize,
+    pub spawn_weights: SpawnWeights,
+    pub combine_rule: CombineRule,
+    pub obstacles: ObstacleConfig,
+    pub border: BorderKind,
+    /// How many wedges away from the last target a new target may be picked.
+    pub min_target_distance: i8,
+    pub max_target_distance: i8,
+    pub player_gravity: f32,
+    pub flap_impulse: f32,
+    pub player_max_speed: f32,
     pub grid_width: f32,
     pub grid_color: Color,
     pub crystal_linvel: f32,
@@ -19,36 +50,71 @@ pub struct MapConfig {
     pub player_default_color: Color,
     pub player_outline_color: Color,
     pub background_speed: f32,
-    pub colorwheel_height: f32,
-    pub colorwheel_radius: f32,
     pub help_height: f32,
+    pub help_idle_delay: f32,
+    pub help_fade_in: f32,
+    pub help_fade_out: f32,
+    pub player_horizontal_speed: f32,
+    /// Seconds of horizontal velocity the camera leads the player by.
+    pub camera_lookahead: f32,
+    /// How far the camera leads toward the right, where the scroll brings crystals from.
+    pub camera_scroll_lead: f32,
+    /// Height of the view in world units, before zooming out for speed.
+    pub camera_view_height: f32,
+    /// Roughly how many seconds the camera takes to catch up with the player.
+    pub camera_smooth_time: f32,
+    /// How far the player can move up or down from the middle of the view before it follows.
+    pub camera_dead_zone: f32,
+    /// How much further out the view is at `player_max_speed`, as a fraction of its height.
+    pub camera_speed_zoom: f32,
+    pub hud: HudConfig,
 }
 
-const MAP_CONFIG: MapConfig = MapConfig {
-    map_size: Vec2::new(100., 32.),
-    grid_width: 0.05,
-    grid_color: Color::rgb(0.5, 0.5, 0.5),
-    crystal_linvel: 6.,
-    crystal_angvel: 4.,
-    player_default_color: Color::BLACK,
-    player_outline_color: Color::WHITE,
-    background_speed: 3.,
-    colorwheel_height: 8.,
-    colorwheel_radius: 2.,
-    help_height: 5.5,
-};
-const HMAP_SIZE: Vec2 = Vec2::new(MAP_CONFIG.map_size.x / 2., MAP_CONFIG.map_size.y / 2.);
+impl Default for MapConfig {
+    fn default() -> Self {
+        MapConfig {
+            map_size: Vec2::new(100., 32.),
+            crystal_count: 32,
+            spawn_weights: SpawnWeights::PRIMARY_ONLY,
+            combine_rule: CombineRule::Step,
+            obstacles: ObstacleConfig::default(),
+            border: BorderKind::Solid,
+            min_target_distance: 1,
+            max_target_distance: 6,
+            player_gravity: 0.5,
+            flap_impulse: 0.005,
+            player_max_speed: 15.,
+            grid_width: 0.05,
+            grid_color: Color::rgb(0.5, 0.5, 0.5),
+            crystal_linvel: 6.,
+            crystal_angvel: 4.,
+            player_default_color: Color::BLACK,
+            player_outline_color: Color::WHITE,
+            background_speed: 0.6,
+            help_height: 5.5,
+            help_idle_delay: 6.,
+            help_fade_in: 2.,
+            help_fade_out: 0.5,
+            player_horizontal_speed: 8.,
+            camera_lookahead: 0.25,
+            camera_scroll_lead: 2.,
+            camera_view_height: 21.,
+            camera_smooth_time: 0.2,
+            camera_dead_zone: 2.,
+            camera_speed_zoom: 0.25,
+            hud: HudConfig::default(),
+        }
+    }
+}
+
+impl MapConfig {
+    pub fn half_size(&self) -> Vec2 {
+        self.map_size / 2.
+    }
+}
 
 #[derive(Component)]
-struct VerticalLine;
-#[derive(Component)]
-struct ColorWheelWedge(CrystalColor);
-#[derive(Component)]
-struct ColorWheelIndicator;
-#[derive(Component)]
-struct ColorWheel;
-#[derive(Component)]
-struct ScoreText;
+struct Border;
 #[derive(Debug, PartialEq, Eq)]
 enum FadeDirection {
     Visible,
@@ -62,105 +128,211 @@ struct HelpText {
     pub fade_start_time: f64,
 }
 
-struct CurrentColor(Option<CrystalColor>);
-struct Score(u32);
+/// How the player is allowed to move.
+/// `Flap` is the original jam mode where only vertical movement is controlled. X isn't locked
+/// for it: walls and conveyors can push the player sideways, and `move_player` homes them back
+/// to their `Player::home_x` after.
+/// `Free` also steers sideways so crystals can be chased across the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovementMode {
+    Flap,
+    Free,
+}
+
+/// What the top and bottom borders of the map do to a player touching them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum BorderKind {
+    #[default]
+    Solid,
+    Bouncy,
+    /// Touching a border knocks the color out of the player, like running into an obstacle.
+    Damaging,
+}
+
+/// Whether a person is playing or editing, or the game is demoing itself behind the title screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    Attract,
+    Playing,
+    /// Building a level in the editor, with the simulation paused.
+    Editing,
+    /// Play-testing the level from the editor.
+    Testing,
+    /// Picking a campaign level, with the game demoing itself behind.
+    LevelSelect,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub enum BumpSystem {
+    Reset,
+    Movement,
+    Collision,
+    /// Obstacles and other things that change the player after crystals have been collected.
+    Hazard,
+    /// Picking the next target once the current one is reached.
+    Scoring,
+}
+
+/// The only source of randomness for the simulation, so a seed reproduces a whole run.
+pub struct GameRng(pub StdRng);
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        GameRng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng(StdRng::from_entropy())
+    }
+}
+
+pub struct CurrentColor(pub Option<CrystalColor>);
+pub struct Score(pub u32);
+/// Points the next target scores. Every target reached raises it, and losing the color drops it
+/// back to 1.
+pub struct Multiplier(pub u32);
 struct MostRecentMovement(Option<f64>);
-struct TargetColor(CrystalColor);
+pub struct TargetColor(pub CrystalColor);
+/// Targets chosen ahead of time, such as by a level. New targets are random once it's empty.
+#[derive(Default)]
+pub struct TargetQueue(pub VecDeque<CrystalColor>);
 
+/// The game simulation: player, crystals, scoring and input.
+/// Needs no window, renderer or assets so it can also run headless.
 pub struct BumpPlugin;
 
 impl Plugin for BumpPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Score(0))
+        app.init_resource::<MapConfig>()
+            .insert_resource(Score(0))
+            .insert_resource(Multiplier(1))
             .insert_resource(CurrentColor(None))
-            .insert_resource(TargetColor(CrystalColor::random_primary()))
+            .init_resource::<GameRng>()
+            // Rerolled from the GameRng during startup
+            .insert_resource(TargetColor(CrystalColor::Red))
+            .init_resource::<TargetQueue>()
             .insert_resource(MostRecentMovement(None))
+            .insert_resource(MovementMode::Flap)
+            .insert_resource(GameMode::Playing)
+            .add_fixed_event::<ResetGame>()
+            .add_fixed_event::<CrystalCollected>()
+            .add_fixed_event::<ColorChanged>()
+            .add_fixed_event::<TargetReached>()
+            .add_fixed_event::<PlayerOutOfBounds>()
             .add_startup_system(startup)
-            .add_startup_system(startup_colorwheel)
             .add_system(input_keyboard)
             .add_system(input_mouse)
             .add_system(input_touch)
-            .add_system(move_player.after(input_keyboard).after(input_touch))
-            .add_system(camera_follow)
-            .add_system(background_treadmill)
-            .add_system(crystal_treadmill)
-            .add_system(crystal_collision)
+            .add_system(input_gamepad)
+            .add_system(toggle_movement_mode)
+            .add_system_to_stage(CoreStage::Last, clear_movement)
             .add_system(colorizer)
-            .add_system(colorwheel_follow)
-            .add_system(colorwheel_indicator_update)
-            .add_system(colorwheel_wedge_update)
-            .add_system(update_score)
-            .add_system(update_help);
+            .add_system(sync_borders)
+            .add_system(sync_crystal_count)
+            .add_system(sync_player_gravity)
+            // Anything that changes the simulation runs on the fixed timestep
+            .add_fixed_system(
+                reset_game
+                    .label(BumpSystem::Reset)
+                    .before(BumpSystem::Movement)
+                    .before(BumpSystem::Collision),
+            )
+            .add_fixed_system(move_player.label(BumpSystem::Movement))
+            // Collected crystals are recycled on the step they were collected, for repeatable runs
+            .add_fixed_system(crystal_treadmill.after(BumpSystem::Collision))
+            .add_fixed_system(
+                crystal_collision
+                    .label(BumpSystem::Collision)
+                    .after(BumpSystem::Movement),
+            )
+            .add_fixed_system(
+                track_current_color
+                    .after(crystal_collision)
+                    .after(BumpSystem::Hazard),
+            )
+            // After the treadmill, as both draw from the GameRng
+            .add_fixed_system(
+                update_score
+                    .label(BumpSystem::Scoring)
+                    .after(track_current_color)
+                    .after(crystal_treadmill),
+            );
     }
 }
 
-fn startup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let size = MAP_CONFIG.map_size;
-    let hsize = size / 2.;
+/// The help text in the game world. The colorwheel and score are on the screen-space
+/// `HudPlugin`, the backdrop is `BackgroundPlugin` and the camera has its own `CameraPlugin`.
+pub struct BumpHudPlugin;
 
-    // Horizontal lines
-    for i in 0..=MAP_CONFIG.map_size.y as i32 {
-        commands.spawn_bundle(SpriteBundle {
-            transform: Transform::from_xyz(0., i as f32 - hsize.y, 10.),
-            sprite: Sprite {
-                color: Color::rgb(0.5, 0.5, 0.5),
-                custom_size: Some(Vec2::new(size.x, MAP_CONFIG.grid_width)),
-                ..default()
-            },
-            ..default()
-        });
+impl Plugin for BumpHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(startup_help)
+            .add_system(update_help);
     }
+}
 
-    // Vertical lines
-    for i in 0..=MAP_CONFIG.map_size.x as i32 {
-        commands
-            .spawn_bundle(SpriteBundle {
-                transform: Transform::from_xyz(i as f32 - hsize.x, 0., 10.),
-                sprite: Sprite {
-                    color: Color::rgb(0.5, 0.5, 0.5),
-                    custom_size: Some(Vec2::new(MAP_CONFIG.grid_width, size.y)),
-                    ..default()
-                },
-                ..default()
-            })
-            .insert(VerticalLine {});
-    }
+fn startup(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    mut target_color: ResMut<TargetColor>,
+    map_config: Res<MapConfig>,
+) {
+    let size = map_config.map_size;
+    let hsize = size / 2.;
+    let rng = &mut rng.0;
+
+    *target_color = TargetColor(CrystalColor::random_primary_with(rng));
 
     // Create a player
     commands
         .spawn_bundle(
             PlayerBundle::from_shape(4, 1.5 * SQRT_2)
                 .with_color(
-                    MAP_CONFIG.player_default_color,
-                    MAP_CONFIG.player_outline_color,
+                    map_config.player_default_color,
+                    map_config.player_outline_color,
                 )
                 .with_position(-0.5, 0.5)
-                .with_gravity(0.5),
+                .with_gravity(map_config.player_gravity),
         )
-        .insert(LockedAxes::ROTATION_LOCKED | LockedAxes::TRANSLATION_LOCKED_X);
+        .insert(LockedAxes::ROTATION_LOCKED);
 
     // Spawn a containment cell
     let border_color = Color::rgb(0.8, 0.8, 0.8);
-    commands.spawn_bundle(
-        PlatformBundle::default()
-            .with_color(border_color)
-            .with_position(0., -hsize.y)
-            .with_size(size.x + 1., 1.),
-    );
-    commands.spawn_bundle(
-        PlatformBundle::default()
-            .with_color(border_color)
-            .with_position(0., hsize.y)
-            .with_size(size.x + 1., 1.),
-    );
+    for y in [-hsize.y, hsize.y] {
+        commands
+            .spawn_bundle(
+                PlatformBundle::default()
+                    .with_color(border_color)
+                    .with_position(0., y)
+                    .with_size(size.x + 1., 1.),
+            )
+            .insert(Border);
+    }
 
     // Spawn crystals to collect
-    for _ in 0..32 {
-        let x = rand::random::<f32>() * size.x - hsize.x;
-        let y = rand::random::<f32>() * size.y - hsize.y;
-        commands.spawn_bundle(CrystalBundle::random_primary().with_position(x, y));
+    scatter_crystals(&mut commands, rng, &map_config, map_config.crystal_count);
+}
+
+/// Spawns `count` random crystals anywhere on the map.
+pub(crate) fn scatter_crystals(
+    commands: &mut Commands,
+    rng: &mut StdRng,
+    map_config: &MapConfig,
+    count: usize,
+) {
+    let size = map_config.map_size;
+    let hsize = size / 2.;
+    for _ in 0..count {
+        let x = rng.gen::<f32>() * size.x - hsize.x;
+        let y = rng.gen::<f32>() * size.y - hsize.y;
+        let color = CrystalColor::random_weighted(rng, &map_config.spawn_weights);
+        commands.spawn_bundle(CrystalBundle::default().with_color(color).with_position(x, y));
     }
+}
 
+fn startup_help(mut commands: Commands, asset_server: Res<AssetServer>) {
     // Spawn the help text
     let font = asset_server.load("fonts/Hind-Regular.otf");
     let text_style = TextStyle {
@@ -172,14 +344,163 @@ fn startup(mut commands: Commands, asset_server: Res<AssetServer>) {
     let score_scale = Vec3::splat(1. / 42.);
     commands
     .spawn_bundle(Text2dBundle {
-        text: Text::from_section("Touch/Click/Space to jump.\nCollect the hexes to\ntraverse the colorwheel\n and reach the highlighted segment.", text_style).with_alignment(TextAlignment {
+        text: Text::from_section("", text_style).with_alignment(TextAlignment {
             vertical: VerticalAlign::Top,
             horizontal: HorizontalAlign::Center,
         }),
         transform: Transform::from_translation(box_position).with_scale(score_scale),
         ..default()
     })
-    .insert(HelpText{fade_direction: FadeDirection::Visible, fade_start_time: 0.});
+    .insert(HelpText{fade_direction: FadeDirection::Visible, fade_start_time: 0.})
+    .insert(Localized("help"));
+}
+
+/// Per-run state that a reset puts back.
+#[derive(SystemParam)]
+struct RunState<'w, 's> {
+    score: ResMut<'w, Score>,
+    multiplier: ResMut<'w, Multiplier>,
+    current_color: ResMut<'w, CurrentColor>,
+    target_color: ResMut<'w, TargetColor>,
+    target_queue: ResMut<'w, TargetQueue>,
+    most_recent_movement: ResMut<'w, MostRecentMovement>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+/// Puts the run back to how `startup` left it, without respawning anything.
+#[allow(clippy::type_complexity)]
+fn reset_game(
+    mut reset_events: EventReader<ResetGame>,
+    mut players: Query<
+        (&mut Transform, &mut Velocity, &mut ExternalImpulse, &mut Player),
+        Without<Crystal>,
+    >,
+    mut crystals: Query<(&mut Transform, &mut Crystal), (Without<Player>, Without<Placed>)>,
+    mut run: RunState,
+    mut rng: ResMut<GameRng>,
+    map_config: Res<MapConfig>,
+) {
+    if reset_events.iter().count() == 0 {
+        return;
+    }
+    let hmap_size = map_config.half_size();
+    let rng = &mut rng.0;
+
+    *run.score = Score(0);
+    *run.multiplier = Multiplier(1);
+    *run.current_color = CurrentColor(None);
+    *run.target_color = TargetColor(CrystalColor::random_primary_with(rng));
+    run.target_queue.0.clear();
+    *run.most_recent_movement = MostRecentMovement(None);
+
+    for (mut transform, mut velocity, mut external_impulse, mut player) in &mut players {
+        transform.translation = Vec3::new(-0.5, 0.5, 100.);
+        transform.rotation = Quat::IDENTITY;
+        *velocity = Velocity::default();
+        *external_impulse = ExternalImpulse::default();
+        player.color = None;
+        player.action_down = false;
+        player.home_x = -0.5;
+    }
+
+    // Scatter the crystals over the whole map again
+    for (mut transform, mut crystal) in &mut crystals {
+        transform.translation.x = rng.gen::<f32>() * map_config.map_size.x - hmap_size.x;
+        transform.translation.y = rng.gen::<f32>() * map_config.map_size.y - hmap_size.y;
+        transform.rotation = Quat::IDENTITY;
+        crystal.crystal_color = CrystalColor::random_weighted(rng, &map_config.spawn_weights);
+        crystal.collected = false;
+    }
+}
+
+/// Fits the borders to the map and applies the configured `BorderKind`,
+/// including after a tuning or difficulty change.
+fn sync_borders(
+    mut borders: Query<
+        (
+            &mut Platform,
+            &mut Restitution,
+            &mut Transform,
+            &mut Sprite,
+            &mut Collider,
+        ),
+        With<Border>,
+    >,
+    map_config: Res<MapConfig>,
+) {
+    if !map_config.is_changed() {
+        return;
+    }
+
+    let size = map_config.map_size;
+    for (mut platform, mut restitution, mut transform, mut sprite, mut collider) in &mut borders {
+        let (kind, bounce) = match map_config.border {
+            BorderKind::Solid => (PlatformKind::Solid, None),
+            BorderKind::Bouncy => (PlatformKind::Solid, Some(1.)),
+            BorderKind::Damaging => (PlatformKind::Damaging, None),
+        };
+        platform.kind = kind;
+        *restitution = match bounce {
+            Some(coefficient) => Restitution {
+                coefficient,
+                combine_rule: CoefficientCombineRule::Max,
+            },
+            None => Restitution::default(),
+        };
+
+        // The top border is the one above the middle
+        transform.translation.y = size.y / 2. * transform.translation.y.signum();
+        if sprite.custom_size != Some(Vec2::new(size.x + 1., 1.)) {
+            sprite.custom_size = Some(Vec2::new(size.x + 1., 1.));
+            *collider = Collider::cuboid((size.x + 1.) / 2., 0.5);
+        }
+    }
+}
+
+/// Spawns or despawns crystals until there are as many as the config asks for.
+fn sync_crystal_count(
+    mut commands: Commands,
+    crystals: Query<Entity, (With<Crystal>, Without<Placed>)>,
+    mut rng: ResMut<GameRng>,
+    map_config: Res<MapConfig>,
+) {
+    if !map_config.is_changed() {
+        return;
+    }
+
+    let count = crystals.iter().count();
+    if count > map_config.crystal_count {
+        for entity in crystals.iter().skip(map_config.crystal_count) {
+            commands.entity(entity).despawn_recursive();
+        }
+    } else {
+        // New crystals enter from the right edge like respawned ones
+        let hmap_size = map_config.half_size();
+        let rng = &mut rng.0;
+        for _ in count..map_config.crystal_count {
+            let color = CrystalColor::random_weighted(rng, &map_config.spawn_weights);
+            let y = rng.gen::<f32>() * map_config.map_size.y - hmap_size.y;
+            commands.spawn_bundle(
+                CrystalBundle::default()
+                    .with_color(color)
+                    .with_position(hmap_size.x, y),
+            );
+        }
+    }
+}
+
+fn sync_player_gravity(
+    map_config: Res<MapConfig>,
+    mut players: Query<&mut GravityScale, With<Player>>,
+) {
+    if !map_config.is_changed() {
+        return;
+    }
+
+    for mut gravity in &mut players {
+        gravity.0 = map_config.player_gravity;
+    }
 }
 
 // fn shutdown(mut commands: Commands) {}
@@ -190,6 +511,19 @@ pub fn input_keyboard(keys: Res<Input<KeyCode>>, mut player_query: Query<&mut Pl
             player.movement_dir.y = 1.;
         }
     }
+
+    let mut horizontal = 0.;
+    if keys.any_pressed([KeyCode::Left, KeyCode::A]) {
+        horizontal -= 1.;
+    }
+    if keys.any_pressed([KeyCode::Right, KeyCode::D]) {
+        horizontal += 1.;
+    }
+    if horizontal != 0. {
+        for mut player in player_query.iter_mut() {
+            player.movement_dir.x = horizontal;
+        }
+    }
 }
 
 pub fn input_mouse(mouse: Res<Input<MouseButton>>, mut player_query: Query<&mut Player>) {
@@ -214,43 +548,126 @@ pub fn input_touch(touches: Res<Touches>, mut player_query: Query<&mut Player>)
     }
 }
 
+pub fn input_gamepad(
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    buttons: Res<Input<GamepadButton>>,
+    mut player_query: Query<&mut Player>,
+) {
+    // Small dead zone so a resting stick doesn't drift the player
+    let dead_zone = 0.15;
+
+    for gamepad in gamepads.iter() {
+        let jump = buttons.pressed(GamepadButton::new(*gamepad, GamepadButtonType::South));
+        let stick_x = axes
+            .get(GamepadAxis::new(*gamepad, GamepadAxisType::LeftStickX))
+            .unwrap_or(0.);
+
+        for mut player in player_query.iter_mut() {
+            if jump {
+                player.movement_dir.y = 1.;
+            }
+            if stick_x.abs() > dead_zone {
+                player.movement_dir.x = stick_x;
+            }
+        }
+    }
+}
+
+/// Switches between flapping and free movement with the F key, only while playing.
+fn toggle_movement_mode(
+    keys: Res<Input<KeyCode>>,
+    game_mode: Res<GameMode>,
+    mut movement_mode: ResMut<MovementMode>,
+) {
+    if *game_mode != GameMode::Playing || !keys.just_pressed(KeyCode::F) {
+        return;
+    }
+
+    *movement_mode = match *movement_mode {
+        MovementMode::Flap => MovementMode::Free,
+        MovementMode::Free => MovementMode::Flap,
+    };
+    info!("Movement mode: {:?}", *movement_mode);
+}
+
+/// Input is set once a frame and held for every step of it, then let go once a frame has run
+/// steps, so a held flap pushes on every step whatever the frame rate. A held touch keeps it.
+fn clear_movement(
+    mut player_query: Query<&mut Player>,
+    mut last_ticks: Local<u64>,
+    clock: Res<GameClock>,
+) {
+    if clock.ticks == *last_ticks {
+        return;
+    }
+    *last_ticks = clock.ticks;
+
+    for mut player in &mut player_query {
+        if !player.action_down {
+            player.movement_dir = Vec2::ZERO;
+        }
+    }
+}
+
 fn move_player(
     mut player_query: Query<(
+        Entity,
         &mut Transform,
         &mut Velocity,
         &mut ExternalImpulse,
-        &mut Player,
+        &Player,
     )>,
     mut most_recent_movement: ResMut<MostRecentMovement>,
-    time: Res<Time>,
+    movement_mode: Res<MovementMode>,
+    clock: Res<GameClock>,
+    mut out_of_bounds_events: EventWriter<PlayerOutOfBounds>,
+    map_config: Res<MapConfig>,
 ) {
-    // TODO: Move the magic constants to a Bump game config
-    for (mut transform, mut velocity, mut external_impulse, mut player) in &mut player_query {
-        let move_speed = 0.005;
-        let mut move_delta = player.movement_dir;
-        if !player.action_down {
-            player.movement_dir = Vec2::ZERO;
-        }
-
+    let hmap_size = map_config.half_size();
+    for (entity, mut transform, mut velocity, mut external_impulse, player) in
+        &mut player_query
+    {
+        let move_delta = player.movement_dir;
         if move_delta.length() > 0. {
-            *most_recent_movement = MostRecentMovement(Some(time.seconds_since_startup()));
+            *most_recent_movement = MostRecentMovement(Some(clock.elapsed));
         }
 
-        move_delta = move_delta.normalize_or_zero() * move_speed;
-
-        external_impulse.impulse = move_delta;
+        // Flapping is always an upward impulse, horizontal movement steers directly
+        external_impulse.impulse =
+            Vec2::new(0., move_delta.y.clamp(0., 1.)) * map_config.flap_impulse;
+        let horizontal_speed = map_config.player_horizontal_speed;
+        velocity.linvel.x = match *movement_mode {
+            MovementMode::Free => move_delta.x.clamp(-1., 1.) * horizontal_speed,
+            // Set every step, so a wall in the way wins and carries the player along
+            MovementMode::Flap => ((player.home_x - transform.translation.x) * FLAP_HOME_RATE)
+                .clamp(-horizontal_speed, horizontal_speed),
+        };
         transform.rotation = Quat::IDENTITY;
 
         // Clamp the linear velocity
-        let max_speed = 15.;
+        let max_speed = map_config.player_max_speed;
         velocity.linvel = velocity.linvel.clamp(
             Vec2::new(-max_speed, -max_speed),
             Vec2::new(max_speed, max_speed),
         );
 
+        // In free mode the player can roam, but is kept within the horizontal extents of the map
+        if *movement_mode == MovementMode::Free {
+            let max_x = hmap_size.x - 1.;
+            if transform.translation.x.abs() > max_x {
+                transform.translation.x = transform.translation.x.clamp(-max_x, max_x);
+                velocity.linvel.x = 0.;
+            }
+        }
+
         let cur_pos = transform.translation.xy();
         // If the player is outside the map, move them back to 0,0, clear impulse/velocities
-        if cur_pos.x.abs() > HMAP_SIZE.x || cur_pos.y.abs() > HMAP_SIZE.y {
+        if cur_pos.x.abs() > hmap_size.x || cur_pos.y.abs() > hmap_size.y {
+            out_of_bounds_events.send(PlayerOutOfBounds {
+                player: entity,
+                position: cur_pos,
+            });
             transform.translation = Vec3::new(0., 0., 100.);
             transform.rotation = Quat::IDENTITY;
             external_impulse.impulse = Vec2::ZERO;
@@ -261,54 +678,51 @@ fn move_player(
     }
 }
 
-fn camera_follow(
-    player_query: Query<(&Transform, &Player)>,
-    mut camera_query: Query<&mut Transform, (With<Camera>, Without<Player>)>,
-) {
-    for (player_transform, _) in &player_query {
-        let pos = player_transform.translation;
-
-        for mut transform in &mut camera_query {
-            transform.translation.x = pos.x;
-            transform.translation.y = pos.y;
-        }
-    }
-}
-
-fn background_treadmill(mut background_query: Query<(&mut Transform, &VerticalLine)>) {
-    for (mut transform, _) in &mut background_query {
-        transform.translation.x -= 0.01;
-        if transform.translation.x < -HMAP_SIZE.x {
-            transform.translation.x += HMAP_SIZE.x * 2.;
-        }
-    }
-}
-
+#[allow(clippy::type_complexity)]
 fn crystal_treadmill(
-    mut crystal_query: Query<(&mut Transform, &mut Velocity, &mut Crystal, &mut DrawMode)>,
+    mut commands: Commands,
+    mut crystal_query: Query<(
+        Entity,
+        &mut Transform,
+        &mut Velocity,
+        &mut Crystal,
+        &mut DrawMode,
+        Option<&Placed>,
+    )>,
+    mut rng: ResMut<GameRng>,
+    map_config: Res<MapConfig>,
 ) {
-    let map_size = MAP_CONFIG.map_size;
+    let map_size = map_config.map_size;
     let hmap_size = map_size / 2.;
+    let rng = &mut rng.0;
 
-    for (mut transform, mut velocity, mut crystal, mut draw_mode) in &mut crystal_query {
+    for (entity, mut transform, mut velocity, mut crystal, mut draw_mode, placed) in
+        &mut crystal_query
+    {
         let cur_pos = transform.translation.xy();
 
         // Crystals have a constant left to right velocity.
         // Crystals have a constant angular velocity so they look cool :)
-        velocity.linvel = Vec2::new(-MAP_CONFIG.crystal_linvel, 0.);
-        velocity.angvel = MAP_CONFIG.crystal_angvel; // Dancing and twirling... Dancing and twirling...
+        velocity.linvel = Vec2::new(-map_config.crystal_linvel * crystal.kind.speed(), 0.);
+        velocity.angvel = map_config.crystal_angvel; // Dancing and twirling... Dancing and twirling...
+
+        let gone = cur_pos.x < -hmap_size.x || crystal.collected;
+        if gone && placed.is_some() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
 
         // When they leave the playfield, they are moved to the other side
         // and their color is randomized again.
-        if cur_pos.x < -hmap_size.x || crystal.collected {
+        if gone {
             transform.translation.x = hmap_size.x;
             // Further offset the position randomly to avoid patterns
-            transform.translation.x += rand::random::<f32>() * 4.;
-            transform.translation.y = rand::random::<f32>() * map_size.y - hmap_size.y;
+            transform.translation.x += rng.gen::<f32>() * 4.;
+            transform.translation.y = rng.gen::<f32>() * map_size.y - hmap_size.y;
 
             transform.rotation = Quat::IDENTITY;
 
-            crystal.crystal_color = CrystalColor::random_primary();
+            crystal.crystal_color = CrystalColor::random_weighted(rng, &map_config.spawn_weights);
             *draw_mode = crystal.crystal_color.to_draw_mode();
 
             crystal.collected = false;
@@ -323,8 +737,10 @@ fn crystal_treadmill(
 fn crystal_collision(
     mut collision_events: EventReader<CollisionEvent>,
     mut players: Query<&mut Player>,
-    mut crystals: Query<&mut Crystal>,
-    mut current_color: ResMut<CurrentColor>,
+    mut crystals: Query<(&mut Crystal, &Transform)>,
+    mut collected_events: EventWriter<CrystalCollected>,
+    mut color_changed_events: EventWriter<ColorChanged>,
+    map_config: Res<MapConfig>,
 ) {
     for event in collision_events.iter() {
         if let CollisionEvent::Started(entity_a, entity_b, _) = &event {
@@ -337,43 +753,77 @@ fn crystal_collision(
                 continue;
             };
             if let Ok(mut player) = players.get_mut(player_entity) {
-                if let Ok(mut crystal) = crystals.get_mut(crystal_entity) {
+                if let Ok((mut crystal, transform)) = crystals.get_mut(crystal_entity) {
                     // Player and crystal are touching, change the player's color
-                    match player.color {
-                        Some(color) => {
-                            player.color = Some(color.combine(&crystal.crystal_color));
-                        }
-                        None => {
-                            player.color = Some(crystal.crystal_color);
-                        }
-                    }
+                    let from = player.color;
+                    let to = map_config.combine_rule.apply(from, crystal.crystal_color);
+                    player.color = Some(to);
 
                     // Don't actually despawn, just mark as collected and let the treadmill handle it
                     crystal.collected = true;
 
-                    // Update the current color
-                    *current_color = CurrentColor(player.color);
+                    collected_events.send(CrystalCollected {
+                        player: player_entity,
+                        crystal: crystal_entity,
+                        crystal_color: crystal.crystal_color,
+                        position: transform.translation.xy(),
+                        from,
+                        to,
+                    });
+                    if from != Some(to) {
+                        color_changed_events.send(ColorChanged {
+                            player: player_entity,
+                            from,
+                            to: Some(to),
+                        });
+                    }
                 }
             }
         }
     }
 }
 
-/// Loops through the player/crystals and sets the draw mode to match the corresponding color
+/// Loops through the player/crystals and sets the draw mode to match the corresponding color.
+/// The player blends into a new color through the hues in between instead of snapping to it.
+#[allow(clippy::type_complexity)]
 fn colorizer(
-    mut player_query: Query<(&mut DrawMode, &Player, Without<Crystal>)>,
+    mut commands: Commands,
+    mut player_query: Query<(
+        Entity,
+        &mut DrawMode,
+        &Player,
+        Option<&mut Tween<Color>>,
+        Without<Crystal>,
+    )>,
     mut crystal_query: Query<(&mut DrawMode, &Crystal, Without<Player>)>,
+    map_config: Res<MapConfig>,
+    time: Res<Time>,
 ) {
-    for (mut draw_mode, player, _) in &mut player_query {
-        let color = match player.color {
+    let now = time.seconds_since_startup();
+    for (entity, mut draw_mode, player, tween, _) in &mut player_query {
+        let target = match player.color {
             Some(crystal_color) => crystal_color.to_color(),
-            None => MAP_CONFIG.player_default_color,
+            None => map_config.player_default_color,
+        };
+        let color = match tween {
+            Some(mut tween) => {
+                if tween.to != target {
+                    tween.retarget(target, now);
+                }
+                tween.value(now)
+            }
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(Tween::at(target, PLAYER_BLEND_TIME).with_ease(Ease::Out));
+                target
+            }
         };
 
         *draw_mode = DrawMode::Outlined {
             fill_mode: bevy_prototype_lyon::prelude::FillMode::color(color),
             outline_mode: StrokeMode::new(
-                MAP_CONFIG.player_outline_color,
+                map_config.player_outline_color,
                 crate::core::player::PLAYER_OUTLINE_WIDTH,
             ),
         }
@@ -384,237 +834,91 @@ fn colorizer(
     }
 }
 
-fn startup_colorwheel(mut commands: Commands, asset_server: Res<AssetServer>) {
-    // Use the shape plugin to draw a color wheel, coloring each of the wheel segments
-    //  by iterating through the CrystalColor enum.
-    let radius = MAP_CONFIG.colorwheel_radius;
-    let height = MAP_CONFIG.colorwheel_height;
-    let current_color = CrystalColor::Orange;
-
-    commands
-        .spawn()
-        .insert(ColorWheel {})
-        .insert(Transform::from_xyz(0., height, 90.))
-        .insert(GlobalTransform::default())
-        .insert(Visibility::default())
-        .insert(ComputedVisibility::default())
-        .with_children(|parent| {
-            parent.spawn_bundle(GeometryBuilder::build_as(
-                &shapes::RegularPolygon {
-                    sides: CrystalColor::iter().count(),
-                    feature: shapes::RegularPolygonFeature::Radius(radius),
-                    ..default()
-                },
-                DrawMode::Outlined {
-                    fill_mode: bevy_prototype_lyon::prelude::FillMode::color(Color::rgb(
-                        0.1, 0.1, 0.1,
-                    )),
-                    outline_mode: StrokeMode::new(Color::WHITE, 0.05),
-                },
-                Transform::from_xyz(0., 0., 0.).with_rotation(Quat::from_rotation_z(
-                    std::f32::consts::PI / CrystalColor::iter().count() as f32,
-                )),
-            ));
-
-            let font = asset_server.load("fonts/Hind-Regular.otf");
-            let text_style = TextStyle {
-                font,
-                font_size: 72.0,
-                color: Color::WHITE,
-            };
-
-            let box_position = Vec3::new(0., 0.05, 5.);
-            let score_scale = Vec3::splat(1. / 42.);
-            parent
-                .spawn_bundle(Text2dBundle {
-                    text: Text::from_section("00", text_style).with_alignment(TextAlignment {
-                        vertical: VerticalAlign::Center,
-                        horizontal: HorizontalAlign::Center,
-                    }),
-                    transform: Transform::from_translation(box_position).with_scale(score_scale),
-                    ..default()
-                })
-                .insert(ScoreText);
-
-            for (i, color) in CrystalColor::iter().enumerate() {
-                let angle1 =
-                    i as f32 * 2. * std::f32::consts::PI / CrystalColor::iter().count() as f32;
-
-                let angle2 = (i + 1) as f32 * 2. * std::f32::consts::PI
-                    / CrystalColor::iter().count() as f32;
-                let outside1 = Vec2::new(angle1.cos(), angle1.sin()) * radius;
-                let outside2 = Vec2::new(angle2.cos(), angle2.sin()) * radius;
-                let inside1 = outside1 * 0.5;
-                let inside2 = outside2 * 0.5;
-
-                let shape = shapes::Polygon {
-                    points: vec![outside1, outside2, inside2, inside1],
-                    closed: true,
-                };
-
-                parent
-                    .spawn_bundle(GeometryBuilder::build_as(
-                        &shape,
-                        DrawMode::Outlined {
-                            fill_mode: bevy_prototype_lyon::prelude::FillMode::color(
-                                color.to_color(),
-                            ),
-                            outline_mode: StrokeMode::new(Color::WHITE, 0.05),
-                        },
-                        Transform::from_xyz(0., 0., 1.),
-                    ))
-                    .insert(ColorWheelWedge(color));
-            }
-
-            // Draw the current color indicator
-            let angle = (current_color as usize as f32 + 0.5) * 2. * std::f32::consts::PI
-                / CrystalColor::iter().count() as f32;
-            let x = angle.cos() * radius * 0.75;
-            let y = angle.sin() * radius * 0.75;
-            parent
-                .spawn_bundle(GeometryBuilder::build_as(
-                    &shapes::RegularPolygon {
-                        sides: 4,
-                        feature: shapes::RegularPolygonFeature::Radius(0.2),
-                        ..default()
-                    },
-                    DrawMode::Outlined {
-                        fill_mode: bevy_prototype_lyon::prelude::FillMode::color(
-                            current_color.to_color(),
-                        ),
-                        outline_mode: StrokeMode::new(Color::WHITE, 0.05),
-                    },
-                    Transform::from_xyz(x, y, 3.),
-                ))
-                .insert(ColorWheelIndicator {});
-        });
-}
-
-fn colorwheel_follow(
-    mut colorwheels: Query<(&mut Transform, &ColorWheel, Without<Player>)>,
-    players: Query<(&Transform, &Player, Without<ColorWheel>)>,
-) {
-    for (mut transform, _, _) in colorwheels.iter_mut() {
-        for (player_transform, _, _) in players.iter() {
-            transform.translation =
-                player_transform.translation + Vec3::new(0., MAP_CONFIG.colorwheel_height, 0.);
-        }
-    }
-}
-
-fn colorwheel_wedge_update(
-    mut colorwheel_wedges: Query<(&ColorWheelWedge, &mut Transform, &mut DrawMode)>,
-    current_color: Res<CurrentColor>,
-    target_color: Res<TargetColor>,
+/// Mirrors the latest player color into the `CurrentColor` resource.
+fn track_current_color(
+    mut color_changed_events: EventReader<ColorChanged>,
+    mut current_color: ResMut<CurrentColor>,
 ) {
-    for (wedge, mut transform, mut draw_mode) in colorwheel_wedges.iter_mut() {
-        let is_target = wedge.0 == target_color.0;
-        let is_current = Some(wedge.0) == current_color.0;
-        let mut alpha = 0.1;
-        if wedge.0.is_primary() {
-            alpha = 0.4;
-        }
-
-        if is_target {
-            alpha = 1.;
-        }
-
-        if is_current {
-            alpha = 0.8;
-        }
-
-        let mut fill_color = wedge.0.to_color();
-        fill_color.set_a(alpha);
-
-        let border_color = match is_target {
-            true => Color::WHITE,
-            false => wedge.0.to_color() * 0.5,
-        };
-
-        transform.translation = match is_target {
-            true => Vec3::new(0., 0., 2.),
-            false => Vec3::new(0., 0., 1.),
-        };
-
-        *draw_mode = DrawMode::Outlined {
-            fill_mode: bevy_prototype_lyon::prelude::FillMode::color(fill_color),
-            outline_mode: StrokeMode::new(border_color, 0.05),
-        };
+    for event in color_changed_events.iter() {
+        *current_color = CurrentColor(event.to);
     }
 }
 
-fn colorwheel_indicator_update(
-    mut colorwheel_indicator: Query<(
-        &mut Visibility,
-        &mut Transform,
-        &mut DrawMode,
-        &ColorWheelIndicator,
-    )>,
-    current_color: Res<CurrentColor>,
-) {
-    if let CurrentColor(Some(current_color)) = *current_color {
-        let angle = (current_color as usize as f32 + 0.5) * 2. * std::f32::consts::PI
-            / CrystalColor::iter().count() as f32;
-        let x = angle.cos() * 2. * 0.75;
-        let y = angle.sin() * 2. * 0.75;
-        for (mut visibility, mut transform, mut draw_mode, _) in colorwheel_indicator.iter_mut() {
-            transform.translation = Vec3::new(x, y, 3.);
-            *draw_mode = DrawMode::Outlined {
-                fill_mode: bevy_prototype_lyon::prelude::FillMode::color(current_color.to_color()),
-                outline_mode: StrokeMode::new(Color::WHITE, 0.05),
-            };
-            visibility.is_visible = true;
-        }
-    } else {
-        for (mut visibility, _, _, _) in colorwheel_indicator.iter_mut() {
-            visibility.is_visible = false;
-        }
-    }
+/// The score and what the next target is worth.
+#[derive(SystemParam)]
+struct Scoring<'w, 's> {
+    score: ResMut<'w, Score>,
+    multiplier: ResMut<'w, Multiplier>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
 }
 
+/// Scores whenever a player changes into the target color, then picks a new target.
 fn update_score(
-    mut score_text: Query<(&mut Text, &ScoreText)>,
-    mut score: ResMut<Score>,
+    mut color_changed_events: EventReader<ColorChanged>,
+    mut target_reached_events: EventWriter<TargetReached>,
+    mut scoring: Scoring,
     mut target_color: ResMut<TargetColor>,
-    current_color: Res<CurrentColor>,
+    mut target_queue: ResMut<TargetQueue>,
+    mut rng: ResMut<GameRng>,
+    map_config: Res<MapConfig>,
 ) {
-    if let CurrentColor(Some(current_color)) = *current_color {
-        if current_color == target_color.0 {
-            *score = Score(score.0 + 1);
-            let new_color = CrystalColor::iter()
-                .filter(|color| *color != target_color.0)
-                .choose(&mut rand::thread_rng())
-                .unwrap();
+    let target_distance = map_config.min_target_distance.max(1)..=map_config.max_target_distance;
+
+    for event in color_changed_events.iter() {
+        if event.to.is_none() {
+            *scoring.multiplier = Multiplier(1);
+        }
+        if event.to == Some(target_color.0) {
+            let points = scoring.multiplier.0;
+            *scoring.score = Score(scoring.score.0 + points);
+            *scoring.multiplier = Multiplier((points + 1).min(MAX_MULTIPLIER));
+            let old_color = target_color.0;
+            let new_color = target_queue.0.pop_front().unwrap_or_else(|| {
+                CrystalColor::iter()
+                    .filter(|color| target_distance.contains(&old_color.steps_to(color).abs()))
+                    .choose(&mut rng.0)
+                    .or_else(|| {
+                        CrystalColor::iter()
+                            .filter(|color| *color != old_color)
+                            .choose(&mut rng.0)
+                    })
+                    .unwrap()
+            });
+
+            target_reached_events.send(TargetReached {
+                player: event.player,
+                color: target_color.0,
+                next_target: new_color,
+                score: scoring.score.0,
+            });
             *target_color = TargetColor(new_color);
         }
     }
-
-    for (mut text, _) in score_text.iter_mut() {
-        text.sections[0].value = format!("{:02}", score.0);
-    }
 }
 
 fn update_help(
     mut help_text: Query<(&mut Transform, &mut Text, &mut HelpText, Without<Player>)>,
     players: Query<(&Transform, &Player, Without<HelpText>)>,
     most_recent_movement: Res<MostRecentMovement>,
-    time: Res<Time>,
+    clock: Res<GameClock>,
+    map_config: Res<MapConfig>,
 ) {
     for (mut transform, mut text, mut help_text, _) in help_text.iter_mut() {
         for (player_transform, _, _) in players.iter() {
             transform.translation =
-                player_transform.translation + Vec3::new(0., MAP_CONFIG.help_height, 200.);
+                player_transform.translation + Vec3::new(0., map_config.help_height, 200.);
         }
 
-        let dt = (time.seconds_since_startup() - most_recent_movement.0.unwrap_or(-100.)) as f32;
+        let dt = (clock.elapsed - most_recent_movement.0.unwrap_or(-100.)) as f32;
 
-        // If it has been more than 6 seconds since the last movement, fade in the help text over 2 seconds
-        // After a movement, fade out the text over half a second
-        let state = if dt > 8. {
+        // After the player has been idle for a while, fade in the help text
+        // After a movement, quickly fade it back out
+        let state = if dt > map_config.help_idle_delay + map_config.help_fade_in {
             FadeDirection::Visible
-        } else if dt > 6. {
+        } else if dt > map_config.help_idle_delay {
             FadeDirection::FadingIn
-        } else if dt > 0.5 {
+        } else if dt > map_config.help_fade_out {
             FadeDirection::NotVisible
         } else {
             FadeDirection::FadingOut
@@ -633,19 +937,19 @@ fn update_help(
         if (state == FadeDirection::FadingIn
             || state == FadeDirection::FadingOut) && help_text.fade_direction != state
         {
-            help_text.fade_start_time = time.seconds_since_startup();
+            help_text.fade_start_time = clock.elapsed;
         }
         help_text.fade_direction = state;
 
         let color = &mut text.sections[0].style.color;
 
-        let anim_dt = (time.seconds_since_startup() - help_text.fade_start_time) as f32;
+        let anim_dt = (clock.elapsed - help_text.fade_start_time) as f32;
 
         let alpha = match help_text.fade_direction {
             FadeDirection::Visible => 1.,
-            FadeDirection::FadingIn => anim_dt / 2.,
+            FadeDirection::FadingIn => anim_dt / map_config.help_fade_in,
             FadeDirection::NotVisible => 0.,
-            FadeDirection::FadingOut => 1. - (anim_dt / 0.5),
+            FadeDirection::FadingOut => 1. - (anim_dt / map_config.help_fade_out),
         }
         .clamp(0., 1.);
 
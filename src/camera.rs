@@ -0,0 +1,230 @@
+//! The game camera: follows the player smoothly, leads into the scroll, zooms out with speed
+//! and never shows past the border platforms. The HUD has a camera of its own on top that
+//! stays put, so nothing done to the game camera moves it.
+
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::{camera::ScalingMode, view::RenderLayers},
+};
+use bevy_rapier2d::prelude::*;
+
+use crate::{bump::MapConfig, core::player::Player};
+
+/// Moves the camera along with the player. Anything nudging the camera, like screen shake,
+/// goes after it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub struct CameraFollow;
+
+/// The camera looking at the game world.
+#[derive(Component)]
+pub struct GameCamera;
+
+/// Draws the HUD over the game, a unit to a logical pixel with the middle of the window at
+/// the origin. Shapes it draws go on `HUD_LAYER`, UI nodes are drawn by it too.
+#[derive(Component)]
+pub struct HudCamera;
+
+/// The render layer only the `HudCamera` sees.
+pub const HUD_LAYER: u8 = 1;
+
+/// Puts a shape on the HUD rather than in the game world.
+pub fn hud_layer() -> RenderLayers {
+    RenderLayers::layer(HUD_LAYER)
+}
+
+/// Moves `current` toward `target` like a critically damped spring: as fast as possible
+/// without overshooting, taking roughly `smooth_time` seconds. `velocity` carries over
+/// between calls.
+pub fn smooth_damp(
+    current: f32,
+    target: f32,
+    velocity: &mut f32,
+    smooth_time: f32,
+    dt: f32,
+) -> f32 {
+    let smooth_time = smooth_time.max(0.0001);
+    let omega = 2. / smooth_time;
+    let x = omega * dt;
+    // Cheap approximation of exp(-x) that is accurate for the small steps of a frame
+    let decay = 1. / (1. + x + 0.48 * x * x + 0.235 * x * x * x);
+
+    let change = current - target;
+    let temp = (*velocity + omega * change) * dt;
+    *velocity = (*velocity - omega * temp) * decay;
+    let result = target + (change + temp) * decay;
+
+    // Don't overshoot when the step is large compared to smooth_time
+    if (target - current > 0.) == (result > target) {
+        *velocity = 0.;
+        return target;
+    }
+    result
+}
+
+/// Keeps a camera coordinate far enough inside `min..=max` that a view `half_extent` across
+/// shows nothing beyond them. Views bigger than the range are centered on it.
+pub fn clamp_view(position: f32, half_extent: f32, min: f32, max: f32) -> f32 {
+    if max - min <= half_extent * 2. {
+        (min + max) / 2.
+    } else {
+        position.clamp(min + half_extent, max - half_extent)
+    }
+}
+
+/// Half the size of what a camera with `projection` sees, in world units.
+pub fn half_view(projection: &OrthographicProjection) -> Vec2 {
+    Vec2::new(
+        projection.right - projection.left,
+        projection.top - projection.bottom,
+    ) * projection.scale
+        / 2.
+}
+
+/// Keeps a view `half_view` across from showing anything past the border platforms.
+pub fn clamp_to_map(position: Vec2, half_view: Vec2, map_config: &MapConfig) -> Vec2 {
+    // The border platforms are a unit thick and centered on the map's edges
+    let bounds = map_config.half_size() + Vec2::splat(0.5);
+    Vec2::new(
+        clamp_view(position.x, half_view.x, -bounds.x, bounds.x),
+        clamp_view(position.y, half_view.y, -bounds.y, bounds.y),
+    )
+}
+
+#[derive(Default)]
+struct CameraState {
+    /// Where the camera would be without anything nudging it, such as screen shake.
+    position: Vec2,
+    velocity: Vec2,
+    /// The height the camera is centered on, only moved once the player leaves the dead zone.
+    focus_y: f32,
+    lookahead: f32,
+    zoom_velocity: f32,
+}
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_camera)
+            .add_system(camera_view_height)
+            .add_system(camera_follow.label(CameraFollow).after(camera_view_height));
+    }
+}
+
+fn spawn_camera(mut commands: Commands, map_config: Res<MapConfig>) {
+    commands
+        .spawn_bundle(Camera2dBundle {
+            projection: OrthographicProjection {
+                scale: 1.,
+                scaling_mode: ScalingMode::FixedVertical(map_config.camera_view_height),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(GameCamera)
+        // The HUD camera draws the UI, on top of everything
+        .insert(UiCameraConfig { show_ui: false });
+
+    commands
+        .spawn_bundle(Camera2dBundle {
+            camera: Camera {
+                priority: 1,
+                ..default()
+            },
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::None,
+            },
+            ..default()
+        })
+        .insert(HudCamera)
+        .insert(hud_layer());
+}
+
+/// Picks up a new view height from the tuning.
+fn camera_view_height(
+    mut projections: Query<&mut OrthographicProjection, With<GameCamera>>,
+    map_config: Res<MapConfig>,
+) {
+    if !map_config.is_changed() {
+        return;
+    }
+
+    for mut projection in &mut projections {
+        projection.scaling_mode = ScalingMode::FixedVertical(map_config.camera_view_height);
+    }
+}
+
+/// Follows the player with a critically damped spring. The camera only moves up or down
+/// once the player leaves a dead zone, leads toward where the scroll brings things from,
+/// and zooms out as the player speeds up.
+#[allow(clippy::type_complexity)]
+fn camera_follow(
+    players: Query<(&Transform, &Velocity), With<Player>>,
+    mut cameras: Query<
+        (&mut Transform, &mut OrthographicProjection),
+        (With<GameCamera>, Without<Player>),
+    >,
+    mut state: Local<CameraState>,
+    time: Res<Time>,
+    map_config: Res<MapConfig>,
+) {
+    let state = &mut *state;
+    let dt = time.delta_seconds();
+    let (player_transform, velocity) = match players.iter().next() {
+        Some(player) => player,
+        None => return,
+    };
+    let position = player_transform.translation.truncate();
+
+    // Only follow vertically once the player is near the edge of the dead zone
+    let dead_zone = map_config.camera_dead_zone;
+    state.focus_y = state
+        .focus_y
+        .clamp(position.y - dead_zone, position.y + dead_zone);
+
+    // Ease toward the look-ahead target so direction changes don't jerk the view
+    let lookahead_target =
+        map_config.camera_scroll_lead + velocity.linvel.x * map_config.camera_lookahead;
+    state.lookahead += (lookahead_target - state.lookahead) * (dt * 4.).min(1.);
+
+    let speed = (velocity.linvel.length() / map_config.player_max_speed).clamp(0., 1.);
+    let zoom_target = 1. + map_config.camera_speed_zoom * speed;
+
+    let smooth_time = map_config.camera_smooth_time;
+    for (mut transform, mut projection) in &mut cameras {
+        let zoom = smooth_damp(
+            projection.scale,
+            zoom_target,
+            &mut state.zoom_velocity,
+            smooth_time * 4.,
+            dt,
+        );
+        if (zoom - projection.scale).abs() > f32::EPSILON {
+            projection.scale = zoom;
+        }
+
+        let target = clamp_to_map(
+            Vec2::new(position.x + state.lookahead, state.focus_y),
+            half_view(&projection),
+            &map_config,
+        );
+
+        state.position.x = smooth_damp(
+            state.position.x,
+            target.x,
+            &mut state.velocity.x,
+            smooth_time,
+            dt,
+        );
+        state.position.y = smooth_damp(
+            state.position.y,
+            target.y,
+            &mut state.velocity.y,
+            smooth_time,
+            dt,
+        );
+        transform.translation.x = state.position.x;
+        transform.translation.y = state.position.y;
+    }
+}
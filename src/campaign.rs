@@ -0,0 +1,525 @@
+use std::{collections::BTreeMap, marker::PhantomData};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bump::GameMode,
+    config::{GameConfig, BUTTON_COLOR, BUTTON_HOVER_COLOR, BUTTON_PRESSED_COLOR},
+    difficulty::Difficulty,
+    level::{ActiveLevel, Level, LevelFinished, LevelOutcome, LoseReason, PlayLevel},
+    locale::{Localized, Translations},
+    storage,
+};
+
+/// The campaign's levels in the order they unlock.
+pub const CAMPAIGN_LEVELS: &[&str] = &[
+    "levels/01-first-steps.level.ron",
+    "levels/02-two-ways-round.level.ron",
+    "levels/03-the-long-way.level.ron",
+];
+/// Storage key the progress is saved under.
+const PROGRESS_KEY: &str = "campaign";
+const LOCKED_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const NUMBER_KEYS: [KeyCode; 9] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+];
+
+/// The best run of a level so far.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LevelRecord {
+    pub stars: u8,
+    pub best_time: f64,
+    pub best_score: u32,
+    /// The difficulty the best star rating was earned on.
+    pub difficulty: Difficulty,
+}
+
+/// Best records of every level that has been won, by asset path.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct CampaignProgress {
+    pub levels: BTreeMap<String, LevelRecord>,
+}
+
+impl CampaignProgress {
+    pub fn from_ron(text: &str) -> Result<Self, ron::Error> {
+        ron::from_str(text)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new())
+    }
+
+    pub fn record(&self, path: &str) -> Option<&LevelRecord> {
+        self.levels.get(path)
+    }
+
+    /// The first level is always open, the rest once the one before has been won.
+    pub fn is_unlocked(&self, levels: &[&str], index: usize) -> bool {
+        index == 0
+            || levels
+                .get(index - 1)
+                .is_some_and(|previous| self.record(previous).is_some())
+    }
+
+    pub fn total_stars(&self) -> u32 {
+        self.levels.values().map(|record| record.stars as u32).sum()
+    }
+
+    /// Keeps the best of a won run and the record so far. Returns whether anything improved.
+    pub fn complete(
+        &mut self,
+        path: &str,
+        stars: u8,
+        finished: &LevelFinished,
+        difficulty: Difficulty,
+    ) -> bool {
+        let record = LevelRecord {
+            stars,
+            best_time: finished.time,
+            best_score: finished.score,
+            difficulty,
+        };
+        let old = match self.levels.get_mut(path) {
+            Some(old) => old,
+            None => {
+                self.levels.insert(path.to_string(), record);
+                return true;
+            }
+        };
+
+        let before = old.clone();
+        if stars > old.stars {
+            old.stars = stars;
+            old.difficulty = difficulty;
+        }
+        old.best_time = old.best_time.min(finished.time);
+        old.best_score = old.best_score.max(finished.score);
+        *old != before
+    }
+}
+
+/// Handles of every campaign level, in order.
+struct CampaignLevels(Vec<Handle<Level>>);
+
+/// The campaign level being played, by index.
+#[derive(Default)]
+struct CurrentCampaignLevel(Option<usize>);
+
+/// How the last campaign level went, shown on the level select.
+#[derive(Default)]
+struct LastResult(Option<String>);
+
+#[derive(Component)]
+struct LevelSelect;
+#[derive(Component)]
+struct LevelList;
+#[derive(Component)]
+struct LevelButton(usize);
+
+/// An ordered run of authored levels that unlock one after another and are rated with stars,
+/// picked from a level select and saved between sessions.
+pub struct CampaignPlugin;
+
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CampaignProgress>()
+            .init_resource::<CurrentCampaignLevel>()
+            .init_resource::<LastResult>()
+            .add_startup_system(startup_campaign)
+            .add_startup_system(startup_level_select)
+            .add_system(level_select_buttons)
+            .add_system(level_select_keys)
+            .add_system(record_result)
+            .add_system(level_select_visibility)
+            .add_system(
+                update_level_list
+                    .after(record_result)
+                    .after(level_select_visibility),
+            );
+    }
+}
+
+fn startup_campaign(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_config: Res<GameConfig>,
+) {
+    let levels = CAMPAIGN_LEVELS
+        .iter()
+        .map(|path| asset_server.load(*path))
+        .collect();
+    commands.insert_resource(CampaignLevels(levels));
+
+    let progress = storage::load(game_config.game_slug, PROGRESS_KEY)
+        .and_then(|text| match CampaignProgress::from_ron(&text) {
+            Ok(progress) => Some(progress),
+            Err(error) => {
+                warn!("Ignoring unreadable campaign progress: {}", error);
+                None
+            }
+        })
+        .unwrap_or_default();
+    commands.insert_resource(progress);
+}
+
+fn startup_level_select(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/Hind-Regular.otf");
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::rgba(0., 0., 0., 0.6).into(),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(LevelSelect)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 72.,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(Localized("campaign-title"))
+                .insert(LevelSelect);
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::ColumnReverse,
+                        align_items: AlignItems::Stretch,
+                        margin: UiRect::all(Val::Px(16.)),
+                        ..default()
+                    },
+                    color: Color::NONE.into(),
+                    ..default()
+                })
+                .insert(LevelList)
+                .insert(LevelSelect);
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font,
+                        font_size: 24.,
+                        color: Color::rgb(0.6, 0.6, 0.6),
+                    },
+                ))
+                .insert(Localized("campaign-keys"))
+                .insert(LevelSelect);
+        });
+}
+
+fn level_select_visibility(
+    game_mode: Res<GameMode>,
+    mut level_select: Query<&mut Visibility, With<LevelSelect>>,
+) {
+    if !game_mode.is_changed() {
+        return;
+    }
+
+    for mut visibility in &mut level_select {
+        visibility.is_visible = *game_mode == GameMode::LevelSelect;
+    }
+}
+
+/// Everything the level select shows.
+#[derive(SystemParam)]
+struct ListedLevels<'w, 's> {
+    progress: Res<'w, CampaignProgress>,
+    last_result: Res<'w, LastResult>,
+    campaign_levels: Res<'w, CampaignLevels>,
+    levels: Res<'w, Assets<Level>>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+/// Rebuilds the list of levels whenever it's shown, a level loads, progress changes
+/// or the language does.
+fn update_level_list(
+    mut commands: Commands,
+    mut asset_events: EventReader<AssetEvent<Level>>,
+    game_mode: Res<GameMode>,
+    listed: ListedLevels,
+    translations: Res<Translations>,
+    asset_server: Res<AssetServer>,
+    level_lists: Query<Entity, With<LevelList>>,
+) {
+    let ListedLevels {
+        progress,
+        last_result,
+        campaign_levels,
+        levels,
+        ..
+    } = &listed;
+    let levels_changed = asset_events.iter().count() > 0;
+    if *game_mode != GameMode::LevelSelect
+        || !(levels_changed
+            || game_mode.is_changed()
+            || progress.is_changed()
+            || translations.is_changed())
+    {
+        return;
+    }
+
+    let font = asset_server.load("fonts/Hind-Regular.otf");
+    let text_style = TextStyle {
+        font,
+        font_size: 32.,
+        color: Color::WHITE,
+    };
+    for list in &level_lists {
+        let mut list = commands.entity(list);
+        list.despawn_descendants();
+        list.with_children(|parent| {
+            if let Some(result) = &last_result.0 {
+                parent
+                    .spawn_bundle(TextBundle::from_section(result, text_style.clone()))
+                    .insert(LevelSelect);
+            }
+
+            for (index, path) in CAMPAIGN_LEVELS.iter().enumerate() {
+                let unlocked = progress.is_unlocked(CAMPAIGN_LEVELS, index);
+                let name = campaign_levels
+                    .0
+                    .get(index)
+                    .and_then(|handle| levels.get(handle))
+                    .map_or(*path, |level| level.name.as_str());
+                let number = index + 1;
+                let label = match progress.record(path) {
+                    _ if !unlocked => {
+                        translations.format("campaign-locked", &[("number", &number)])
+                    }
+                    Some(record) => translations.format(
+                        "campaign-level-record",
+                        &[
+                            ("number", &number),
+                            ("name", &name),
+                            (
+                                "stars",
+                                &format!(
+                                    "{}{}",
+                                    "*".repeat(record.stars as usize),
+                                    "-".repeat(3 - record.stars.min(3) as usize)
+                                ),
+                            ),
+                            ("time", &format!("{:.1}", record.best_time)),
+                            (
+                                "difficulty",
+                                &translations.text(record.difficulty.message_id()),
+                            ),
+                        ],
+                    ),
+                    None => translations.format(
+                        "campaign-level-new",
+                        &[("number", &number), ("name", &name)],
+                    ),
+                };
+
+                parent
+                    .spawn_bundle(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(560.), Val::Px(48.)),
+                            margin: UiRect::all(Val::Px(4.)),
+                            padding: UiRect::all(Val::Px(8.)),
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        color: UiColor(if unlocked { BUTTON_COLOR } else { LOCKED_COLOR }),
+                        ..default()
+                    })
+                    .insert(LevelButton(index))
+                    .insert(LevelSelect)
+                    .with_children(|parent| {
+                        parent
+                            .spawn_bundle(TextBundle::from_section(label, text_style.clone()))
+                            .insert(LevelSelect);
+                    });
+            }
+        });
+    }
+}
+
+fn play_campaign_level(
+    index: usize,
+    progress: &CampaignProgress,
+    campaign_levels: &CampaignLevels,
+    current_level: &mut CurrentCampaignLevel,
+    play_events: &mut EventWriter<PlayLevel>,
+) {
+    if !progress.is_unlocked(CAMPAIGN_LEVELS, index) {
+        return;
+    }
+    if let Some(handle) = campaign_levels.0.get(index) {
+        current_level.0 = Some(index);
+        play_events.send(PlayLevel(handle.clone()));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn level_select_buttons(
+    mut buttons: Query<(&Interaction, &LevelButton, &mut UiColor), Changed<Interaction>>,
+    game_mode: Res<GameMode>,
+    progress: Res<CampaignProgress>,
+    campaign_levels: Res<CampaignLevels>,
+    mut current_level: ResMut<CurrentCampaignLevel>,
+    mut play_events: EventWriter<PlayLevel>,
+) {
+    for (interaction, button, mut color) in &mut buttons {
+        if !progress.is_unlocked(CAMPAIGN_LEVELS, button.0) {
+            continue;
+        }
+        *color = match interaction {
+            Interaction::Clicked => BUTTON_PRESSED_COLOR,
+            Interaction::Hovered => BUTTON_HOVER_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        }
+        .into();
+
+        if *interaction == Interaction::Clicked && *game_mode == GameMode::LevelSelect {
+            play_campaign_level(
+                button.0,
+                &progress,
+                &campaign_levels,
+                &mut current_level,
+                &mut play_events,
+            );
+        }
+    }
+}
+
+fn level_select_keys(
+    keys: Res<Input<KeyCode>>,
+    mut game_mode: ResMut<GameMode>,
+    progress: Res<CampaignProgress>,
+    campaign_levels: Res<CampaignLevels>,
+    mut current_level: ResMut<CurrentCampaignLevel>,
+    mut play_events: EventWriter<PlayLevel>,
+) {
+    if *game_mode != GameMode::LevelSelect {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        *game_mode = GameMode::Attract;
+        return;
+    }
+    for (index, key) in NUMBER_KEYS.iter().enumerate() {
+        if keys.just_pressed(*key) {
+            play_campaign_level(
+                index,
+                &progress,
+                &campaign_levels,
+                &mut current_level,
+                &mut play_events,
+            );
+        }
+    }
+}
+
+/// The campaign level being played and the progress it counts toward.
+#[derive(SystemParam)]
+struct CampaignRun<'w, 's> {
+    current_level: ResMut<'w, CurrentCampaignLevel>,
+    progress: ResMut<'w, CampaignProgress>,
+    last_result: ResMut<'w, LastResult>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+/// Rates a finished campaign level, saves any improvement and goes back to the level select.
+fn record_result(
+    mut finished_events: EventReader<LevelFinished>,
+    mut run: CampaignRun,
+    mut game_mode: ResMut<GameMode>,
+    mut active_level: ResMut<ActiveLevel>,
+    difficulty: Res<Difficulty>,
+    translations: Res<Translations>,
+    game_config: Res<GameConfig>,
+) {
+    let CampaignRun {
+        current_level,
+        progress,
+        last_result,
+        ..
+    } = &mut run;
+
+    // Leaving a level some other way abandons it
+    if current_level.0.is_some() && game_mode.is_changed() && *game_mode != GameMode::Playing {
+        current_level.0 = None;
+    }
+    let index = match current_level.0 {
+        Some(index) => index,
+        None => {
+            finished_events.clear();
+            return;
+        }
+    };
+    let finished = match finished_events.iter().last() {
+        Some(finished) => finished,
+        None => return,
+    };
+
+    let path = CAMPAIGN_LEVELS[index];
+    let name = active_level
+        .0
+        .as_ref()
+        .map_or(path.to_string(), |level| level.name.clone());
+    last_result.0 = Some(match finished.outcome {
+        LevelOutcome::Won => {
+            let stars = active_level
+                .0
+                .as_ref()
+                .map_or(1, |level| level.stars.rate(finished));
+            if progress.complete(path, stars, finished, *difficulty) {
+                match progress.to_ron() {
+                    Ok(text) => {
+                        if let Err(error) =
+                            storage::save(game_config.game_slug, PROGRESS_KEY, &text)
+                        {
+                            error!("Unable to save campaign progress: {}", error);
+                        }
+                    }
+                    Err(error) => error!("Unable to save campaign progress: {}", error),
+                }
+            }
+            translations.format(
+                "campaign-cleared",
+                &[
+                    ("name", &name),
+                    ("stars", &stars),
+                    ("time", &format!("{:.1}", finished.time)),
+                ],
+            )
+        }
+        LevelOutcome::Lost(reason) => {
+            let reason = translations.text(match reason {
+                LoseReason::TimeUp => "lose-time-up",
+                LoseReason::TooManyHits => "lose-too-many-hits",
+                LoseReason::TooManyFalls => "lose-too-many-falls",
+            });
+            translations.format("campaign-failed", &[("name", &name), ("reason", &reason)])
+        }
+    });
+
+    current_level.0 = None;
+    *active_level = ActiveLevel(None);
+    *game_mode = GameMode::LevelSelect;
+}
@@ -0,0 +1,12 @@
+//! Collision group bits. Bodies without `CollisionGroups` are in every group and collide with everything.
+
+use super::crystal::CrystalColor;
+
+pub const PLAYER_GROUP: u32 = 1 << 0;
+pub const OBSTACLE_GROUP: u32 = 1 << 1;
+/// First of the twelve groups for color-keyed barriers, one per `CrystalColor`.
+const BARRIER_GROUP_START: u32 = 4;
+
+pub fn barrier_group(color: CrystalColor) -> u32 {
+    1 << (BARRIER_GROUP_START + color as u32)
+}
@@ -3,10 +3,14 @@ use bevy_prototype_lyon::{entity::ShapeBundle, prelude::*};
 use bevy_rapier2d::prelude::*;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, FromPrimitive)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, FromPrimitive, Deserialize, Serialize,
+)]
 pub enum CrystalColor {
     Orange = 0,
     YellowOrange,
@@ -24,8 +28,12 @@ pub enum CrystalColor {
 
 impl CrystalColor {
     pub fn random_primary() -> Self {
+        Self::random_primary_with(&mut rand::thread_rng())
+    }
+
+    pub fn random_primary_with<R: Rng + ?Sized>(rng: &mut R) -> Self {
         use CrystalColor::*;
-        match rand::random::<u8>() % 3 {
+        match rng.gen::<u8>() % 3 {
             0 => Blue,
             1 => Red,
             2 => Yellow,
@@ -35,8 +43,12 @@ impl CrystalColor {
 
     #[allow(dead_code)]
     pub fn random_secondary() -> Self {
+        Self::random_secondary_with(&mut rand::thread_rng())
+    }
+
+    pub fn random_secondary_with<R: Rng + ?Sized>(rng: &mut R) -> Self {
         use CrystalColor::*;
-        match rand::random::<u8>() % 3 {
+        match rng.gen::<u8>() % 3 {
             0 => Orange,
             1 => Green,
             2 => Purple,
@@ -46,8 +58,12 @@ impl CrystalColor {
 
     #[allow(dead_code)]
     pub fn random_tertiary() -> Self {
+        Self::random_tertiary_with(&mut rand::thread_rng())
+    }
+
+    pub fn random_tertiary_with<R: Rng + ?Sized>(rng: &mut R) -> Self {
         use CrystalColor::*;
-        match rand::random::<u8>() % 6 {
+        match rng.gen::<u8>() % 6 {
             0 => YellowOrange,
             1 => YellowGreen,
             2 => BlueGreen,
@@ -58,6 +74,19 @@ impl CrystalColor {
         }
     }
 
+    pub fn random_weighted<R: Rng + ?Sized>(rng: &mut R, weights: &SpawnWeights) -> Self {
+        let total = weights.primary + weights.secondary + weights.tertiary;
+        let roll = rng.gen::<f32>() * total;
+
+        if roll < weights.primary {
+            Self::random_primary_with(rng)
+        } else if roll < weights.primary + weights.secondary {
+            Self::random_secondary_with(rng)
+        } else {
+            Self::random_tertiary_with(rng)
+        }
+    }
+
     pub fn to_color(self) -> Color {
         match self {
             CrystalColor::Orange => Color::rgb_u8(255, 126, 0),
@@ -75,6 +104,24 @@ impl CrystalColor {
         }
     }
 
+    /// Id of the color's name in the message files.
+    pub fn message_id(self) -> &'static str {
+        match self {
+            CrystalColor::Orange => "color-orange",
+            CrystalColor::YellowOrange => "color-yellow-orange",
+            CrystalColor::Yellow => "color-yellow",
+            CrystalColor::YellowGreen => "color-yellow-green",
+            CrystalColor::Green => "color-green",
+            CrystalColor::BlueGreen => "color-blue-green",
+            CrystalColor::Blue => "color-blue",
+            CrystalColor::BluePurple => "color-blue-purple",
+            CrystalColor::Purple => "color-purple",
+            CrystalColor::RedPurple => "color-red-purple",
+            CrystalColor::Red => "color-red",
+            CrystalColor::RedOrange => "color-red-orange",
+        }
+    }
+
     pub fn to_draw_mode(self) -> DrawMode {
         DrawMode::Outlined {
             fill_mode: bevy_prototype_lyon::prelude::FillMode::color(self.to_color()),
@@ -103,6 +150,34 @@ impl CrystalColor {
         !self.is_primary() && !self.is_secondary()
     }
 
+    /// Signed number of wedges from `self` to `other` along the shortest way around the wheel.
+    /// The sign matches the direction `combine` would step in.
+    pub fn steps_to(&self, other: &Self) -> i8 {
+        let num_colors = CrystalColor::iter().count() as i8;
+        let half_num_colors = num_colors / 2;
+        let delta = *other as i8 - *self as i8;
+
+        if delta > half_num_colors {
+            delta - num_colors
+        } else if delta < -half_num_colors {
+            delta + num_colors
+        } else {
+            delta
+        }
+    }
+
+    /// The wedges passed through on the shortest way from `self` to `other`, ending with `other`.
+    pub fn route_to(&self, other: &Self) -> Vec<CrystalColor> {
+        let steps = self.steps_to(other);
+        let num_colors = CrystalColor::iter().count() as i8;
+        (1..=steps.abs())
+            .map(|step| {
+                let index = *self as i8 + step * steps.signum();
+                CrystalColor::from_i8((index + num_colors) % num_colors).unwrap()
+            })
+            .collect()
+    }
+
     pub fn combine(&self, other: &Self) -> Self {
         let old_color: i8 = *self as i8;
         let new_color: i8 = *other as i8;
@@ -131,12 +206,106 @@ impl CrystalColor {
     }
 }
 
+/// Relative odds of each tier of color when a crystal is spawned.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct SpawnWeights {
+    pub primary: f32,
+    pub secondary: f32,
+    pub tertiary: f32,
+}
+
+impl SpawnWeights {
+    pub const PRIMARY_ONLY: Self = Self {
+        primary: 1.,
+        secondary: 0.,
+        tertiary: 0.,
+    };
+}
+
+impl Default for SpawnWeights {
+    fn default() -> Self {
+        Self::PRIMARY_ONLY
+    }
+}
+
+/// What collecting a crystal does to the player's color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum CombineRule {
+    /// Move one wedge toward the crystal's color.
+    #[default]
+    Step,
+    /// Move up to two wedges toward the crystal's color.
+    DoubleStep,
+    /// Take on the crystal's color outright.
+    Adopt,
+}
+
+impl CombineRule {
+    /// The player's color after collecting a `crystal` colored crystal.
+    /// A player without a color always takes the crystal's.
+    pub fn apply(self, from: Option<CrystalColor>, crystal: CrystalColor) -> CrystalColor {
+        match (self, from) {
+            (_, None) | (CombineRule::Adopt, _) => crystal,
+            (CombineRule::Step, Some(color)) => color.combine(&crystal),
+            (CombineRule::DoubleStep, Some(color)) => color.combine(&crystal).combine(&crystal),
+        }
+    }
+
+    /// How many wedges closer to `target` collecting a `crystal` colored crystal gets you,
+    /// negative when it takes you further away. Any color is closer than none.
+    pub fn progress(
+        self,
+        from: Option<CrystalColor>,
+        crystal: CrystalColor,
+        target: CrystalColor,
+    ) -> i8 {
+        let before = from.map_or(CrystalColor::iter().count() as i8 / 2 + 1, |color| {
+            color.steps_to(&target).abs()
+        });
+        before - self.apply(from, crystal).steps_to(&target).abs()
+    }
+}
+
+/// How fast a crystal drifts across the map, relative to `MapConfig::crystal_linvel`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum CrystalKind {
+    #[default]
+    Normal,
+    Fast,
+    Slow,
+}
+
+impl CrystalKind {
+    pub fn speed(self) -> f32 {
+        match self {
+            CrystalKind::Normal => 1.,
+            CrystalKind::Fast => 1.5,
+            CrystalKind::Slow => 0.5,
+        }
+    }
+
+    /// Id of the kind's name in the message files.
+    pub fn message_id(self) -> &'static str {
+        match self {
+            CrystalKind::Normal => "crystal-normal",
+            CrystalKind::Fast => "crystal-fast",
+            CrystalKind::Slow => "crystal-slow",
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct Crystal {
     pub crystal_color: CrystalColor,
     pub collected: bool,
+    pub kind: CrystalKind,
 }
 
+/// A crystal placed by a level rather than the random spawner.
+/// It's removed once collected or off the map, instead of being recycled.
+#[derive(Component)]
+pub struct Placed;
+
 #[derive(Bundle)]
 pub struct CrystalBundle {
     #[bundle]
@@ -154,6 +323,11 @@ impl CrystalBundle {
         let crystal_color = CrystalColor::random_primary();
         Self::default().with_color(crystal_color)
     }
+
+    pub fn random_primary_with<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let crystal_color = CrystalColor::random_primary_with(rng);
+        Self::default().with_color(crystal_color)
+    }
     pub fn with_color(mut self, crystal_color: CrystalColor) -> Self {
         self.crystal.crystal_color = crystal_color;
         self.shape_bundle.mode = crystal_color.to_draw_mode();
@@ -164,6 +338,11 @@ impl CrystalBundle {
         self.shape_bundle.transform = Transform::from_xyz(x, y, 75.);
         self
     }
+
+    pub fn with_kind(mut self, kind: CrystalKind) -> Self {
+        self.crystal.kind = kind;
+        self
+    }
 }
 
 impl Default for CrystalBundle {
@@ -178,6 +357,7 @@ impl Default for CrystalBundle {
             crystal: Crystal {
                 crystal_color: color,
                 collected: false,
+                kind: CrystalKind::Normal,
             },
             shape_bundle: GeometryBuilder::build_as(
                 &shape,
@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+
+use super::crystal::CrystalColor;
+
+/// A player touched a crystal and absorbed its color.
+#[derive(Debug, Clone)]
+pub struct CrystalCollected {
+    pub player: Entity,
+    pub crystal: Entity,
+    pub crystal_color: CrystalColor,
+    /// Where the crystal was when it was touched.
+    pub position: Vec2,
+    pub from: Option<CrystalColor>,
+    pub to: CrystalColor,
+}
+
+/// A player's color changed, for any reason.
+#[derive(Debug, Clone)]
+pub struct ColorChanged {
+    pub player: Entity,
+    pub from: Option<CrystalColor>,
+    pub to: Option<CrystalColor>,
+}
+
+/// A player's color matched the target. `next_target` has already been chosen.
+#[derive(Debug, Clone)]
+pub struct TargetReached {
+    pub player: Entity,
+    pub color: CrystalColor,
+    pub next_target: CrystalColor,
+    pub score: u32,
+}
+
+/// A player left the map and was moved back to the origin.
+#[derive(Debug, Clone)]
+pub struct PlayerOutOfBounds {
+    pub player: Entity,
+    pub position: Vec2,
+}
+
+/// A player ran into the side of an obstacle and lost their color.
+#[derive(Debug, Clone)]
+pub struct ObstacleHit {
+    pub player: Entity,
+    pub obstacle: Entity,
+}
+
+/// Request to start a fresh run: score, colors, player and crystals are all reset.
+#[derive(Debug, Clone, Default)]
+pub struct ResetGame;
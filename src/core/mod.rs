@@ -1,20 +1,25 @@
-use bevy::{prelude::*, render::camera::ScalingMode};
+use bevy::prelude::*;
 use bevy_prototype_lyon::prelude::ShapePlugin;
 use bevy_rapier2d::prelude::*;
 
+pub mod collision;
 pub mod component;
 pub mod crystal;
+pub mod event;
 pub mod platform;
 pub mod player;
+pub mod timestep;
 
+use timestep::{FixedStage, FixedTimestepPlugin, FixedUpdateAppExt, FIXED_TIMESTEP};
+
+/// Everything needed to run the game with a window and renderer.
 pub struct CorePlugin;
 
 impl Plugin for CorePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
+        app.add_plugin(SimulationPlugin)
             .insert_resource(Msaa { samples: 4 })
-            .add_plugin(ShapePlugin)
-            .add_startup_system(spawn_camera);
+            .add_plugin(ShapePlugin);
 
         if cfg!(debug_assertions) {
             app.add_plugin(bevy::diagnostic::FrameTimeDiagnosticsPlugin::default())
@@ -24,13 +29,40 @@ impl Plugin for CorePlugin {
     }
 }
 
-fn spawn_camera(mut commands: Commands) {
-    commands.spawn_bundle(Camera2dBundle {
-        projection: OrthographicProjection {
-            scale: 1.,
-            scaling_mode: ScalingMode::FixedVertical(21.),
-            ..default()
-        },
-        ..default()
-    });
+/// The fixed timestep schedule and physics, without anything that needs a window.
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        // Physics is stepped from the fixed timestep schedule alongside gameplay,
+        // so rapier's own stages are replaced with ours.
+        app.add_plugin(FixedTimestepPlugin)
+            .add_plugin(
+                RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0)
+                    .with_default_system_setup(false),
+            )
+            .insert_resource(RapierConfiguration {
+                timestep_mode: TimestepMode::Fixed {
+                    dt: FIXED_TIMESTEP as f32,
+                    substeps: 1,
+                },
+                ..default()
+            })
+            .add_fixed_system_set_to_stage(
+                FixedStage::SyncBackend,
+                RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsStages::SyncBackend),
+            )
+            .add_fixed_system_set_to_stage(
+                FixedStage::StepSimulation,
+                RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsStages::StepSimulation),
+            )
+            .add_fixed_system_set_to_stage(
+                FixedStage::Writeback,
+                RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsStages::Writeback),
+            )
+            .add_system_set_to_stage(
+                CoreStage::Last,
+                RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsStages::DetectDespawn),
+            );
+    }
 }
@@ -1,8 +1,39 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How a platform behaves when the player touches it.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+pub enum PlatformKind {
+    #[default]
+    Solid,
+    /// Can be jumped through from below and landed on from above.
+    OneWay,
+    /// Falls apart `delay` seconds after the player first touches it.
+    Crumbling { delay: f32 },
+    /// Carries a player standing on it sideways at `speed`.
+    Conveyor { speed: f32 },
+    /// Knocks the color out of the player on any contact.
+    Damaging,
+}
+
+impl PlatformKind {
+    /// Id of the kind's name in the message files.
+    pub fn message_id(self) -> &'static str {
+        match self {
+            PlatformKind::Solid => "platform-solid",
+            PlatformKind::OneWay => "platform-one-way",
+            PlatformKind::Crumbling { .. } => "platform-crumbling",
+            PlatformKind::Conveyor { .. } => "platform-conveyor",
+            PlatformKind::Damaging => "platform-damaging",
+        }
+    }
+}
 
 #[derive(Component)]
-pub struct Platform {}
+pub struct Platform {
+    pub kind: PlatformKind,
+}
 
 #[derive(Bundle)]
 pub struct PlatformBundle {
@@ -11,6 +42,8 @@ pub struct PlatformBundle {
     pub platform: Platform,
     pub collider: Collider,
     pub rigid_body: RigidBody,
+    pub restitution: Restitution,
+    pub solver_groups: SolverGroups,
 }
 
 impl PlatformBundle {
@@ -24,17 +57,39 @@ impl PlatformBundle {
         self
     }
 
+    pub fn with_rigid_body(mut self, rigid_body: RigidBody) -> Self {
+        self.rigid_body = rigid_body;
+        self
+    }
+
     pub fn with_size(mut self, width: f32, height: f32) -> Self {
         self.sprite_bundle.sprite.custom_size = Some(Vec2::new(width, height));
         self.collider = Collider::cuboid(width / 2., height / 2.);
         self
     }
+
+    pub fn with_kind(mut self, kind: PlatformKind) -> Self {
+        self.platform.kind = kind;
+        self
+    }
+
+    /// Makes the platform bouncy. The higher of the two bodies' restitution is used,
+    /// so anything above the player's own makes the player bounce harder.
+    pub fn with_restitution(mut self, coefficient: f32) -> Self {
+        self.restitution = Restitution {
+            coefficient,
+            combine_rule: CoefficientCombineRule::Max,
+        };
+        self
+    }
 }
 
 impl Default for PlatformBundle {
     fn default() -> Self {
         Self {
-            platform: Platform {},
+            platform: Platform {
+                kind: PlatformKind::Solid,
+            },
             sprite_bundle: SpriteBundle {
                 transform: Transform::from_xyz(0., 0., 0.),
                 sprite: Sprite {
@@ -46,6 +101,8 @@ impl Default for PlatformBundle {
             },
             collider: Collider::cuboid(0.5, 0.5),
             rigid_body: RigidBody::Fixed,
+            restitution: Restitution::default(),
+            solver_groups: SolverGroups::default(),
         }
     }
 }
@@ -3,7 +3,7 @@ use bevy_prototype_lyon::{entity::ShapeBundle, prelude::*};
 use bevy_rapier2d::prelude::*;
 use std::f32::consts::SQRT_2;
 
-use super::crystal::CrystalColor;
+use super::{collision::PLAYER_GROUP, crystal::CrystalColor};
 
 pub const PLAYER_OUTLINE_WIDTH: f32 = 0.05;
 
@@ -13,6 +13,8 @@ pub struct Player {
     pub color: Option<CrystalColor>,
     pub movement_dir: Vec2,
     pub action_down: bool,
+    /// Where the player drifts back to after being pushed sideways, while flapping.
+    pub home_x: f32,
 }
 
 #[derive(Bundle)]
@@ -29,6 +31,8 @@ pub struct PlayerBundle {
     pub collider_mass_properties: ColliderMassProperties,
     pub locked_axes: LockedAxes,
     pub active_events: ActiveEvents,
+    pub collision_groups: CollisionGroups,
+    pub solver_groups: SolverGroups,
 }
 
 impl PlayerBundle {
@@ -67,6 +71,7 @@ impl PlayerBundle {
 
     pub fn with_position(mut self, x: f32, y: f32) -> Self {
         self.shape_bundle.transform = Transform::from_xyz(x, y, 100.);
+        self.player.home_x = x;
         self
     }
 }
@@ -83,6 +88,7 @@ impl Default for PlayerBundle {
                 color: None,
                 movement_dir: Vec2::ZERO,
                 action_down: false,
+                home_x: 0.,
             },
             shape_bundle: GeometryBuilder::build_as(
                 &shape,
@@ -101,6 +107,8 @@ impl Default for PlayerBundle {
             collider_mass_properties: ColliderMassProperties::Density(1.0),
             locked_axes: LockedAxes::ROTATION_LOCKED,
             active_events: ActiveEvents::COLLISION_EVENTS,
+            collision_groups: CollisionGroups::new(PLAYER_GROUP, u32::MAX),
+            solver_groups: SolverGroups::new(PLAYER_GROUP, u32::MAX),
         }
     }
 }
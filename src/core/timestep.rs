@@ -0,0 +1,188 @@
+use bevy::{
+    ecs::{
+        event::Event,
+        schedule::{IntoSystemDescriptor, ShouldRun},
+    },
+    prelude::*,
+};
+
+/// Length of a single gameplay/physics step in seconds.
+pub const FIXED_TIMESTEP: f64 = 1. / 60.;
+
+/// Stage that runs once for every elapsed `FIXED_TIMESTEP`, catching up if a frame took longer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, StageLabel)]
+pub struct FixedUpdateStage;
+
+/// Sub-stages of the `FixedUpdateStage` schedule, run in order on every step.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, StageLabel)]
+pub enum FixedStage {
+    First,
+    Update,
+    SyncBackend,
+    StepSimulation,
+    Writeback,
+}
+
+/// Simulation time, advanced by exactly `FIXED_TIMESTEP` on each step.
+/// Gameplay should use this instead of `Time` so it behaves the same at any frame rate.
+#[derive(Debug, Default)]
+pub struct GameClock {
+    pub elapsed: f64,
+    pub ticks: u64,
+}
+
+impl GameClock {
+    pub fn delta_seconds(&self) -> f32 {
+        FIXED_TIMESTEP as f32
+    }
+}
+
+/// How fast gameplay runs compared to real time, between `GameSpeed::MIN` and 1.
+/// Slowing down only spaces out the fixed steps, each step still simulates exactly `FIXED_TIMESTEP`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GameSpeed(pub f32);
+
+impl GameSpeed {
+    pub const MIN: f32 = 0.5;
+    pub const MAX: f32 = 1.;
+
+    pub fn clamped(self) -> f32 {
+        self.0.clamp(Self::MIN, Self::MAX)
+    }
+}
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        GameSpeed(Self::MAX)
+    }
+}
+
+/// Stops the fixed steps altogether, such as while editing a level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Paused(pub bool);
+
+/// Holds the fixed steps for a moment to make a hit land harder.
+/// The game stands still until `Time::seconds_since_startup` reaches this.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HitStop(pub f64);
+
+pub struct FixedTimestepPlugin;
+
+impl Plugin for FixedTimestepPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameClock>()
+            .init_resource::<GameSpeed>()
+            .init_resource::<Paused>()
+            .init_resource::<HitStop>()
+            .add_stage_after(
+                CoreStage::Update,
+                FixedUpdateStage,
+                Schedule::default()
+                    .with_run_criteria(fixed_timestep)
+                    .with_stage(FixedStage::First, SystemStage::parallel())
+                    .with_stage(FixedStage::Update, SystemStage::parallel())
+                    .with_stage(FixedStage::SyncBackend, SystemStage::parallel())
+                    .with_stage(FixedStage::StepSimulation, SystemStage::parallel())
+                    .with_stage(FixedStage::Writeback, SystemStage::parallel()),
+            );
+
+        app.add_fixed_system_to_stage(FixedStage::First, tick_game_clock);
+    }
+}
+
+#[derive(Default)]
+struct FixedTimestepState {
+    accumulator: f64,
+    looping: bool,
+}
+
+/// Same as bevy's `FixedTimestep`, except frame time is scaled by the `GameSpeed` first.
+fn fixed_timestep(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    paused: Res<Paused>,
+    hit_stop: Res<HitStop>,
+    mut state: Local<FixedTimestepState>,
+) -> ShouldRun {
+    // Time spent paused is dropped rather than caught up on afterwards
+    if paused.0 || time.seconds_since_startup() < hit_stop.0 {
+        *state = FixedTimestepState::default();
+        return ShouldRun::No;
+    }
+
+    if !state.looping {
+        state.accumulator += time.delta_seconds_f64() * game_speed.clamped() as f64;
+    }
+
+    if state.accumulator >= FIXED_TIMESTEP {
+        state.accumulator -= FIXED_TIMESTEP;
+        state.looping = true;
+        ShouldRun::YesAndCheckAgain
+    } else {
+        state.looping = false;
+        ShouldRun::No
+    }
+}
+
+fn tick_game_clock(mut clock: ResMut<GameClock>) {
+    clock.ticks += 1;
+    clock.elapsed = clock.ticks as f64 * FIXED_TIMESTEP;
+}
+
+/// Swaps the event buffers only after a frame that ran steps, so events wait for a step to see them.
+fn update_fixed_events<T: Event>(
+    mut events: ResMut<Events<T>>,
+    mut last_ticks: Local<u64>,
+    clock: Res<GameClock>,
+) {
+    if clock.ticks != *last_ticks {
+        *last_ticks = clock.ticks;
+        events.update();
+    }
+}
+
+/// Helpers for registering systems in the fixed timestep schedule.
+pub trait FixedUpdateAppExt {
+    fn add_fixed_system_to_stage<Params>(
+        &mut self,
+        stage: FixedStage,
+        system: impl IntoSystemDescriptor<Params>,
+    ) -> &mut Self;
+
+    fn add_fixed_system_set_to_stage(&mut self, stage: FixedStage, set: SystemSet) -> &mut Self;
+
+    /// Like `add_event`, for events sent or read by the fixed timestep schedule. Bevy drops
+    /// events after two frames, which may run no steps at high frame rates, so these are kept
+    /// until a frame has run steps instead.
+    fn add_fixed_event<T: Event>(&mut self) -> &mut Self;
+
+    fn add_fixed_system<Params>(&mut self, system: impl IntoSystemDescriptor<Params>) -> &mut Self {
+        self.add_fixed_system_to_stage(FixedStage::Update, system)
+    }
+}
+
+impl FixedUpdateAppExt for App {
+    fn add_fixed_system_to_stage<Params>(
+        &mut self,
+        stage: FixedStage,
+        system: impl IntoSystemDescriptor<Params>,
+    ) -> &mut Self {
+        self.stage(FixedUpdateStage, |schedule: &mut Schedule| {
+            schedule.add_system_to_stage(stage, system)
+        })
+    }
+
+    fn add_fixed_system_set_to_stage(&mut self, stage: FixedStage, set: SystemSet) -> &mut Self {
+        self.stage(FixedUpdateStage, |schedule: &mut Schedule| {
+            schedule.add_system_set_to_stage(stage, set)
+        })
+    }
+
+    fn add_fixed_event<T: Event>(&mut self) -> &mut Self {
+        if !self.world.contains_resource::<Events<T>>() {
+            self.init_resource::<Events<T>>()
+                .add_system_to_stage(CoreStage::First, update_fixed_events::<T>);
+        }
+        self
+    }
+}
@@ -0,0 +1,356 @@
+//! Audio cues for playing by ear. With them on, a ping keeps sounding from the nearest crystal
+//! that would bring the player closer to the target: panned toward its side, higher when
+//! it's above and lower when it's below, and quicker the closer it is. Collecting a crystal
+//! answers with a rising tone if it helped and a falling one if it didn't, and reaching the
+//! target, hitting an obstacle and falling off the map each have their own pattern. Every new
+//! target is announced on its own note, and a quickening beep warns of a wall or barrier
+//! coming up in the player's way.
+
+use std::marker::PhantomData;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use strum::IntoEnumIterator;
+
+use crate::{
+    bump::{GameMode, MapConfig, TargetColor},
+    core::{
+        crystal::{CombineRule, Crystal, CrystalColor},
+        event::{CrystalCollected, ObstacleHit, PlayerOutOfBounds, TargetReached},
+        platform::{Platform, PlatformKind},
+        player::Player,
+    },
+    obstacle::{can_pass, Barrier, Obstacle},
+    radar::HelpfulColors,
+    sound::{glide, pan, pitch, sequence, sfx_audible, stereo_wav, tone, SoundSettings},
+};
+
+/// Positions a ping can come from, from hard left to hard right.
+pub const PAN_STEPS: usize = 9;
+/// Pitches a ping can have, from the bottom of the map to the top.
+pub const PITCH_STEPS: usize = 9;
+/// The ping's note for a crystal level with the player, the A an octave above orange's.
+const PING_PITCH: f32 = 880.;
+/// Semitones between the ping for a crystal level with the player and one a whole map away.
+const PING_PITCH_RANGE: f32 = 12.;
+const PING_TIME: f32 = 0.07;
+/// Horizontal distance, in world units, that pans a ping all the way to one side.
+const PAN_RANGE: f32 = 20.;
+/// Seconds between pings for a crystal right by the player and one `PAN_RANGE` or more away.
+const PING_INTERVAL_NEAR: f32 = 0.2;
+const PING_INTERVAL_FAR: f32 = 0.8;
+/// Seconds the reached-target cue lasts, so the next target is announced after it.
+const TARGET_CUE_TIME: f64 = 0.9;
+/// How far ahead, in world units, an obstacle in the player's way starts the warning.
+pub const WARNING_RANGE: f32 = 10.;
+/// Seconds between warning beeps for an obstacle about to hit and one `WARNING_RANGE` away.
+const WARNING_INTERVAL_NEAR: f32 = 0.15;
+const WARNING_INTERVAL_FAR: f32 = 0.6;
+/// About half the player's height, for telling whether an obstacle is in their way.
+const PLAYER_HALF_HEIGHT: f32 = 1.5;
+
+/// Whether the game is narrated with sound for players who can't rely on the screen.
+/// Turned on from the title screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AudioCues(pub bool);
+
+/// Which of the pings to play for a crystal at `relative` to the player, as steps of
+/// `PAN_STEPS` from the left and `PITCH_STEPS` from the bottom. A crystal `vertical_range`
+/// above or below gets the highest or lowest pitch.
+pub fn ping_steps(relative: Vec2, vertical_range: f32) -> (usize, usize) {
+    let step = |offset: f32, steps: usize| {
+        ((offset.clamp(-1., 1.) + 1.) / 2. * (steps - 1) as f32).round() as usize
+    };
+    (
+        step(relative.x / PAN_RANGE, PAN_STEPS),
+        step(relative.y / vertical_range, PITCH_STEPS),
+    )
+}
+
+/// How far ahead the near side of an obstacle at `relative` to the player is, if it's coming
+/// up in their way within `WARNING_RANGE`.
+pub fn obstacle_ahead(relative: Vec2, size: Vec2) -> Option<f32> {
+    let ahead = relative.x - size.x / 2.;
+    let in_line = relative.y.abs() <= size.y / 2. + PLAYER_HALF_HEIGHT;
+    (in_line && (0. ..=WARNING_RANGE).contains(&ahead)).then_some(ahead)
+}
+
+/// The synthesized cues. Pings are indexed by pan step then pitch step,
+/// target and announcement cues by `CrystalColor`.
+struct CueSounds {
+    pings: Vec<Handle<AudioSource>>,
+    closer: Handle<AudioSource>,
+    further: Handle<AudioSource>,
+    no_change: Handle<AudioSource>,
+    targets: Vec<Handle<AudioSource>>,
+    announcements: Vec<Handle<AudioSource>>,
+    obstacle: Handle<AudioSource>,
+    warning: Handle<AudioSource>,
+    fall: Handle<AudioSource>,
+}
+
+pub struct AudioCuesPlugin;
+
+impl Plugin for AudioCuesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioCues>()
+            .add_startup_system(startup_cues)
+            .add_system(crystal_ping)
+            .add_system(progress_cues)
+            .add_system(target_announcement)
+            .add_system(obstacle_warning)
+            .add_system(mishap_cues)
+            .add_system(toggle_confirmation);
+    }
+}
+
+fn startup_cues(mut commands: Commands, mut audio_sources: ResMut<Assets<AudioSource>>) {
+    let mut add = |frames: Vec<[f32; 2]>| {
+        audio_sources.add(AudioSource {
+            bytes: stereo_wav(&frames).into(),
+        })
+    };
+
+    let mut pings = Vec::new();
+    for pan_step in 0..PAN_STEPS {
+        let position = pan_step as f32 / (PAN_STEPS - 1) as f32 * 2. - 1.;
+        for pitch_step in 0..PITCH_STEPS {
+            let semitones =
+                (pitch_step as f32 / (PITCH_STEPS - 1) as f32 * 2. - 1.) * PING_PITCH_RANGE;
+            let frequency = PING_PITCH * 2f32.powf(semitones / 12.);
+            pings.push(add(pan(&tone(frequency, PING_TIME), position)));
+        }
+    }
+
+    let center = |samples: Vec<f32>| pan(&samples, 0.);
+    let base = pitch(CrystalColor::Orange, 0);
+    let sounds = CueSounds {
+        pings,
+        // A minor third up or down, so the two are easy to tell apart
+        closer: add(center(glide(base, base * 2f32.powf(3. / 12.), 0.25))),
+        further: add(center(glide(base, base * 2f32.powf(-3. / 12.), 0.25))),
+        no_change: add(center(tone(base, 0.12))),
+        // Two short notes and a long one on the target's note, like calling out its name
+        targets: CrystalColor::iter()
+            .map(|color| {
+                let root = pitch(color, 0);
+                add(center(sequence(
+                    &[tone(root, 0.12), tone(root, 0.12), tone(root * 2., 0.5)],
+                    0.05,
+                )))
+            })
+            .collect(),
+        // A swoop up to the new target's note, held
+        announcements: CrystalColor::iter()
+            .map(|color| {
+                let root = pitch(color, 0);
+                add(center(sequence(
+                    &[glide(root / 2., root, 0.15), tone(root, 0.35)],
+                    0.,
+                )))
+            })
+            .collect(),
+        obstacle: add(center(sequence(
+            &[glide(110., 80., 0.12), glide(110., 80., 0.12)],
+            0.03,
+        ))),
+        warning: add(center(sequence(
+            &[tone(1320., 0.04), tone(1320., 0.04)],
+            0.03,
+        ))),
+        fall: add(center(glide(base, base / 4., 0.6))),
+    };
+    commands.insert_resource(sounds);
+}
+
+/// Plays cues at the sound effect volume, when they're turned on and effects can be heard.
+#[derive(SystemParam)]
+struct CuePlayer<'w, 's> {
+    audio: Res<'w, Audio>,
+    sounds: Res<'w, CueSounds>,
+    audio_cues: Res<'w, AudioCues>,
+    settings: Res<'w, SoundSettings>,
+    game_mode: Res<'w, GameMode>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+impl CuePlayer<'_, '_> {
+    fn enabled(&self) -> bool {
+        self.audio_cues.0 && sfx_audible(*self.game_mode)
+    }
+
+    fn play(&self, sound: &Handle<AudioSource>) {
+        self.audio.play_with_settings(
+            sound.clone(),
+            PlaybackSettings::ONCE.with_volume(self.settings.sfx_volume),
+        );
+    }
+}
+
+/// Keeps pinging from the nearest helpful crystal.
+fn crystal_ping(
+    mut next_ping_at: Local<f64>,
+    players: Query<&Transform, With<Player>>,
+    crystals: Query<(&Transform, &Crystal), Without<Player>>,
+    cues: CuePlayer,
+    helpful: HelpfulColors,
+    map_config: Res<MapConfig>,
+    time: Res<Time>,
+) {
+    let now = time.seconds_since_startup();
+    if !cues.enabled() || now < *next_ping_at {
+        return;
+    }
+    let player = match players.iter().next() {
+        Some(transform) => transform.translation.truncate(),
+        None => return,
+    };
+
+    let nearest = crystals
+        .iter()
+        .filter(|(_, crystal)| !crystal.collected && helpful.helps(crystal.crystal_color))
+        .map(|(transform, _)| transform.translation.truncate() - player)
+        .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+    let relative = match nearest {
+        Some(relative) => relative,
+        None => return,
+    };
+
+    let (pan_step, pitch_step) = ping_steps(relative, map_config.map_size.y);
+    cues.play(&cues.sounds.pings[pan_step * PITCH_STEPS + pitch_step]);
+
+    let distance = (relative.length() / PAN_RANGE).min(1.);
+    *next_ping_at =
+        now + (PING_INTERVAL_NEAR + (PING_INTERVAL_FAR - PING_INTERVAL_NEAR) * distance) as f64;
+}
+
+/// Tells the player whether a crystal took them closer to the target, or that they reached it.
+fn progress_cues(
+    mut collected_events: EventReader<CrystalCollected>,
+    mut target_reached_events: EventReader<TargetReached>,
+    cues: CuePlayer,
+    target_color: Res<TargetColor>,
+) {
+    let reached = target_reached_events.iter().last().map(|event| event.color);
+    let collected: Vec<_> = collected_events.iter().cloned().collect();
+    if !cues.enabled() {
+        return;
+    }
+    let sounds = &cues.sounds;
+
+    // The target has already moved on, so there's nothing to compare with
+    if let Some(color) = reached {
+        cues.play(&sounds.targets[color as usize]);
+        return;
+    }
+
+    for event in collected {
+        // Adopting takes on the color outright, so this is how much closer `to` is than `from`
+        let progress = CombineRule::Adopt.progress(event.from, event.to, target_color.0);
+        cues.play(match progress {
+            progress if progress > 0 => &sounds.closer,
+            progress if progress < 0 => &sounds.further,
+            _ => &sounds.no_change,
+        });
+    }
+}
+
+/// Names each new target on its note, once the cue for reaching the last one has finished.
+fn target_announcement(
+    mut announce_at: Local<Option<f64>>,
+    mut target_reached_events: EventReader<TargetReached>,
+    cues: CuePlayer,
+    target_color: Res<TargetColor>,
+    time: Res<Time>,
+) {
+    let now = time.seconds_since_startup();
+    let reached = target_reached_events.iter().count() > 0;
+    if target_color.is_changed() {
+        *announce_at = Some(now + if reached { TARGET_CUE_TIME } else { 0. });
+    }
+    if !cues.enabled() {
+        *announce_at = None;
+        return;
+    }
+
+    if announce_at.is_some_and(|at| now >= at) {
+        *announce_at = None;
+        cues.play(&cues.sounds.announcements[target_color.0 as usize]);
+    }
+}
+
+/// Beeps quicker and quicker as a wall or barrier the player can't pass comes up in their way.
+fn obstacle_warning(
+    mut next_warning_at: Local<f64>,
+    players: Query<(&Transform, &Player)>,
+    obstacles: Query<(&Transform, &Obstacle, &Platform, Option<&Barrier>), Without<Player>>,
+    cues: CuePlayer,
+    map_config: Res<MapConfig>,
+    time: Res<Time>,
+) {
+    let now = time.seconds_since_startup();
+    if !cues.enabled() || now < *next_warning_at {
+        return;
+    }
+    let (player_position, player) = match players.iter().next() {
+        Some((transform, player)) => (transform.translation.truncate(), player),
+        None => return,
+    };
+
+    let tolerance = map_config.obstacles.barrier_tolerance;
+    let nearest = obstacles
+        .iter()
+        .filter(|(_, _, platform, barrier)| {
+            // Players jump up through one-way platforms, and through barriers in their color
+            platform.kind != PlatformKind::OneWay
+                && !barrier.is_some_and(|barrier| can_pass(player.color, barrier.0, tolerance))
+        })
+        .filter_map(|(transform, obstacle, ..)| {
+            obstacle_ahead(
+                transform.translation.truncate() - player_position,
+                obstacle.size,
+            )
+        })
+        .min_by(f32::total_cmp);
+    let distance = match nearest {
+        Some(distance) => distance,
+        None => return,
+    };
+
+    cues.play(&cues.sounds.warning);
+    let distance = distance / WARNING_RANGE;
+    *next_warning_at = now
+        + (WARNING_INTERVAL_NEAR + (WARNING_INTERVAL_FAR - WARNING_INTERVAL_NEAR) * distance)
+            as f64;
+}
+
+fn mishap_cues(
+    mut hit_events: EventReader<ObstacleHit>,
+    mut out_of_bounds_events: EventReader<PlayerOutOfBounds>,
+    cues: CuePlayer,
+) {
+    let hit = hit_events.iter().count() > 0;
+    let fell = out_of_bounds_events.iter().count() > 0;
+    if !cues.enabled() {
+        return;
+    }
+
+    for (happened, sound) in [(hit, &cues.sounds.obstacle), (fell, &cues.sounds.fall)] {
+        if happened {
+            cues.play(sound);
+        }
+    }
+}
+
+/// Answers the cues being turned on or off, since the title screen may not be seen.
+/// Plays whatever the game mode, as the menus are otherwise quiet.
+fn toggle_confirmation(cues: CuePlayer) {
+    if !cues.audio_cues.is_changed() || cues.audio_cues.is_added() {
+        return;
+    }
+
+    cues.play(match cues.audio_cues.0 {
+        true => &cues.sounds.closer,
+        false => &cues.sounds.further,
+    });
+}
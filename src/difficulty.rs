@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bump::{BorderKind, GameMode, MapConfig, Score},
+    config::GameConfig,
+    core::crystal::SpawnWeights,
+    level::ActiveLevel,
+    storage,
+};
+
+/// Storage key the high scores are saved under.
+const HIGH_SCORES_KEY: &str = "high-scores";
+
+/// Named presets applied on top of the tuning, picked on the title screen.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Deserialize, Serialize,
+)]
+pub enum Difficulty {
+    Relaxed,
+    #[default]
+    Normal,
+    Hard,
+    Insane,
+}
+
+impl Difficulty {
+    /// Id of the difficulty's name in the message files.
+    pub fn message_id(self) -> &'static str {
+        match self {
+            Difficulty::Relaxed => "difficulty-relaxed",
+            Difficulty::Normal => "difficulty-normal",
+            Difficulty::Hard => "difficulty-hard",
+            Difficulty::Insane => "difficulty-insane",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Relaxed => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Insane,
+            Difficulty::Insane => Difficulty::Insane,
+        }
+    }
+
+    pub fn previous(self) -> Self {
+        match self {
+            Difficulty::Relaxed => Difficulty::Relaxed,
+            Difficulty::Normal => Difficulty::Relaxed,
+            Difficulty::Hard => Difficulty::Normal,
+            Difficulty::Insane => Difficulty::Hard,
+        }
+    }
+
+    /// The tuning to play with at this difficulty. `Normal` plays the tuning as is.
+    pub fn apply(self, base: &MapConfig) -> MapConfig {
+        let (speed, count, gravity) = match self {
+            Difficulty::Relaxed => (0.7, 1.25, 0.7),
+            Difficulty::Normal => (1., 1., 1.),
+            Difficulty::Hard => (1.25, 0.85, 1.2),
+            Difficulty::Insane => (1.5, 0.7, 1.5),
+        };
+
+        let mut map_config = base.clone();
+        map_config.crystal_linvel *= speed;
+        map_config.crystal_count = (base.crystal_count as f32 * count).round() as usize;
+        map_config.player_gravity *= gravity;
+        map_config.obstacles.spacing /= speed;
+        map_config.obstacles.min_gap /= gravity;
+        map_config.obstacles.max_gap /= gravity;
+
+        match self {
+            Difficulty::Relaxed => {
+                // Only primaries, and targets right next to each other
+                map_config.spawn_weights = SpawnWeights::PRIMARY_ONLY;
+                map_config.min_target_distance = 1;
+                map_config.max_target_distance = 2;
+                map_config.obstacles.barrier_tolerance = 1;
+                map_config.border = BorderKind::Bouncy;
+            }
+            Difficulty::Normal => {}
+            Difficulty::Hard => {
+                map_config.spawn_weights = SpawnWeights {
+                    primary: 1.,
+                    secondary: 0.5,
+                    tertiary: 0.,
+                };
+                map_config.min_target_distance = 3;
+                map_config.max_target_distance = 6;
+            }
+            Difficulty::Insane => {
+                map_config.spawn_weights = SpawnWeights {
+                    primary: 1.,
+                    secondary: 1.,
+                    tertiary: 1.,
+                };
+                map_config.min_target_distance = 4;
+                map_config.max_target_distance = 6;
+                map_config.border = BorderKind::Damaging;
+            }
+        }
+
+        map_config
+    }
+}
+
+/// Tuning before the `Difficulty` is applied. `MapConfig` is derived from it,
+/// so anything that loads or overrides tuning should write here.
+#[derive(Debug, Clone, Default)]
+pub struct BaseMapConfig(pub MapConfig);
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+            .init_resource::<BaseMapConfig>()
+            // Before startup so the world is spawned at the chosen difficulty
+            .add_startup_system_to_stage(StartupStage::PreStartup, apply_difficulty)
+            .add_system_to_stage(CoreStage::PreUpdate, apply_difficulty);
+    }
+}
+
+/// Derives the `MapConfig` from the tuning, the difficulty and the level being played.
+fn apply_difficulty(
+    difficulty: Res<Difficulty>,
+    base_map_config: Res<BaseMapConfig>,
+    active_level: Res<ActiveLevel>,
+    mut map_config: ResMut<MapConfig>,
+) {
+    if difficulty.is_changed() || base_map_config.is_changed() || active_level.is_changed() {
+        let mut config = difficulty.apply(&base_map_config.0);
+        if let Some(level) = &active_level.0 {
+            level.apply(&mut config);
+        }
+        *map_config = config;
+    }
+}
+
+/// The best endless score on each difficulty, as a score only compares with others earned on
+/// the same one. Campaign levels keep their own records.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct HighScores(pub BTreeMap<Difficulty, u32>);
+
+impl HighScores {
+    pub fn from_ron(text: &str) -> Result<Self, ron::Error> {
+        ron::from_str(text)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new())
+    }
+
+    pub fn best(&self, difficulty: Difficulty) -> Option<u32> {
+        self.0.get(&difficulty).copied()
+    }
+
+    /// Keeps `score` if it's the best on `difficulty` so far. Returns whether it was.
+    pub fn submit(&mut self, difficulty: Difficulty, score: u32) -> bool {
+        if score == 0 || self.best(difficulty).is_some_and(|best| best >= score) {
+            return false;
+        }
+        self.0.insert(difficulty, score);
+        true
+    }
+}
+
+/// Keeps the best endless scores between sessions. Needs storage, so it's only part of the full game.
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HighScores>()
+            .add_startup_system(startup_high_scores)
+            .add_system(keep_high_score);
+    }
+}
+
+fn startup_high_scores(mut high_scores: ResMut<HighScores>, game_config: Res<GameConfig>) {
+    if let Some(text) = storage::load(game_config.game_slug, HIGH_SCORES_KEY) {
+        match HighScores::from_ron(&text) {
+            Ok(loaded) => *high_scores = loaded,
+            Err(error) => warn!("Ignoring unreadable high scores: {}", error),
+        }
+    }
+}
+
+/// Records the score of an endless run someone is playing, along with its difficulty.
+fn keep_high_score(
+    score: Res<Score>,
+    difficulty: Res<Difficulty>,
+    active_level: Res<ActiveLevel>,
+    game_mode: Res<GameMode>,
+    mut high_scores: ResMut<HighScores>,
+    game_config: Res<GameConfig>,
+) {
+    if !score.is_changed() || active_level.0.is_some() || *game_mode != GameMode::Playing {
+        return;
+    }
+    if !high_scores.submit(*difficulty, score.0) {
+        return;
+    }
+
+    let saved = high_scores
+        .to_ron()
+        .map_err(anyhow::Error::from)
+        .and_then(|text| storage::save(game_config.game_slug, HIGH_SCORES_KEY, &text));
+    if let Err(error) = saved {
+        error!("Unable to save high scores: {}", error);
+    }
+}
@@ -0,0 +1,799 @@
+use bevy::{input::mouse::MouseWheel, prelude::*, window::ReceivedCharacter};
+use bevy_prototype_lyon::prelude::*;
+use strum::IntoEnumIterator;
+
+use crate::{
+    bump::{GameMode, MapConfig},
+    camera::GameCamera,
+    core::{
+        crystal::{Crystal, CrystalColor, CrystalKind},
+        event::ResetGame,
+        platform::PlatformKind,
+        player::Player,
+        timestep::Paused,
+    },
+    level::{ActiveLevel, CrystalWave, Level, LevelFinished, PlacedCrystal},
+    locale::Translations,
+    obstacle::{Obstacle, PlacedObstacle},
+};
+
+/// Where saved levels are written, relative to the working directory.
+const LEVELS_DIR: &str = "assets/levels";
+/// Edits remembered for undo.
+const UNDO_LIMIT: usize = 100;
+/// Seconds the timeline moves per mouse wheel notch or page key press.
+const TIME_STEP: f64 = 0.5;
+const PLATFORM_KINDS: [PlatformKind; 5] = [
+    PlatformKind::Solid,
+    PlatformKind::OneWay,
+    PlatformKind::Crumbling { delay: 0.75 },
+    PlatformKind::Conveyor { speed: 4. },
+    PlatformKind::Damaging,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorTool {
+    Crystal,
+    Platform,
+    Barrier,
+}
+
+/// Something placed in the level being edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorItem {
+    Crystal { wave: usize, index: usize },
+    Obstacle(usize),
+}
+
+/// The level being edited and how the editor is set up to change it.
+///
+/// The grid shows the map at `time` seconds into the level. Anything placed on it is
+/// given the spawn time and offset that has it arrive at that spot at that time.
+pub struct LevelEditor {
+    pub level: Level,
+    pub time: f64,
+    pub tool: EditorTool,
+    pub color: CrystalColor,
+    pub crystal_kind: CrystalKind,
+    pub platform_kind: PlatformKind,
+    /// The new name being typed in, while renaming the level.
+    pub name_draft: Option<String>,
+    undo: Vec<Level>,
+    redo: Vec<Level>,
+}
+
+impl Default for LevelEditor {
+    fn default() -> Self {
+        Self::new(Level {
+            name: "Untitled".to_string(),
+            ..default()
+        })
+    }
+}
+
+impl LevelEditor {
+    pub fn new(level: Level) -> Self {
+        Self {
+            level,
+            time: 0.,
+            tool: EditorTool::Crystal,
+            color: CrystalColor::Red,
+            crystal_kind: CrystalKind::Normal,
+            platform_kind: PlatformKind::Solid,
+            name_draft: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Applies a change to the level that can be undone.
+    pub fn edit(&mut self, change: impl FnOnce(&mut Level)) {
+        self.undo.push(self.level.clone());
+        if self.undo.len() > UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+        change(&mut self.level);
+        self.level.sort();
+    }
+
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(level) => {
+                self.redo.push(std::mem::replace(&mut self.level, level));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(level) => {
+                self.undo.push(std::mem::replace(&mut self.level, level));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// When and how far past the right edge something moving at `speed` has to enter
+    /// to be at `x` at the editor's time.
+    fn entry_for(&self, x: f32, speed: f32, map_config: &MapConfig) -> (f64, f32) {
+        let time = self.time - ((map_config.half_size().x - x) / speed) as f64;
+        // Rounded so things placed in the same column share a wave
+        let time = (time * 1000.).round() / 1000.;
+        if time >= 0. {
+            (time, 0.)
+        } else {
+            (0., speed * time as f32)
+        }
+    }
+
+    fn x_at(&self, time: f64, x_offset: f32, speed: f32, map_config: &MapConfig) -> f32 {
+        map_config.half_size().x + x_offset - speed * (self.time - time) as f32
+    }
+
+    /// Every crystal that has entered the map by the editor's time, and where it is.
+    pub fn crystals<'a>(
+        &'a self,
+        map_config: &'a MapConfig,
+    ) -> impl Iterator<Item = (EditorItem, Vec2, &'a PlacedCrystal)> + 'a {
+        self.level
+            .waves
+            .iter()
+            .enumerate()
+            .filter(|(_, wave)| wave.time <= self.time)
+            .flat_map(move |(wave_index, wave)| {
+                wave.crystals
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, crystal)| {
+                        let speed = map_config.crystal_linvel * crystal.kind.speed();
+                        let x = self.x_at(wave.time, crystal.x_offset, speed, map_config);
+                        let item = EditorItem::Crystal {
+                            wave: wave_index,
+                            index,
+                        };
+                        (item, Vec2::new(x, crystal.y), crystal)
+                    })
+            })
+    }
+
+    /// Every obstacle that has entered the map by the editor's time, and where its center is.
+    pub fn obstacles<'a>(
+        &'a self,
+        map_config: &'a MapConfig,
+    ) -> impl Iterator<Item = (EditorItem, Vec2, &'a PlacedObstacle)> + 'a {
+        self.level
+            .obstacles
+            .iter()
+            .enumerate()
+            .filter(|(_, obstacle)| obstacle.time <= self.time)
+            .map(move |(index, obstacle)| {
+                let left = self.x_at(
+                    obstacle.time,
+                    obstacle.x_offset,
+                    map_config.crystal_linvel,
+                    map_config,
+                );
+                let position = Vec2::new(left + obstacle.width / 2., obstacle.y);
+                (EditorItem::Obstacle(index), position, obstacle)
+            })
+    }
+
+    /// Places whatever the current tool makes, centered on `position` at the editor's time.
+    pub fn place(&mut self, position: Vec2, map_config: &MapConfig) {
+        match self.tool {
+            EditorTool::Crystal => {
+                let speed = map_config.crystal_linvel * self.crystal_kind.speed();
+                let (time, x_offset) = self.entry_for(position.x, speed, map_config);
+                let crystal = PlacedCrystal {
+                    y: position.y,
+                    x_offset,
+                    color: self.color,
+                    kind: self.crystal_kind,
+                };
+                self.edit(
+                    |level| match level.waves.iter_mut().find(|wave| wave.time == time) {
+                        Some(wave) => wave.crystals.push(crystal),
+                        None => level.waves.push(CrystalWave {
+                            time,
+                            crystals: vec![crystal],
+                        }),
+                    },
+                );
+            }
+            EditorTool::Platform | EditorTool::Barrier => {
+                let (width, height, platform, barrier) = match self.tool {
+                    EditorTool::Barrier => (1., 4., PlatformKind::Solid, Some(self.color)),
+                    _ => (4., 0.5, self.platform_kind, None),
+                };
+                let (time, x_offset) = self.entry_for(
+                    position.x - width / 2.,
+                    map_config.crystal_linvel,
+                    map_config,
+                );
+                let obstacle = PlacedObstacle {
+                    time,
+                    x_offset,
+                    y: position.y,
+                    width,
+                    height,
+                    platform,
+                    restitution: None,
+                    barrier,
+                    amplitude: 0.,
+                };
+                self.edit(|level| level.obstacles.push(obstacle));
+            }
+        }
+    }
+
+    /// The topmost item under `position`, crystals first.
+    pub fn item_at(&self, position: Vec2, map_config: &MapConfig) -> Option<EditorItem> {
+        let crystal = self
+            .crystals(map_config)
+            .find(|(_, center, _)| center.distance(position) <= 1.)
+            .map(|(item, _, _)| item);
+        crystal.or_else(|| {
+            self.obstacles(map_config)
+                .find(|(_, center, obstacle)| {
+                    let offset = (position - *center).abs();
+                    offset.x <= obstacle.width / 2. + 0.5 && offset.y <= obstacle.height / 2. + 0.5
+                })
+                .map(|(item, _, _)| item)
+        })
+    }
+
+    pub fn remove(&mut self, item: EditorItem) {
+        self.edit(|level| match item {
+            EditorItem::Crystal { wave, index } => {
+                level.waves[wave].crystals.remove(index);
+                if level.waves[wave].crystals.is_empty() {
+                    level.waves.remove(wave);
+                }
+            }
+            EditorItem::Obstacle(index) => {
+                level.obstacles.remove(index);
+            }
+        });
+    }
+
+    pub fn push_target(&mut self, color: CrystalColor) {
+        self.edit(|level| level.targets.push(color));
+    }
+
+    pub fn pop_target(&mut self) {
+        if !self.level.targets.is_empty() {
+            self.edit(|level| {
+                level.targets.pop();
+            });
+        }
+    }
+
+    /// Starts the player out in `color`, or without a color if they already start in it.
+    pub fn toggle_start_color(&mut self, color: CrystalColor) {
+        self.edit(|level| {
+            level.start_color = match level.start_color {
+                Some(start_color) if start_color == color => None,
+                _ => Some(color),
+            }
+        });
+    }
+
+    /// The level as it stands at the editor's time, with the player starting at `position`.
+    /// Anything already on the map enters at once, the rest keeps its timing.
+    pub fn playtest_level(&self, position: Vec2, map_config: &MapConfig) -> Level {
+        let hmap_size = map_config.half_size();
+        let mut level = self.level.clone();
+        level.start_position = Some((position.x, position.y));
+
+        let mut on_map = CrystalWave {
+            time: 0.,
+            crystals: Vec::new(),
+        };
+        level.waves.clear();
+        for (_, center, crystal) in self.crystals(map_config) {
+            if center.x >= -hmap_size.x {
+                on_map.crystals.push(PlacedCrystal {
+                    x_offset: center.x - hmap_size.x,
+                    ..crystal.clone()
+                });
+            }
+        }
+        if !on_map.crystals.is_empty() {
+            level.waves.push(on_map);
+        }
+        for wave in self.level.waves.iter().filter(|wave| wave.time > self.time) {
+            level.waves.push(CrystalWave {
+                time: wave.time - self.time,
+                ..wave.clone()
+            });
+        }
+
+        level.obstacles.clear();
+        for (_, center, obstacle) in self.obstacles(map_config) {
+            if center.x + obstacle.width / 2. >= -hmap_size.x {
+                level.obstacles.push(PlacedObstacle {
+                    time: 0.,
+                    x_offset: center.x - obstacle.width / 2. - hmap_size.x,
+                    ..obstacle.clone()
+                });
+            }
+        }
+        for obstacle in self
+            .level
+            .obstacles
+            .iter()
+            .filter(|obstacle| obstacle.time > self.time)
+        {
+            level.obstacles.push(PlacedObstacle {
+                time: obstacle.time - self.time,
+                ..obstacle.clone()
+            });
+        }
+
+        level.sort();
+        level
+    }
+
+    /// Renames the level, which also names the file it's saved to. Blank names are ignored.
+    pub fn rename(&mut self, name: &str) {
+        let name = name.trim();
+        if !name.is_empty() && name != self.level.name {
+            let name = name.to_string();
+            self.edit(|level| level.name = name);
+        }
+    }
+
+    /// The file the level is saved to, named after it.
+    pub fn file_name(&self) -> String {
+        let slug: String = self
+            .level
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        format!("{}.level.ron", slug.trim_matches('-'))
+    }
+}
+
+#[derive(Component)]
+struct EditorHud;
+#[derive(Component)]
+struct EditorPreview;
+
+/// Press F2 while playing to build levels on the map grid: place crystals, platforms and barriers
+/// along a timeline, set the targets, play-test and save to the level format.
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelEditor>()
+            .add_startup_system(startup_editor_hud)
+            .add_system(toggle_editor)
+            .add_system(finish_playtest)
+            .add_system(editor_input.after(toggle_editor))
+            .add_system(
+                editor_visibility
+                    .after(toggle_editor)
+                    .after(finish_playtest),
+            )
+            .add_system(editor_preview.after(editor_input))
+            .add_system(update_editor_hud.after(editor_input));
+    }
+}
+
+fn startup_editor_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/Hind-Regular.otf");
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font,
+                    font_size: 20.,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.),
+                    top: Val::Px(10.),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(Visibility { is_visible: false })
+        .insert(EditorHud);
+}
+
+/// F2 opens the editor from a game, and F2 or Escape leave it unless a new name is being
+/// typed in. Escape ends a play-test.
+fn toggle_editor(
+    keys: Res<Input<KeyCode>>,
+    mut game_mode: ResMut<GameMode>,
+    mut paused: ResMut<Paused>,
+    mut editor: ResMut<LevelEditor>,
+    mut active_level: ResMut<ActiveLevel>,
+    mut reset_events: EventWriter<ResetGame>,
+) {
+    match *game_mode {
+        GameMode::Playing if keys.just_pressed(KeyCode::F2) => {
+            // Pick up the level being played, if there is one
+            if let Some(level) = &active_level.0 {
+                let level = level.clone();
+                editor.edit(|edited| *edited = level);
+            }
+            *game_mode = GameMode::Editing;
+            *paused = Paused(true);
+        }
+        GameMode::Editing
+            if editor.name_draft.is_none()
+                && keys.any_just_pressed([KeyCode::F2, KeyCode::Escape]) =>
+        {
+            *game_mode = GameMode::Attract;
+            *paused = Paused(false);
+            *active_level = ActiveLevel(None);
+            reset_events.send(ResetGame);
+        }
+        GameMode::Testing if keys.just_pressed(KeyCode::Escape) => {
+            *game_mode = GameMode::Editing;
+            *paused = Paused(true);
+        }
+        _ => {}
+    }
+}
+
+/// A play-test goes back to the editor once the level is won or lost.
+fn finish_playtest(
+    mut finished_events: EventReader<LevelFinished>,
+    mut game_mode: ResMut<GameMode>,
+    mut paused: ResMut<Paused>,
+) {
+    for event in finished_events.iter() {
+        if *game_mode == GameMode::Testing {
+            info!(
+                "Play-test finished: {:?} in {:.1}s",
+                event.outcome, event.time
+            );
+            *game_mode = GameMode::Editing;
+            *paused = Paused(true);
+        }
+    }
+}
+
+fn cursor_position(
+    windows: &Windows,
+    cameras: &Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+) -> Option<Vec2> {
+    let window = windows.get_primary()?;
+    let cursor = window.cursor_position()?;
+    let (camera, camera_transform) = cameras.iter().next()?;
+
+    let window_size = Vec2::new(window.width(), window.height());
+    let ndc = cursor / window_size * 2. - Vec2::ONE;
+    let ndc_to_world = camera_transform.compute_matrix() * camera.projection_matrix().inverse();
+    Some(ndc_to_world.project_point3(ndc.extend(-1.)).truncate())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn editor_input(
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    mut wheel_events: EventReader<MouseWheel>,
+    mut char_events: EventReader<ReceivedCharacter>,
+    windows: Res<Windows>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    mut editor: ResMut<LevelEditor>,
+    mut game_mode: ResMut<GameMode>,
+    mut paused: ResMut<Paused>,
+    mut active_level: ResMut<ActiveLevel>,
+    map_config: Res<MapConfig>,
+) {
+    let scroll: f32 = wheel_events.iter().map(|event| event.y.signum()).sum();
+    let typed: String = char_events
+        .iter()
+        .map(|event| event.char)
+        .filter(|c| !c.is_control())
+        .collect();
+    if *game_mode != GameMode::Editing {
+        return;
+    }
+
+    // While renaming, keys type into the name instead
+    if let Some(mut draft) = editor.name_draft.clone() {
+        draft.push_str(&typed);
+        if keys.just_pressed(KeyCode::Back) {
+            draft.pop();
+        }
+        if keys.just_pressed(KeyCode::Return) {
+            editor.name_draft = None;
+            editor.rename(&draft);
+        } else if keys.just_pressed(KeyCode::Escape) {
+            editor.name_draft = None;
+        } else if editor.name_draft.as_ref() != Some(&draft) {
+            editor.name_draft = Some(draft);
+        }
+        return;
+    }
+
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    let hmap_size = map_config.half_size();
+    // Snapped to the grid lines, and only on the map
+    let cursor = cursor_position(&windows, &cameras)
+        .map(|position| position.round())
+        .filter(|position| position.x.abs() <= hmap_size.x && position.y.abs() <= hmap_size.y);
+
+    if ctrl {
+        if keys.just_pressed(KeyCode::Z) && !shift {
+            editor.undo();
+        }
+        if keys.just_pressed(KeyCode::Y) || (keys.just_pressed(KeyCode::Z) && shift) {
+            editor.redo();
+        }
+        if keys.just_pressed(KeyCode::S) {
+            save_level(&editor);
+        }
+        return;
+    }
+
+    let mut time_delta = scroll as f64 * TIME_STEP;
+    if keys.just_pressed(KeyCode::PageUp) {
+        time_delta += TIME_STEP;
+    }
+    if keys.just_pressed(KeyCode::PageDown) {
+        time_delta -= TIME_STEP;
+    }
+    if time_delta != 0. {
+        editor.time = (editor.time + time_delta).max(0.);
+    }
+
+    if keys.just_pressed(KeyCode::Key1) {
+        editor.tool = EditorTool::Crystal;
+    }
+    if keys.just_pressed(KeyCode::Key2) {
+        editor.tool = EditorTool::Platform;
+    }
+    if keys.just_pressed(KeyCode::Key3) {
+        editor.tool = EditorTool::Barrier;
+    }
+
+    let colors: Vec<CrystalColor> = CrystalColor::iter().collect();
+    let color_index = editor.color as usize;
+    if keys.just_pressed(KeyCode::Q) {
+        editor.color = colors[(color_index + colors.len() - 1) % colors.len()];
+    }
+    if keys.just_pressed(KeyCode::E) {
+        editor.color = colors[(color_index + 1) % colors.len()];
+    }
+    if keys.just_pressed(KeyCode::Tab) {
+        match editor.tool {
+            EditorTool::Crystal => {
+                editor.crystal_kind = match editor.crystal_kind {
+                    CrystalKind::Normal => CrystalKind::Fast,
+                    CrystalKind::Fast => CrystalKind::Slow,
+                    CrystalKind::Slow => CrystalKind::Normal,
+                }
+            }
+            _ => {
+                let index = PLATFORM_KINDS
+                    .iter()
+                    .position(|kind| *kind == editor.platform_kind)
+                    .map_or(0, |index| index + 1);
+                editor.platform_kind = PLATFORM_KINDS[index % PLATFORM_KINDS.len()];
+            }
+        }
+    }
+
+    if keys.just_pressed(KeyCode::T) {
+        let color = editor.color;
+        editor.push_target(color);
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        editor.pop_target();
+    }
+    if keys.just_pressed(KeyCode::C) {
+        let color = editor.color;
+        editor.toggle_start_color(color);
+    }
+    if keys.just_pressed(KeyCode::N) {
+        editor.name_draft = Some(editor.level.name.clone());
+    }
+
+    let cursor = match cursor {
+        Some(cursor) => cursor,
+        None => return,
+    };
+    if mouse.just_pressed(MouseButton::Left) {
+        editor.place(cursor, &map_config);
+    }
+    if mouse.just_pressed(MouseButton::Right) {
+        if let Some(item) = editor.item_at(cursor, &map_config) {
+            editor.remove(item);
+        }
+    }
+    if keys.just_pressed(KeyCode::P) {
+        *active_level = ActiveLevel(Some(editor.playtest_level(cursor, &map_config)));
+        *game_mode = GameMode::Testing;
+        *paused = Paused(false);
+    }
+}
+
+fn save_level(editor: &LevelEditor) {
+    let ron = match editor.level.to_ron() {
+        Ok(ron) => ron,
+        Err(error) => {
+            error!("Unable to save level: {}", error);
+            return;
+        }
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let path = std::path::Path::new(LEVELS_DIR).join(editor.file_name());
+        match std::fs::write(&path, ron) {
+            Ok(()) => info!("Saved level to {}", path.display()),
+            Err(error) => error!("Unable to save level to {}: {}", path.display(), error),
+        }
+    }
+    // There's no filesystem on the web, so the level is logged to be copied out of the console
+    #[cfg(target_arch = "wasm32")]
+    info!("{}/{}:\n{}", LEVELS_DIR, editor.file_name(), ron);
+}
+
+/// The paused game is hidden behind the editor's own preview of the level.
+#[allow(clippy::type_complexity)]
+fn editor_visibility(
+    game_mode: Res<GameMode>,
+    mut game_entities: Query<
+        &mut Visibility,
+        (
+            Or<(With<Crystal>, With<Obstacle>, With<Player>)>,
+            Without<EditorHud>,
+        ),
+    >,
+    mut hud: Query<&mut Visibility, With<EditorHud>>,
+) {
+    if !game_mode.is_changed() {
+        return;
+    }
+
+    let editing = *game_mode == GameMode::Editing;
+    for mut visibility in &mut game_entities {
+        visibility.is_visible = !editing;
+    }
+    for mut visibility in &mut hud {
+        visibility.is_visible = editing;
+    }
+}
+
+/// Redraws the level as it looks at the editor's time.
+fn editor_preview(
+    mut commands: Commands,
+    editor: Res<LevelEditor>,
+    game_mode: Res<GameMode>,
+    map_config: Res<MapConfig>,
+    previews: Query<Entity, With<EditorPreview>>,
+) {
+    if !editor.is_changed() && !game_mode.is_changed() && !map_config.is_changed() {
+        return;
+    }
+
+    for entity in &previews {
+        commands.entity(entity).despawn_recursive();
+    }
+    if *game_mode != GameMode::Editing {
+        return;
+    }
+
+    let hmap_size = map_config.half_size();
+    let on_map = |position: Vec2, half_width: f32| {
+        position.x + half_width >= -hmap_size.x && position.x - half_width <= hmap_size.x
+    };
+    let shape = shapes::RegularPolygon {
+        sides: 6,
+        feature: shapes::RegularPolygonFeature::Radius(1.),
+        ..default()
+    };
+    for (_, position, crystal) in editor.crystals(&map_config) {
+        if on_map(position, 1.) {
+            commands
+                .spawn_bundle(GeometryBuilder::build_as(
+                    &shape,
+                    crystal.color.to_draw_mode(),
+                    Transform::from_xyz(position.x, position.y, 75.),
+                ))
+                .insert(EditorPreview);
+        }
+    }
+    for (_, position, obstacle) in editor.obstacles(&map_config) {
+        if on_map(position, obstacle.width / 2.) {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: obstacle.color(),
+                        custom_size: Some(Vec2::new(obstacle.width, obstacle.height)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(position.x, position.y, 70.),
+                    ..default()
+                })
+                .insert(EditorPreview);
+        }
+    }
+}
+
+fn update_editor_hud(
+    editor: Res<LevelEditor>,
+    translations: Res<Translations>,
+    mut hud: Query<&mut Text, With<EditorHud>>,
+) {
+    if !editor.is_changed() && !translations.is_changed() {
+        return;
+    }
+
+    let color = translations.text(editor.color.message_id());
+    let brush = match editor.tool {
+        EditorTool::Crystal => translations.format(
+            "editor-crystal",
+            &[
+                ("color", &color),
+                ("kind", &translations.text(editor.crystal_kind.message_id())),
+            ],
+        ),
+        EditorTool::Platform => translations.format(
+            "editor-platform",
+            &[(
+                "kind",
+                &translations.text(editor.platform_kind.message_id()),
+            )],
+        ),
+        EditorTool::Barrier => translations.format("editor-barrier", &[("color", &color)]),
+    };
+    let targets: Vec<String> = editor
+        .level
+        .targets
+        .iter()
+        .map(|color| translations.text(color.message_id()))
+        .collect();
+    let start = translations.text(
+        editor
+            .level
+            .start_color
+            .map_or("editor-no-color", |color| color.message_id()),
+    );
+    let name = match &editor.name_draft {
+        Some(draft) => translations.format("editor-renaming", &[("name", draft)]),
+        None => editor.level.name.clone(),
+    };
+
+    for mut text in &mut hud {
+        text.sections[0].value = [
+            translations.format(
+                "editor-title",
+                &[
+                    ("name", &name),
+                    ("time", &format!("{:.1}", editor.time)),
+                    ("brush", &brush),
+                ],
+            ),
+            translations.format(
+                "editor-colors",
+                &[("start", &start), ("targets", &targets.join(", "))],
+            ),
+            translations.text("editor-keys"),
+        ]
+        .join("\n");
+    }
+}
@@ -0,0 +1,296 @@
+//! Juice: particle bursts when crystals are collected, a shockwave when a target is reached,
+//! screen shake and a short hit-stop. None of it touches the simulation's outcome.
+
+use bevy::{math::Vec3Swizzles, prelude::*};
+use bevy_prototype_lyon::prelude::*;
+
+use crate::{
+    bump::MapConfig,
+    camera::{clamp_to_map, half_view, CameraFollow, GameCamera},
+    core::{
+        event::{CrystalCollected, ObstacleHit, ResetGame, TargetReached},
+        player::Player,
+        timestep::HitStop,
+    },
+    tween::{Ease, Tween},
+};
+
+/// Particles in a full strength burst.
+const BURST_PARTICLES: usize = 14;
+const PARTICLE_LIFETIME: f32 = 0.5;
+const PARTICLE_SPEED: f32 = 6.;
+/// Fraction of a particle's speed kept each second.
+const PARTICLE_DRAG: f32 = 0.05;
+const SHOCKWAVE_LIFETIME: f32 = 0.45;
+const SHOCKWAVE_RADIUS: f32 = 5.;
+/// Seconds the game holds still when a target is reached.
+const HIT_STOP_TIME: f64 = 0.06;
+/// Furthest the camera is thrown by a shake at full trauma, in world units.
+const SHAKE_OFFSET: f32 = 0.6;
+const SHAKE_ROLL: f32 = 0.03;
+/// Trauma lost per second.
+const SHAKE_DECAY: f32 = 1.8;
+
+/// How strong the effects are, picked on the title screen.
+/// `ReducedMotion` turns them all off for players who are sensitive to motion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EffectIntensity {
+    ReducedMotion,
+    Low,
+    #[default]
+    Full,
+}
+
+impl EffectIntensity {
+    /// Id of the setting's name in the message files.
+    pub fn message_id(self) -> &'static str {
+        match self {
+            EffectIntensity::ReducedMotion => "effects-reduced-motion",
+            EffectIntensity::Low => "effects-low",
+            EffectIntensity::Full => "effects-full",
+        }
+    }
+
+    /// The setting after this one, wrapping round.
+    pub fn next(self) -> Self {
+        match self {
+            EffectIntensity::ReducedMotion => EffectIntensity::Low,
+            EffectIntensity::Low => EffectIntensity::Full,
+            EffectIntensity::Full => EffectIntensity::ReducedMotion,
+        }
+    }
+
+    /// Multiplier for particle counts, shake and hit-stop.
+    pub fn scale(self) -> f32 {
+        match self {
+            EffectIntensity::ReducedMotion => 0.,
+            EffectIntensity::Low => 0.5,
+            EffectIntensity::Full => 1.,
+        }
+    }
+}
+
+/// How shaken up the camera is, between 0 and 1. The shake grows with the square of it,
+/// so small bumps barely move the view.
+#[derive(Debug, Default)]
+pub struct ScreenShake {
+    pub trauma: f32,
+}
+
+impl ScreenShake {
+    pub fn add(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).min(1.);
+    }
+}
+
+/// One of the bits a collected crystal bursts into.
+#[derive(Component)]
+pub struct Particle {
+    velocity: Vec2,
+    spawned_at: f64,
+}
+
+/// The ring rushing out from a player who reached the target.
+#[derive(Component)]
+pub struct Shockwave;
+
+/// Despawned once `Time` passes this.
+#[derive(Component)]
+struct Expires(f64);
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EffectIntensity>()
+            .init_resource::<ScreenShake>()
+            .add_system(collection_burst)
+            .add_system(target_shockwave)
+            .add_system(obstacle_shake)
+            .add_system(update_particles)
+            .add_system(expire_effects)
+            .add_system(clear_effects)
+            .add_system(screen_shake.after(CameraFollow));
+    }
+}
+
+fn collection_burst(
+    mut commands: Commands,
+    mut collected_events: EventReader<CrystalCollected>,
+    intensity: Res<EffectIntensity>,
+    time: Res<Time>,
+) {
+    let now = time.seconds_since_startup();
+    let count = (BURST_PARTICLES as f32 * intensity.scale()).round() as usize;
+
+    for event in collected_events.iter() {
+        let color = event.crystal_color.to_color();
+        for i in 0..count {
+            // Spread evenly round the circle, with a little wobble so bursts don't look stamped
+            let angle = (i as f32 + (now as f32 * 7.3 + i as f32 * 1.7).sin() * 0.3)
+                * 2.
+                * std::f32::consts::PI
+                / count as f32;
+            let speed = PARTICLE_SPEED * (0.6 + 0.4 * (i as f32 * 2.3).sin().abs());
+            commands
+                .spawn_bundle(GeometryBuilder::build_as(
+                    &shapes::RegularPolygon {
+                        sides: 6,
+                        feature: shapes::RegularPolygonFeature::Radius(0.15),
+                        ..default()
+                    },
+                    DrawMode::Fill(bevy_prototype_lyon::prelude::FillMode::color(color)),
+                    Transform::from_translation(event.position.extend(110.)),
+                ))
+                .insert(Particle {
+                    velocity: Vec2::new(angle.cos(), angle.sin()) * speed,
+                    spawned_at: now,
+                })
+                .insert(Expires(now + PARTICLE_LIFETIME as f64));
+        }
+    }
+}
+
+/// A ring that rushes out from the player and fades, plus a jolt of shake and hit-stop.
+fn target_shockwave(
+    mut commands: Commands,
+    mut target_reached_events: EventReader<TargetReached>,
+    mut screen_shake: ResMut<ScreenShake>,
+    mut hit_stop: ResMut<HitStop>,
+    players: Query<&Transform, With<Player>>,
+    intensity: Res<EffectIntensity>,
+    time: Res<Time>,
+) {
+    let now = time.seconds_since_startup();
+    for event in target_reached_events.iter() {
+        if *intensity == EffectIntensity::ReducedMotion {
+            continue;
+        }
+
+        screen_shake.add(0.6 * intensity.scale());
+        hit_stop.0 = now + HIT_STOP_TIME * intensity.scale() as f64;
+
+        let position = match players.get(event.player) {
+            Ok(transform) => transform.translation.xy(),
+            Err(_) => continue,
+        };
+        let color = event.color.to_color();
+        let mut faded = color;
+        faded.set_a(0.);
+        let start = Transform::from_translation(position.extend(105.)).with_scale(Vec3::splat(0.2));
+        let end = start.with_scale(Vec3::splat(1.));
+
+        commands
+            .spawn_bundle(GeometryBuilder::build_as(
+                &shapes::Circle {
+                    radius: SHOCKWAVE_RADIUS,
+                    ..default()
+                },
+                DrawMode::Stroke(StrokeMode::new(color, 0.4)),
+                start,
+            ))
+            .insert(Tween::new(start, end, now, SHOCKWAVE_LIFETIME).with_ease(Ease::Out))
+            .insert(
+                Tween::new(
+                    DrawMode::Stroke(StrokeMode::new(color, 0.4)),
+                    DrawMode::Stroke(StrokeMode::new(faded, 0.05)),
+                    now,
+                    SHOCKWAVE_LIFETIME,
+                )
+                .with_ease(Ease::Out),
+            )
+            .insert(Shockwave)
+            .insert(Expires(now + SHOCKWAVE_LIFETIME as f64));
+    }
+}
+
+fn obstacle_shake(
+    mut hit_events: EventReader<ObstacleHit>,
+    mut screen_shake: ResMut<ScreenShake>,
+    intensity: Res<EffectIntensity>,
+) {
+    for _ in hit_events.iter() {
+        screen_shake.add(0.4 * intensity.scale());
+    }
+}
+
+/// Particles fly out, slow down and shrink away.
+fn update_particles(mut particles: Query<(&mut Transform, &mut Particle)>, time: Res<Time>) {
+    let now = time.seconds_since_startup();
+    let dt = time.delta_seconds();
+    let drag = PARTICLE_DRAG.powf(dt);
+
+    for (mut transform, mut particle) in &mut particles {
+        particle.velocity *= drag;
+        transform.translation += (particle.velocity * dt).extend(0.);
+
+        let age = ((now - particle.spawned_at) as f32 / PARTICLE_LIFETIME).clamp(0., 1.);
+        transform.scale = Vec3::splat(1. - age);
+    }
+}
+
+fn expire_effects(mut commands: Commands, effects: Query<(Entity, &Expires)>, time: Res<Time>) {
+    let now = time.seconds_since_startup();
+    for (entity, expires) in &effects {
+        if now >= expires.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// A fresh run starts without leftovers from the last one.
+fn clear_effects(
+    mut commands: Commands,
+    mut reset_events: EventReader<ResetGame>,
+    mut screen_shake: ResMut<ScreenShake>,
+    effects: Query<Entity, With<Expires>>,
+) {
+    if reset_events.iter().count() == 0 {
+        return;
+    }
+
+    screen_shake.trauma = 0.;
+    for entity in &effects {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Throws the camera about on top of wherever `camera_follow` put it, still never showing
+/// past the border platforms.
+fn screen_shake(
+    mut cameras: Query<(&mut Transform, &OrthographicProjection), With<GameCamera>>,
+    mut screen_shake: ResMut<ScreenShake>,
+    intensity: Res<EffectIntensity>,
+    time: Res<Time>,
+    map_config: Res<MapConfig>,
+) {
+    if *intensity == EffectIntensity::ReducedMotion {
+        screen_shake.trauma = 0.;
+    }
+    screen_shake.trauma = (screen_shake.trauma - SHAKE_DECAY * time.delta_seconds()).max(0.);
+
+    // Overlapping sine waves are smooth enough to look like a shake rather than noise
+    let t = time.seconds_since_startup() as f32;
+    let shake = screen_shake.trauma * screen_shake.trauma;
+    let offset = Vec2::new(
+        (t * 37.).sin() + (t * 61.).sin() * 0.5,
+        (t * 43.).cos() + (t * 71.).sin() * 0.5,
+    ) * SHAKE_OFFSET
+        * shake
+        / 1.5;
+    let roll = (t * 29.).sin() * SHAKE_ROLL * shake;
+
+    for (mut transform, projection) in &mut cameras {
+        // A rolled view reaches further out at its corners
+        let half_view = half_view(projection);
+        let (sin, cos) = (roll.sin().abs(), roll.cos().abs());
+        let rolled_half_view = Vec2::new(
+            cos * half_view.x + sin * half_view.y,
+            sin * half_view.x + cos * half_view.y,
+        );
+        let position = transform.translation.truncate() + offset;
+        let position = clamp_to_map(position, rolled_half_view, &map_config);
+        transform.translation = position.extend(transform.translation.z);
+        transform.rotation = Quat::from_rotation_z(roll);
+    }
+}
@@ -0,0 +1,236 @@
+use bevy::{ecs::event::ManualEventReader, math::Vec3Swizzles, prelude::*};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    bump::MovementMode,
+    core::{
+        crystal::{Crystal, CrystalColor},
+        event::{ColorChanged, PlayerOutOfBounds, TargetReached},
+        player::Player,
+    },
+    harness::Simulation,
+};
+
+/// Settings for a `BumpEnv`, fixed for its lifetime.
+#[derive(Debug, Clone)]
+pub struct EnvConfig {
+    /// How many of the closest crystals are included in each observation.
+    pub nearest_crystals: usize,
+    /// Gameplay steps simulated per `step` call, repeating the same action.
+    pub frame_skip: u32,
+    /// Episode length in `step` calls.
+    pub max_steps: u32,
+    pub movement_mode: MovementMode,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            nearest_crystals: 8,
+            frame_skip: 4,
+            max_steps: 60 * 60 * 2 / 4,
+            movement_mode: MovementMode::Flap,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Action {
+    pub flap: bool,
+    /// -1 to 1, only has an effect in `MovementMode::Free`.
+    pub horizontal: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrystalObservation {
+    /// Position relative to the player.
+    pub offset: Vec2,
+    pub velocity: Vec2,
+    pub color: CrystalColor,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    pub player_position: Vec2,
+    pub player_velocity: Vec2,
+    pub current_color: Option<CrystalColor>,
+    pub target_color: CrystalColor,
+    /// Closest crystals first. Shorter than `nearest_crystals` only if there are fewer crystals.
+    pub crystals: Vec<CrystalObservation>,
+}
+
+/// Colors as a point on the unit circle at their wedge's angle, or the origin for no color.
+fn color_features(color: Option<CrystalColor>) -> [f32; 2] {
+    match color {
+        Some(color) => {
+            let angle = color as usize as f32 * std::f32::consts::TAU / 12.;
+            [angle.cos(), angle.sin()]
+        }
+        None => [0., 0.],
+    }
+}
+
+impl Observation {
+    /// Flattens the observation into a fixed size vector for learning code.
+    /// Missing crystals are padded with zeros so the length only depends on `nearest_crystals`.
+    pub fn to_features(&self, nearest_crystals: usize) -> Vec<f32> {
+        let mut features = vec![
+            self.player_position.x,
+            self.player_position.y,
+            self.player_velocity.x,
+            self.player_velocity.y,
+        ];
+        features.extend(color_features(self.current_color));
+        features.extend(color_features(Some(self.target_color)));
+
+        for i in 0..nearest_crystals {
+            match self.crystals.get(i) {
+                Some(crystal) => {
+                    features.extend([
+                        crystal.offset.x,
+                        crystal.offset.y,
+                        crystal.velocity.x,
+                        crystal.velocity.y,
+                    ]);
+                    features.extend(color_features(Some(crystal.color)));
+                }
+                None => features.extend([0.; 6]),
+            }
+        }
+
+        features
+    }
+}
+
+/// A reinforcement learning environment around a headless game.
+///
+/// Each environment owns its own `App`, so any number can run side by side in one process.
+/// Rewards are 1 per target reached, plus a small bonus or penalty for every wedge the player
+/// moves toward or away from the target, and a penalty for leaving the map.
+pub struct BumpEnv {
+    pub config: EnvConfig,
+    sim: Simulation,
+    steps: u32,
+    color_changed_reader: ManualEventReader<ColorChanged>,
+    target_reached_reader: ManualEventReader<TargetReached>,
+    out_of_bounds_reader: ManualEventReader<PlayerOutOfBounds>,
+}
+
+impl BumpEnv {
+    pub fn new(config: EnvConfig) -> Self {
+        Self {
+            sim: Self::simulation(&config, 0),
+            config,
+            steps: 0,
+            color_changed_reader: default(),
+            target_reached_reader: default(),
+            out_of_bounds_reader: default(),
+        }
+    }
+
+    /// Starts a new episode. The same seed and actions always replay the same episode.
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.sim = Self::simulation(&self.config, seed);
+        self.steps = 0;
+        self.color_changed_reader = default();
+        self.target_reached_reader = default();
+        self.out_of_bounds_reader = default();
+
+        self.observation()
+    }
+
+    fn simulation(config: &EnvConfig, seed: u64) -> Simulation {
+        let mut sim = Simulation::with_seed(seed);
+        sim.app.insert_resource(config.movement_mode);
+        sim
+    }
+
+    /// Applies `action` for `frame_skip` gameplay steps.
+    pub fn step(&mut self, action: Action) -> (Observation, f32, bool) {
+        let movement = Vec2::new(action.horizontal.clamp(-1., 1.), action.flap as u8 as f32);
+        let mut reward = 0.;
+        for _ in 0..self.config.frame_skip {
+            // Reaching the target picks a new one, so changes are judged against the one before
+            let target = self.sim.target_color();
+            self.sim.set_movement(movement);
+            self.sim.step();
+            // Events only live for two frames, so rewards are collected after every step
+            reward += self.collect_reward(target);
+        }
+        self.steps += 1;
+
+        let done = self.steps >= self.config.max_steps;
+        (self.observation(), reward, done)
+    }
+
+    pub fn score(&self) -> u32 {
+        self.sim.score()
+    }
+
+    /// The game being played, for setting up situations to learn or test from.
+    pub fn simulation_mut(&mut self) -> &mut Simulation {
+        &mut self.sim
+    }
+
+    fn collect_reward(&mut self, target: CrystalColor) -> f32 {
+        let world = self.sim.world();
+        let mut reward = 0.;
+
+        let target_reached = world.resource::<Events<TargetReached>>();
+        reward += self.target_reached_reader.iter(target_reached).count() as f32;
+
+        let color_changed = world.resource::<Events<ColorChanged>>();
+        for event in self.color_changed_reader.iter(color_changed) {
+            // Reaching the target is already rewarded, and picks a new one
+            if let (Some(from), Some(to)) = (event.from, event.to) {
+                if to != target {
+                    let progress = from.steps_to(&target).abs() - to.steps_to(&target).abs();
+                    reward += 0.1 * progress as f32;
+                }
+            }
+        }
+
+        let out_of_bounds = world.resource::<Events<PlayerOutOfBounds>>();
+        reward -= 0.5 * self.out_of_bounds_reader.iter(out_of_bounds).count() as f32;
+
+        reward
+    }
+
+    pub fn observation(&mut self) -> Observation {
+        let mut players = self
+            .sim
+            .world_mut()
+            .query_filtered::<(&Transform, &Velocity, &Player), Without<Crystal>>();
+        let (player_position, player_velocity, current_color) = players
+            .iter(self.sim.world())
+            .next()
+            .map(|(transform, velocity, player)| {
+                (transform.translation.xy(), velocity.linvel, player.color)
+            })
+            .expect("No player spawned");
+
+        let mut crystals_query = self
+            .sim
+            .world_mut()
+            .query_filtered::<(&Transform, &Velocity, &Crystal), Without<Player>>();
+        let mut crystals: Vec<CrystalObservation> = crystals_query
+            .iter(self.sim.world())
+            .filter(|(_, _, crystal)| !crystal.collected)
+            .map(|(transform, velocity, crystal)| CrystalObservation {
+                offset: transform.translation.xy() - player_position,
+                velocity: velocity.linvel,
+                color: crystal.crystal_color,
+            })
+            .collect();
+        crystals.sort_by(|a, b| a.offset.length().total_cmp(&b.offset.length()));
+        crystals.truncate(self.config.nearest_crystals);
+
+        Observation {
+            player_position,
+            player_velocity,
+            current_color,
+            target_color: self.sim.target_color(),
+            crystals,
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use bevy::{
+    input::{keyboard::KeyboardInput, ButtonState},
+    math::Vec3Swizzles,
+    prelude::*,
+    utils::{Duration, Instant},
+};
+
+use crate::{
+    bot::{Bot, BotDifficulty},
+    bump::{CurrentColor, GameRng, Score, TargetColor},
+    core::{
+        crystal::CrystalColor, event::ColorChanged, player::Player, timestep::FIXED_TIMESTEP,
+    },
+    level::{ActiveLevel, Level},
+};
+
+/// Drives a headless copy of the game one frame at a time, for tests and tooling.
+///
+/// Every frame advances the clock by a fixed amount rather than wall-clock time,
+/// so stepping is as fast as the machine allows and fully repeatable.
+pub struct Simulation {
+    pub app: App,
+    now: Instant,
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Simulation {
+    pub fn new() -> Self {
+        Self::from_app(crate::headless_app())
+    }
+
+    /// A simulation whose every random choice follows from `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        let mut app = crate::headless_app();
+        app.insert_resource(GameRng::from_seed(seed));
+        Self::from_app(app)
+    }
+
+    /// A seeded simulation playing `level` from the start.
+    pub fn with_level(seed: u64, level: Level) -> Self {
+        let mut app = crate::headless_app();
+        app.insert_resource(GameRng::from_seed(seed))
+            .insert_resource(ActiveLevel(Some(level)));
+        Self::from_app(app)
+    }
+
+    /// Wraps an app built with `headless_app`, possibly with extra plugins or resources.
+    pub fn from_app(mut app: App) -> Self {
+        let now = Instant::now();
+        app.world.resource_mut::<Time>().update_with_instant(now);
+        // Run the startup systems so the world is populated before the first step
+        app.update();
+
+        Self { app, now }
+    }
+
+    /// Advances one frame of exactly one gameplay step.
+    pub fn step(&mut self) {
+        self.step_with_frame_time(FIXED_TIMESTEP);
+    }
+
+    pub fn step_frames(&mut self, frames: u32) {
+        for _ in 0..frames {
+            self.step();
+        }
+    }
+
+    /// Advances one frame of `seconds`, as if rendering at `1. / seconds` Hz.
+    /// The number of gameplay steps this runs depends on the fixed timestep.
+    pub fn step_with_frame_time(&mut self, seconds: f64) {
+        // Round up to whole nanoseconds so a frame of FIXED_TIMESTEP never falls just short of a step
+        self.now += Duration::from_nanos((seconds * 1e9).ceil() as u64);
+        self.app
+            .world
+            .resource_mut::<Time>()
+            .update_with_instant(self.now);
+        self.app.update();
+    }
+
+    /// Presses `key` on the next frame, as a keyboard would.
+    pub fn press(&mut self, key: KeyCode) {
+        self.send_key(key, ButtonState::Pressed);
+    }
+
+    pub fn release(&mut self, key: KeyCode) {
+        self.send_key(key, ButtonState::Released);
+    }
+
+    fn send_key(&mut self, key_code: KeyCode, state: ButtonState) {
+        self.app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key_code),
+            state,
+        });
+    }
+
+    /// Sets the movement direction of every player for the steps of the next frame, bypassing input.
+    pub fn set_movement(&mut self, movement_dir: Vec2) {
+        let mut players = self.app.world.query::<&mut Player>();
+        for mut player in players.iter_mut(&mut self.app.world) {
+            player.movement_dir = movement_dir;
+        }
+    }
+
+    /// Hands every player over to a bot.
+    pub fn add_bot(&mut self, difficulty: BotDifficulty) {
+        let mut players = self.app.world.query_filtered::<Entity, With<Player>>();
+        let entities: Vec<Entity> = players.iter(&self.app.world).collect();
+        for entity in entities {
+            self.app
+                .world
+                .entity_mut(entity)
+                .insert(Bot::new(difficulty));
+        }
+    }
+
+    /// Recolors every player as if they had collected crystals, sending `ColorChanged` as the game would.
+    pub fn set_player_color(&mut self, color: Option<CrystalColor>) {
+        let mut changes = Vec::new();
+        let mut players = self.app.world.query::<(Entity, &mut Player)>();
+        for (entity, mut player) in players.iter_mut(&mut self.app.world) {
+            changes.push(ColorChanged {
+                player: entity,
+                from: player.color,
+                to: color,
+            });
+            player.color = color;
+        }
+
+        let mut events = self.app.world.resource_mut::<Events<ColorChanged>>();
+        for change in changes {
+            events.send(change);
+        }
+    }
+
+    pub fn score(&self) -> u32 {
+        self.app.world.resource::<Score>().0
+    }
+
+    pub fn current_color(&self) -> Option<CrystalColor> {
+        self.app.world.resource::<CurrentColor>().0
+    }
+
+    pub fn target_color(&self) -> CrystalColor {
+        self.app.world.resource::<TargetColor>().0
+    }
+
+    pub fn player_position(&mut self) -> Vec2 {
+        let mut players = self.app.world.query_filtered::<&Transform, With<Player>>();
+        players
+            .iter(&self.app.world)
+            .next()
+            .expect("No player spawned")
+            .translation
+            .xy()
+    }
+
+    pub fn world(&self) -> &World {
+        &self.app.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.app.world
+    }
+}
@@ -0,0 +1,737 @@
+//! The screen-space HUD: the colorwheel with the score in its middle, and a panel of
+//! run stats and the multiplier. Everything is laid out in logical pixels from the window corners,
+//! so it stays put at any aspect ratio and scales with the window's DPI. It's drawn by the
+//! `HudCamera`, so screen shake and zoom leave it alone.
+
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use serde::Deserialize;
+use strum::IntoEnumIterator;
+
+use crate::{
+    bump::{CurrentColor, GameMode, MapConfig, Multiplier, Score, TargetColor},
+    camera::{hud_layer, HudCamera},
+    core::{
+        crystal::CrystalColor,
+        timestep::{GameClock, GameSpeed},
+    },
+    difficulty::{Difficulty, HighScores},
+    level::{ActiveLevel, LevelState},
+    locale::Translations,
+    tween::{Repeat, Tween, TweenSystem},
+};
+
+/// How far in front of the camera the colorwheel is drawn.
+const WHEEL_DEPTH: f32 = -10.;
+/// Outline width of the wheel's shapes, relative to its radius.
+const WHEEL_OUTLINE: f32 = 0.025;
+/// Seconds the indicator takes to travel round to a new wedge.
+const INDICATOR_TRAVEL_TIME: f32 = 0.3;
+/// Seconds from rest to the peak of the target wedge's pulse.
+const TARGET_PULSE_TIME: f32 = 0.5;
+
+/// A corner of the window that a part of the HUD is anchored to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum HudCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl HudCorner {
+    /// Where the middle of something `half_extent` big ends up, `margin` away from this corner,
+    /// relative to the middle of a `window` sized screen with Y pointing up.
+    pub fn center(self, window: Vec2, margin: f32, half_extent: Vec2) -> Vec2 {
+        let offset = window / 2. - Vec2::splat(margin) - half_extent;
+        match self {
+            HudCorner::TopLeft => Vec2::new(-offset.x, offset.y),
+            HudCorner::TopRight => offset,
+            HudCorner::BottomLeft => -offset,
+            HudCorner::BottomRight => Vec2::new(offset.x, -offset.y),
+        }
+    }
+
+    fn ui_position(self, margin: f32) -> UiRect<Val> {
+        let margin = Val::Px(margin);
+        match self {
+            HudCorner::TopLeft => UiRect {
+                left: margin,
+                top: margin,
+                ..default()
+            },
+            HudCorner::TopRight => UiRect {
+                right: margin,
+                top: margin,
+                ..default()
+            },
+            HudCorner::BottomLeft => UiRect {
+                left: margin,
+                bottom: margin,
+                ..default()
+            },
+            HudCorner::BottomRight => UiRect {
+                right: margin,
+                bottom: margin,
+                ..default()
+            },
+        }
+    }
+
+    fn text_alignment(self) -> TextAlignment {
+        let horizontal = match self {
+            HudCorner::TopLeft | HudCorner::BottomLeft => HorizontalAlign::Left,
+            HudCorner::TopRight | HudCorner::BottomRight => HorizontalAlign::Right,
+        };
+        TextAlignment {
+            vertical: VerticalAlign::Top,
+            horizontal,
+        }
+    }
+}
+
+/// Where the HUD goes and how big it is, the `hud` section of the tuning. Sizes are in
+/// logical pixels, so they already follow the window's DPI scale factor.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HudConfig {
+    pub wheel_corner: HudCorner,
+    pub stats_corner: HudCorner,
+    pub wheel_radius: f32,
+    /// Space between the HUD and the window edges.
+    pub margin: f32,
+    pub stats_font_size: f32,
+    /// Length of the arrows pointing at crystals off the edge of the screen.
+    pub arrow_size: f32,
+    /// Width of the minimap strip along the bottom of the window.
+    pub minimap_width: f32,
+    /// Scales everything above, for players who want a bigger or smaller HUD.
+    pub scale: f32,
+}
+
+impl Default for HudConfig {
+    fn default() -> Self {
+        HudConfig {
+            wheel_corner: HudCorner::TopRight,
+            stats_corner: HudCorner::TopLeft,
+            wheel_radius: 64.,
+            margin: 16.,
+            stats_font_size: 24.,
+            arrow_size: 14.,
+            minimap_width: 400.,
+            scale: 1.,
+        }
+    }
+}
+
+impl HudConfig {
+    fn wheel_radius(&self) -> f32 {
+        self.wheel_radius * self.scale
+    }
+
+    pub(crate) fn margin(&self) -> f32 {
+        self.margin * self.scale
+    }
+}
+
+/// The primary window's size in logical pixels, the units the `HudCamera` draws in.
+pub(crate) fn window_size(windows: &Windows) -> Option<Vec2> {
+    let window = windows.get_primary()?;
+    let window_size = Vec2::new(window.width(), window.height());
+    (window_size.y > 0.).then_some(window_size)
+}
+
+#[derive(Component)]
+struct ColorWheelWedge(CrystalColor);
+#[derive(Component)]
+struct ColorWheelIndicator;
+/// Arrow on a wedge along the shortest way to the target, pointing the way round.
+#[derive(Component)]
+struct RouteMarker(CrystalColor);
+/// Dot on a wedge, filled if a crystal of its color gets you closer to the target,
+/// hollow if it sets you back.
+#[derive(Component)]
+struct EffectMarker(CrystalColor);
+/// Drawn with a radius of 1, `hud_layout` scales it to `HudConfig::wheel_radius` pixels.
+#[derive(Component)]
+struct ColorWheel;
+/// The node the score is centered in, laid over the colorwheel.
+#[derive(Component)]
+struct ScoreNode;
+#[derive(Component)]
+struct ScoreText;
+#[derive(Component)]
+struct StatsText;
+#[derive(Component)]
+struct RouteText;
+
+/// Whether the colorwheel shows the way to the target. Experts can turn it off in the menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoutePreview(pub bool);
+
+impl Default for RoutePreview {
+    fn default() -> Self {
+        RoutePreview(true)
+    }
+}
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RoutePreview>()
+            // The wheel hangs off the HUD camera, which is only there after `Startup`
+            .add_startup_system_to_stage(StartupStage::PostStartup, startup_colorwheel)
+            .add_startup_system(startup_hud_text)
+            .add_system(hud_layout)
+            .add_system(hud_text_layout)
+            .add_system(colorwheel_indicator_update)
+            .add_system(colorwheel_wedge_update.after(TweenSystem))
+            .add_system(route_preview_update)
+            .add_system(route_text_update)
+            .add_system(update_score_text)
+            .add_system(update_stats_text);
+    }
+}
+
+fn startup_colorwheel(mut commands: Commands, cameras: Query<Entity, With<HudCamera>>) {
+    // Use the shape plugin to draw a color wheel, coloring each of the wheel segments
+    //  by iterating through the CrystalColor enum.
+    let current_color = CrystalColor::Orange;
+
+    let wheel = commands
+        .spawn()
+        .insert(ColorWheel)
+        .insert(Transform::from_xyz(0., 0., WHEEL_DEPTH))
+        .insert(GlobalTransform::default())
+        .insert(Visibility::default())
+        .insert(ComputedVisibility::default())
+        .insert(hud_layer())
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(GeometryBuilder::build_as(
+                    &shapes::RegularPolygon {
+                        sides: CrystalColor::iter().count(),
+                        feature: shapes::RegularPolygonFeature::Radius(1.),
+                        ..default()
+                    },
+                    DrawMode::Outlined {
+                        fill_mode: bevy_prototype_lyon::prelude::FillMode::color(Color::rgb(
+                            0.1, 0.1, 0.1,
+                        )),
+                        outline_mode: StrokeMode::new(Color::WHITE, WHEEL_OUTLINE),
+                    },
+                    Transform::from_xyz(0., 0., 0.).with_rotation(Quat::from_rotation_z(
+                        std::f32::consts::PI / CrystalColor::iter().count() as f32,
+                    )),
+                ))
+                .insert(hud_layer());
+
+            for (i, color) in CrystalColor::iter().enumerate() {
+                let angle1 =
+                    i as f32 * 2. * std::f32::consts::PI / CrystalColor::iter().count() as f32;
+
+                let angle2 = (i + 1) as f32 * 2. * std::f32::consts::PI
+                    / CrystalColor::iter().count() as f32;
+                let outside1 = Vec2::new(angle1.cos(), angle1.sin());
+                let outside2 = Vec2::new(angle2.cos(), angle2.sin());
+                let inside1 = outside1 * 0.5;
+                let inside2 = outside2 * 0.5;
+
+                let shape = shapes::Polygon {
+                    points: vec![outside1, outside2, inside2, inside1],
+                    closed: true,
+                };
+
+                parent
+                    .spawn_bundle(GeometryBuilder::build_as(
+                        &shape,
+                        DrawMode::Outlined {
+                            fill_mode: bevy_prototype_lyon::prelude::FillMode::color(
+                                color.to_color(),
+                            ),
+                            outline_mode: StrokeMode::new(Color::WHITE, WHEEL_OUTLINE),
+                        },
+                        Transform::from_xyz(0., 0., 1.),
+                    ))
+                    .insert(ColorWheelWedge(color))
+                    .insert(hud_layer());
+
+                let middle = wedge_middle(color);
+                parent
+                    .spawn_bundle(GeometryBuilder::build_as(
+                        &shapes::Polygon {
+                            points: vec![
+                                Vec2::new(0.07, 0.),
+                                Vec2::new(-0.04, 0.05),
+                                Vec2::new(-0.04, -0.05),
+                            ],
+                            closed: true,
+                        },
+                        DrawMode::Fill(bevy_prototype_lyon::prelude::FillMode::color(Color::WHITE)),
+                        Transform::from_translation((middle * 0.9).extend(4.)),
+                    ))
+                    .insert(Visibility { is_visible: false })
+                    .insert(RouteMarker(color))
+                    .insert(hud_layer());
+                parent
+                    .spawn_bundle(GeometryBuilder::build_as(
+                        &shapes::Circle {
+                            radius: 0.04,
+                            ..default()
+                        },
+                        DrawMode::Fill(bevy_prototype_lyon::prelude::FillMode::color(Color::WHITE)),
+                        Transform::from_translation((middle * 0.58).extend(4.)),
+                    ))
+                    .insert(Visibility { is_visible: false })
+                    .insert(EffectMarker(color))
+                    .insert(hud_layer());
+            }
+
+            // Draw the current color indicator
+            parent
+                .spawn_bundle(GeometryBuilder::build_as(
+                    &shapes::RegularPolygon {
+                        sides: 4,
+                        feature: shapes::RegularPolygonFeature::Radius(0.1),
+                        ..default()
+                    },
+                    DrawMode::Outlined {
+                        fill_mode: bevy_prototype_lyon::prelude::FillMode::color(
+                            current_color.to_color(),
+                        ),
+                        outline_mode: StrokeMode::new(Color::WHITE, WHEEL_OUTLINE),
+                    },
+                    Transform::from_translation(indicator_position(wedge_angle(current_color))),
+                ))
+                .insert(Tween::at(wedge_angle(current_color), INDICATOR_TRAVEL_TIME))
+                .insert(Tween::at(current_color.to_color(), INDICATOR_TRAVEL_TIME))
+                .insert(ColorWheelIndicator)
+                .insert(hud_layer());
+        })
+        .id();
+
+    for camera in &cameras {
+        commands.entity(camera).add_child(wheel);
+    }
+}
+
+fn startup_hud_text(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/Hind-Regular.otf");
+
+    // Sizes and positions are filled in by `hud_text_layout`
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(ScoreNode)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "00",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 48.,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(ScoreText);
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 16.,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(RouteText);
+        });
+
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font,
+                    font_size: 24.,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                ..default()
+            }),
+        )
+        .insert(StatsText);
+}
+
+/// Keeps the colorwheel in its corner of the window, whatever its size.
+fn hud_layout(
+    mut wheels: Query<&mut Transform, With<ColorWheel>>,
+    windows: Res<Windows>,
+    map_config: Res<MapConfig>,
+) {
+    let window_size = match window_size(&windows) {
+        Some(window_size) => window_size,
+        None => return,
+    };
+
+    let hud_config = &map_config.hud;
+    let radius = hud_config.wheel_radius();
+    let center =
+        hud_config
+            .wheel_corner
+            .center(window_size, hud_config.margin(), Vec2::splat(radius));
+    for mut transform in &mut wheels {
+        transform.translation = center.extend(WHEEL_DEPTH);
+        transform.scale = Vec3::new(radius, radius, 1.);
+    }
+}
+
+/// Lines the UI text up with the colorwheel and moves the stats to their corner.
+#[allow(clippy::type_complexity)]
+fn hud_text_layout(
+    mut score_nodes: Query<&mut Style, With<ScoreNode>>,
+    mut score_text: Query<&mut Text, (With<ScoreText>, Without<RouteText>)>,
+    mut route_text: Query<&mut Text, (With<RouteText>, Without<ScoreText>)>,
+    mut stats_text: Query<
+        (&mut Style, &mut Text),
+        (
+            With<StatsText>,
+            Without<ScoreNode>,
+            Without<ScoreText>,
+            Without<RouteText>,
+        ),
+    >,
+    map_config: Res<MapConfig>,
+) {
+    if !map_config.is_changed() {
+        return;
+    }
+
+    let hud_config = &map_config.hud;
+    let radius = hud_config.wheel_radius();
+    for mut style in &mut score_nodes {
+        style.position = hud_config.wheel_corner.ui_position(hud_config.margin());
+        style.size = Size::new(Val::Px(radius * 2.), Val::Px(radius * 2.));
+    }
+    for mut text in &mut score_text {
+        // Two digits and the route's steps fit inside the wheel's hole
+        text.sections[0].style.font_size = radius * 0.6;
+    }
+    for mut text in &mut route_text {
+        text.sections[0].style.font_size = radius * 0.22;
+    }
+    for (mut style, mut text) in &mut stats_text {
+        style.position = hud_config.stats_corner.ui_position(hud_config.margin());
+        text.alignment = hud_config.stats_corner.text_alignment();
+        text.sections[0].style.font_size = hud_config.stats_font_size * hud_config.scale;
+    }
+}
+
+/// Highlights the current wedge and pulses the target.
+#[allow(clippy::type_complexity)]
+fn colorwheel_wedge_update(
+    mut commands: Commands,
+    mut colorwheel_wedges: Query<(
+        Entity,
+        &ColorWheelWedge,
+        &mut Transform,
+        &mut DrawMode,
+        Option<&Tween<Transform>>,
+    )>,
+    current_color: Res<CurrentColor>,
+    target_color: Res<TargetColor>,
+    time: Res<Time>,
+) {
+    for (entity, wedge, mut transform, mut draw_mode, pulse) in colorwheel_wedges.iter_mut() {
+        let is_target = wedge.0 == target_color.0;
+        let is_current = Some(wedge.0) == current_color.0;
+        let mut alpha = 0.1;
+        if wedge.0.is_primary() {
+            alpha = 0.4;
+        }
+
+        if is_target {
+            alpha = 1.;
+        }
+
+        if is_current {
+            alpha = 0.8;
+        }
+
+        let mut fill_color = wedge.0.to_color();
+        fill_color.set_a(alpha);
+
+        let border_color = match is_target {
+            true => Color::WHITE,
+            false => wedge.0.to_color() * 0.5,
+        };
+
+        let rest = match is_target {
+            true => Transform::from_xyz(0., 0., 2.),
+            false => Transform::from_xyz(0., 0., 1.),
+        };
+        let mode = DrawMode::Outlined {
+            fill_mode: bevy_prototype_lyon::prelude::FillMode::color(fill_color),
+            outline_mode: StrokeMode::new(border_color, WHEEL_OUTLINE),
+        };
+
+        match (is_target, pulse.is_some()) {
+            // Already pulsing, `TweenPlugin` takes care of it
+            (true, true) => continue,
+            (true, false) => {
+                // Swell outward and brighten, then settle back, over and over
+                let [hue, saturation, lightness, alpha] = fill_color.as_hsla_f32();
+                let peak_mode = DrawMode::Outlined {
+                    fill_mode: bevy_prototype_lyon::prelude::FillMode::color(Color::hsla(
+                        hue,
+                        saturation,
+                        (lightness + 0.15).min(1.),
+                        alpha,
+                    )),
+                    outline_mode: StrokeMode::new(border_color, WHEEL_OUTLINE * 2.),
+                };
+                let peak = rest.with_scale(Vec3::new(1.08, 1.08, 1.));
+                let now = time.seconds_since_startup();
+                commands
+                    .entity(entity)
+                    .insert(
+                        Tween::new(rest, peak, now, TARGET_PULSE_TIME)
+                            .with_repeat(Repeat::PingPong),
+                    )
+                    .insert(
+                        Tween::new(mode, peak_mode, now, TARGET_PULSE_TIME)
+                            .with_repeat(Repeat::PingPong),
+                    );
+            }
+            (false, true) => {
+                commands
+                    .entity(entity)
+                    .remove::<Tween<Transform>>()
+                    .remove::<Tween<DrawMode>>();
+            }
+            (false, false) => {}
+        }
+
+        *transform = rest;
+        *draw_mode = mode;
+    }
+}
+
+/// With `RoutePreview` on, marks the way from the current to the target wedge
+/// and which crystal colors help or hurt.
+#[allow(clippy::type_complexity)]
+fn route_preview_update(
+    mut route_markers: Query<
+        (&RouteMarker, &mut Transform, &mut Visibility),
+        (Without<ColorWheelWedge>, Without<EffectMarker>),
+    >,
+    mut effect_markers: Query<
+        (&EffectMarker, &mut DrawMode, &mut Visibility),
+        (Without<ColorWheelWedge>, Without<RouteMarker>),
+    >,
+    current_color: Res<CurrentColor>,
+    target_color: Res<TargetColor>,
+    route_preview: Res<RoutePreview>,
+    map_config: Res<MapConfig>,
+) {
+    let current = current_color.0.filter(|_| route_preview.0);
+    let route = current.map_or_else(Vec::new, |color| color.route_to(&target_color.0));
+    let direction = current.map_or(0, |color| color.steps_to(&target_color.0).signum());
+
+    for (marker, mut transform, mut visibility) in &mut route_markers {
+        visibility.is_visible = route.contains(&marker.0);
+        // Point along the rim, the way the route goes round
+        let middle = wedge_middle(marker.0);
+        let heading = middle.perp() * direction as f32;
+        transform.rotation = Quat::from_rotation_z(heading.y.atan2(heading.x));
+    }
+
+    for (marker, mut draw_mode, mut visibility) in &mut effect_markers {
+        let progress = current.map_or(0, |_| {
+            map_config
+                .combine_rule
+                .progress(current, marker.0, target_color.0)
+        });
+        visibility.is_visible = progress != 0;
+        *draw_mode = if progress > 0 {
+            DrawMode::Fill(bevy_prototype_lyon::prelude::FillMode::color(Color::WHITE))
+        } else {
+            DrawMode::Stroke(StrokeMode::new(Color::WHITE, WHEEL_OUTLINE))
+        };
+    }
+}
+
+/// Counts the steps of the previewed route under the colorwheel.
+fn route_text_update(
+    mut route_text: Query<&mut Text, With<RouteText>>,
+    current_color: Res<CurrentColor>,
+    target_color: Res<TargetColor>,
+    route_preview: Res<RoutePreview>,
+    translations: Res<Translations>,
+) {
+    let steps = current_color
+        .0
+        .filter(|_| route_preview.0)
+        .map_or(0, |color| color.route_to(&target_color.0).len());
+
+    for mut text in &mut route_text {
+        text.sections[0].value = match steps {
+            0 => String::new(),
+            steps => translations.format("hud-route", &[("steps", &steps)]),
+        };
+    }
+}
+
+/// Angle through the middle of `color`'s wedge.
+fn wedge_angle(color: CrystalColor) -> f32 {
+    (color as usize as f32 + 0.5) * 2. * std::f32::consts::PI / CrystalColor::iter().count() as f32
+}
+
+/// Unit vector through the middle of `color`'s wedge.
+fn wedge_middle(color: CrystalColor) -> Vec2 {
+    let angle = wedge_angle(color);
+    Vec2::new(angle.cos(), angle.sin())
+}
+
+/// Where the indicator sits on a wheel of radius 1, at `angle` round it.
+fn indicator_position(angle: f32) -> Vec3 {
+    (Vec2::new(angle.cos(), angle.sin()) * 0.75).extend(3.)
+}
+
+/// Moves the indicator round the wheel to the current color, going the way the color changed,
+/// and blends it into that color on the way.
+#[allow(clippy::type_complexity)]
+fn colorwheel_indicator_update(
+    mut colorwheel_indicator: Query<
+        (
+            &mut Visibility,
+            &mut Transform,
+            &mut DrawMode,
+            &mut Tween<f32>,
+            &mut Tween<Color>,
+        ),
+        With<ColorWheelIndicator>,
+    >,
+    current_color: Res<CurrentColor>,
+    mut previous_color: Local<Option<CrystalColor>>,
+    time: Res<Time>,
+) {
+    let now = time.seconds_since_startup();
+    for (mut visibility, mut transform, mut draw_mode, mut angle, mut fill) in
+        colorwheel_indicator.iter_mut()
+    {
+        let to = match (*previous_color, current_color.0) {
+            (_, None) => {
+                visibility.is_visible = false;
+                continue;
+            }
+            (None, Some(to)) => {
+                // Coming from no color at all, there's nowhere to travel from
+                *angle = Tween::at(wedge_angle(to), INDICATOR_TRAVEL_TIME);
+                *fill = Tween::at(to.to_color(), INDICATOR_TRAVEL_TIME);
+                to
+            }
+            (Some(from), Some(to)) => {
+                if from != to {
+                    let steps = from.steps_to(&to) as f32;
+                    let wedge = 2. * std::f32::consts::PI / CrystalColor::iter().count() as f32;
+                    let target = angle.to + steps * wedge;
+                    angle.retarget(target, now);
+                    fill.retarget(to.to_color(), now);
+                }
+                to
+            }
+        };
+
+        // Keep the angle from winding up over a long run
+        if angle.is_finished(now) && angle.to != wedge_angle(to) {
+            *angle = Tween::at(wedge_angle(to), INDICATOR_TRAVEL_TIME);
+        }
+
+        transform.translation = indicator_position(angle.value(now));
+        *draw_mode = DrawMode::Outlined {
+            fill_mode: bevy_prototype_lyon::prelude::FillMode::color(fill.value(now)),
+            outline_mode: StrokeMode::new(Color::WHITE, WHEEL_OUTLINE),
+        };
+        visibility.is_visible = true;
+    }
+    *previous_color = current_color.0;
+}
+
+fn update_score_text(mut score_text: Query<&mut Text, With<ScoreText>>, score: Res<Score>) {
+    for mut text in score_text.iter_mut() {
+        text.sections[0].value = format!("{:02}", score.0);
+    }
+}
+
+/// The target's name, the multiplier and the run's timers, the best endless score on the
+/// difficulty being played, plus the game speed when it isn't the usual one.
+/// Hidden in the editor, which has its own text in that spot.
+#[allow(clippy::too_many_arguments)]
+fn update_stats_text(
+    mut stats_text: Query<(&mut Text, &mut Visibility), With<StatsText>>,
+    active_level: Res<ActiveLevel>,
+    level_state: Res<LevelState>,
+    target_color: Res<TargetColor>,
+    multiplier: Res<Multiplier>,
+    translations: Res<Translations>,
+    game_speed: Res<GameSpeed>,
+    game_mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    high_scores: Option<Res<HighScores>>,
+    clock: Res<GameClock>,
+) {
+    let time = clock.elapsed - level_state.started_at;
+    let color = translations.text(target_color.0.message_id());
+    let mut lines = vec![
+        translations.format("hud-target", &[("color", &color)]),
+        translations.format("hud-multiplier", &[("multiplier", &multiplier.0)]),
+        translations.format("hud-time", &[("seconds", &format!("{:.1}", time))]),
+    ];
+
+    let best = high_scores.and_then(|high_scores| high_scores.best(*difficulty));
+    if let (None, Some(best)) = (&active_level.0, best) {
+        let difficulty = translations.text(difficulty.message_id());
+        lines.push(
+            translations.format("hud-best", &[("score", &best), ("difficulty", &difficulty)]),
+        );
+    }
+    if let Some(level) = &active_level.0 {
+        if let Some(time_limit) = level.lose.time_limit {
+            let left = format!("{:.1}", (time_limit - time).max(0.));
+            lines.push(translations.format("hud-time-left", &[("seconds", &left)]));
+        }
+        if let Some(max_hits) = level.lose.max_hits {
+            lines.push(translations.format(
+                "hud-hits",
+                &[("hits", &level_state.hits), ("max", &max_hits)],
+            ));
+        }
+        if let Some(max_falls) = level.lose.max_falls {
+            lines.push(translations.format(
+                "hud-falls",
+                &[("falls", &level_state.falls), ("max", &max_falls)],
+            ));
+        }
+    }
+    if game_speed.clamped() < GameSpeed::MAX {
+        let speed = format!("{:.2}", game_speed.clamped());
+        lines.push(translations.format("hud-speed", &[("speed", &speed)]));
+    }
+
+    for (mut text, mut visibility) in &mut stats_text {
+        visibility.is_visible = *game_mode != GameMode::Editing;
+        text.sections[0].value = lines.join("\n");
+    }
+}
@@ -0,0 +1,460 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bump::{
+        scatter_crystals, BumpSystem, CurrentColor, GameMode, GameRng, MapConfig, Score,
+        TargetColor, TargetQueue,
+    },
+    core::{
+        crystal::{CombineRule, Crystal, CrystalBundle, CrystalColor, CrystalKind, Placed},
+        event::{
+            ColorChanged, CrystalCollected, ObstacleHit, PlayerOutOfBounds, ResetGame,
+            TargetReached,
+        },
+        player::Player,
+        timestep::{FixedUpdateAppExt, GameClock},
+    },
+    obstacle::PlacedObstacle,
+};
+
+/// An authored run: a fixed sequence of targets, crystals and obstacles arriving on a schedule,
+/// and the conditions for winning or losing. Replaces the random spawning while active.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, TypeUuid)]
+#[uuid = "8f0b7d4e-2c61-4a3b-9d57-61c0e4a2b9f3"]
+pub struct Level {
+    pub name: String,
+    /// How the wheel turns when a crystal is collected.
+    #[serde(default)]
+    pub combine_rule: CombineRule,
+    #[serde(default)]
+    pub start_color: Option<CrystalColor>,
+    /// Where the player starts, instead of the middle of the map.
+    #[serde(default)]
+    pub start_position: Option<(f32, f32)>,
+    /// Reached in order. The level is won once the last one is reached.
+    pub targets: Vec<CrystalColor>,
+    #[serde(default)]
+    pub waves: Vec<CrystalWave>,
+    #[serde(default)]
+    pub obstacles: Vec<PlacedObstacle>,
+    #[serde(default)]
+    pub lose: LoseConditions,
+    #[serde(default)]
+    pub stars: StarGoals,
+}
+
+/// Crystals that enter from the right edge together.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CrystalWave {
+    /// Seconds after the level starts.
+    pub time: f64,
+    pub crystals: Vec<PlacedCrystal>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlacedCrystal {
+    pub y: f32,
+    /// Distance past the right edge, for staggering crystals within a wave.
+    /// Negative offsets start inside the map.
+    #[serde(default)]
+    pub x_offset: f32,
+    pub color: CrystalColor,
+    #[serde(default)]
+    pub kind: CrystalKind,
+}
+
+/// Any condition that's set ends the level in a loss once it's met.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LoseConditions {
+    /// Seconds to reach every target in.
+    pub time_limit: Option<f64>,
+    /// Obstacle hits allowed before losing.
+    pub max_hits: Option<u32>,
+    /// Falls off the map allowed before losing.
+    pub max_falls: Option<u32>,
+}
+
+/// What a win needs to earn each of its stars. Winning, which means reaching every target,
+/// earns at least one. A goal that's left out is always met.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StarGoals {
+    /// Least score to win with, which takes keeping up the multiplier.
+    pub par_score: Option<u32>,
+    /// Seconds to win within.
+    pub par_time: Option<f64>,
+    /// Most crystals to collect on the way.
+    pub par_crystals: Option<u32>,
+}
+
+impl StarGoals {
+    /// Stars out of 3 for a finished level, one for each goal met, 0 if it was lost.
+    pub fn rate(&self, finished: &LevelFinished) -> u8 {
+        if finished.outcome != LevelOutcome::Won {
+            return 0;
+        }
+
+        let high_score = self.par_score.is_none_or(|par| finished.score >= par);
+        let fast = self.par_time.is_none_or(|par| finished.time <= par);
+        let efficient = self.par_crystals.is_none_or(|par| finished.crystals <= par);
+        (high_score as u8 + fast as u8 + efficient as u8).max(1)
+    }
+}
+
+impl Level {
+    pub fn from_ron(text: &str) -> Result<Self, ron::Error> {
+        let mut level: Level = ron::from_str(text)?;
+        level.sort();
+        Ok(level)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new())
+    }
+
+    /// Orders waves and obstacles by time, which is how they're spawned.
+    pub fn sort(&mut self) {
+        self.waves.sort_by(|a, b| a.time.total_cmp(&b.time));
+        self.obstacles.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
+    /// Overrides the parts of the config the level takes over from the random spawner.
+    pub fn apply(&self, map_config: &mut MapConfig) {
+        map_config.combine_rule = self.combine_rule;
+        map_config.crystal_count = 0;
+        map_config.obstacles.enabled = false;
+    }
+}
+
+/// The level being played, if any. Setting it restarts the run.
+#[derive(Default)]
+pub struct ActiveLevel(pub Option<Level>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoseReason {
+    TimeUp,
+    TooManyHits,
+    TooManyFalls,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelOutcome {
+    Won,
+    Lost(LoseReason),
+}
+
+/// How far into the active level the run is.
+#[derive(Debug, Default)]
+pub struct LevelState {
+    pub started_at: f64,
+    pub targets_reached: usize,
+    pub next_wave: usize,
+    pub next_obstacle: usize,
+    pub hits: u32,
+    pub falls: u32,
+    pub crystals: u32,
+    pub outcome: Option<LevelOutcome>,
+}
+
+/// The active level was won or lost.
+#[derive(Debug, Clone)]
+pub struct LevelFinished {
+    pub outcome: LevelOutcome,
+    /// Seconds since the level started.
+    pub time: f64,
+    pub score: u32,
+    /// Crystals collected along the way.
+    pub crystals: u32,
+}
+
+/// Plays the `ActiveLevel` in place of the endless random mode.
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveLevel>()
+            .init_resource::<LevelState>()
+            .add_fixed_event::<LevelFinished>()
+            .add_system(restart_on_level_change)
+            .add_fixed_system(
+                reset_level
+                    .after(BumpSystem::Reset)
+                    .before(BumpSystem::Movement)
+                    .before(BumpSystem::Collision),
+            )
+            .add_fixed_system(level_waves.after(reset_level))
+            .add_fixed_system(
+                level_progress
+                    .after(level_waves)
+                    .after(BumpSystem::Movement)
+                    .after(BumpSystem::Scoring),
+            );
+    }
+}
+
+/// Starting or leaving a level starts a fresh run.
+fn restart_on_level_change(
+    active_level: Res<ActiveLevel>,
+    mut was_active: Local<bool>,
+    mut reset_events: EventWriter<ResetGame>,
+) {
+    if !active_level.is_changed() {
+        return;
+    }
+
+    let is_active = active_level.0.is_some();
+    if is_active || *was_active {
+        reset_events.send(ResetGame);
+    }
+    *was_active = is_active;
+}
+
+#[allow(clippy::too_many_arguments)]
+fn reset_level(
+    mut commands: Commands,
+    mut reset_events: EventReader<ResetGame>,
+    active_level: Res<ActiveLevel>,
+    mut level_state: ResMut<LevelState>,
+    crystals: Query<(Entity, Option<&Placed>), With<Crystal>>,
+    mut players: Query<(Entity, &mut Transform, &mut Player)>,
+    mut current_color: ResMut<CurrentColor>,
+    mut target_color: ResMut<TargetColor>,
+    mut target_queue: ResMut<TargetQueue>,
+    mut color_changed_events: EventWriter<ColorChanged>,
+    mut rng: ResMut<GameRng>,
+    clock: Res<GameClock>,
+    map_config: Res<MapConfig>,
+) {
+    if reset_events.iter().count() == 0 {
+        return;
+    }
+
+    *level_state = LevelState {
+        started_at: clock.elapsed,
+        ..default()
+    };
+
+    let level = active_level.0.as_ref();
+    // A level places all of its own crystals
+    for (entity, placed) in &crystals {
+        if placed.is_some() || level.is_some() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    let level = match level {
+        Some(level) => level,
+        None => {
+            // Bring back the random crystals, after leaving a level that cleared them away
+            let count = crystals
+                .iter()
+                .filter(|(_, placed)| placed.is_none())
+                .count();
+            let missing = map_config.crystal_count.saturating_sub(count);
+            scatter_crystals(&mut commands, &mut rng.0, &map_config, missing);
+            return;
+        }
+    };
+    if let Some((first, rest)) = level.targets.split_first() {
+        *target_color = TargetColor(*first);
+        target_queue.0 = rest.iter().copied().collect();
+    }
+    if let Some((x, y)) = level.start_position {
+        for (_, mut transform, mut player) in &mut players {
+            transform.translation.x = x;
+            transform.translation.y = y;
+            player.home_x = x;
+        }
+    }
+    if level.start_color.is_some() {
+        *current_color = CurrentColor(level.start_color);
+        for (entity, _, mut player) in &mut players {
+            color_changed_events.send(ColorChanged {
+                player: entity,
+                from: player.color,
+                to: level.start_color,
+            });
+            player.color = level.start_color;
+        }
+    }
+}
+
+/// Spawns each wave of crystals and each obstacle once its time comes.
+fn level_waves(
+    mut commands: Commands,
+    active_level: Res<ActiveLevel>,
+    mut level_state: ResMut<LevelState>,
+    clock: Res<GameClock>,
+    map_config: Res<MapConfig>,
+) {
+    let level = match &active_level.0 {
+        Some(level) => level,
+        None => return,
+    };
+    let time = clock.elapsed - level_state.started_at;
+    let hmap_size = map_config.half_size();
+
+    while let Some(wave) = level.waves.get(level_state.next_wave) {
+        if wave.time > time {
+            break;
+        }
+        for crystal in &wave.crystals {
+            commands
+                .spawn_bundle(
+                    CrystalBundle::default()
+                        .with_color(crystal.color)
+                        .with_kind(crystal.kind)
+                        .with_position(hmap_size.x + crystal.x_offset, crystal.y),
+                )
+                .insert(Placed);
+        }
+        level_state.next_wave += 1;
+    }
+
+    while let Some(obstacle) = level.obstacles.get(level_state.next_obstacle) {
+        if obstacle.time > time {
+            break;
+        }
+        obstacle.spawn(&mut commands, &map_config, &clock);
+        level_state.next_obstacle += 1;
+    }
+}
+
+/// Counts targets, hits and falls against the level's conditions, and finishes it when one is met.
+#[allow(clippy::too_many_arguments)]
+fn level_progress(
+    active_level: Res<ActiveLevel>,
+    mut level_state: ResMut<LevelState>,
+    mut target_reached_events: EventReader<TargetReached>,
+    mut hit_events: EventReader<ObstacleHit>,
+    mut out_of_bounds_events: EventReader<PlayerOutOfBounds>,
+    mut collected_events: EventReader<CrystalCollected>,
+    mut finished_events: EventWriter<LevelFinished>,
+    score: Res<Score>,
+    clock: Res<GameClock>,
+) {
+    let level = match &active_level.0 {
+        Some(level) if level_state.outcome.is_none() => level,
+        _ => {
+            target_reached_events.clear();
+            hit_events.clear();
+            out_of_bounds_events.clear();
+            collected_events.clear();
+            return;
+        }
+    };
+
+    level_state.targets_reached += target_reached_events.iter().count();
+    level_state.hits += hit_events.iter().count() as u32;
+    level_state.falls += out_of_bounds_events.iter().count() as u32;
+    level_state.crystals += collected_events.iter().count() as u32;
+
+    let time = clock.elapsed - level_state.started_at;
+    let lose = &level.lose;
+    let outcome = if !level.targets.is_empty() && level_state.targets_reached >= level.targets.len()
+    {
+        Some(LevelOutcome::Won)
+    } else if lose.max_hits.is_some_and(|max| level_state.hits > max) {
+        Some(LevelOutcome::Lost(LoseReason::TooManyHits))
+    } else if lose.max_falls.is_some_and(|max| level_state.falls > max) {
+        Some(LevelOutcome::Lost(LoseReason::TooManyFalls))
+    } else if lose.time_limit.is_some_and(|limit| time >= limit) {
+        Some(LevelOutcome::Lost(LoseReason::TimeUp))
+    } else {
+        None
+    };
+
+    if let Some(outcome) = outcome {
+        level_state.outcome = Some(outcome);
+        finished_events.send(LevelFinished {
+            outcome,
+            time,
+            score: score.0,
+            crystals: level_state.crystals,
+        });
+    }
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let mut level = ron::de::from_bytes::<Level>(bytes)?;
+            level.sort();
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+/// Request to play a level asset once it has loaded.
+#[derive(Debug, Clone)]
+pub struct PlayLevel(pub Handle<Level>);
+
+/// The level asset the `ActiveLevel` came from, so edits to the file restart it.
+struct ActiveLevelHandle(Handle<Level>);
+
+/// Loads levels from `.level.ron` assets. Needs the asset server, so it's only part of the full game.
+pub struct LevelAssetPlugin;
+
+impl Plugin for LevelAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .add_event::<PlayLevel>()
+            .add_system(play_level);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn play_level(
+    mut commands: Commands,
+    mut play_events: EventReader<PlayLevel>,
+    mut asset_events: EventReader<AssetEvent<Level>>,
+    mut pending: Local<Option<Handle<Level>>>,
+    active_handle: Option<Res<ActiveLevelHandle>>,
+    levels: Res<Assets<Level>>,
+    mut active_level: ResMut<ActiveLevel>,
+    mut game_mode: ResMut<GameMode>,
+) {
+    if let Some(event) = play_events.iter().last() {
+        *pending = Some(event.0.clone());
+    }
+    // Reload the level being played when its file changes
+    for event in asset_events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            if active_handle
+                .as_ref()
+                .is_some_and(|active| active.0 == *handle)
+            {
+                *pending = Some(handle.clone());
+            }
+        }
+    }
+
+    let level = match pending.as_ref().and_then(|handle| levels.get(handle)) {
+        Some(level) => level,
+        None => return,
+    };
+    info!("Playing level {}", level.name);
+    *active_level = ActiveLevel(Some(level.clone()));
+    *game_mode = GameMode::Playing;
+    commands.insert_resource(ActiveLevelHandle(pending.take().unwrap()));
+}
@@ -1,11 +1,50 @@
-use bevy::prelude::*;
+use bevy::{asset::AssetServerSettings, prelude::*};
 use crate::config::*;
 use crate::core::CorePlugin;
-use crate::bump::BumpPlugin;
+use crate::background::BackgroundPlugin;
+use crate::bot::BotPlugin;
+use crate::bump::{BumpHudPlugin, BumpPlugin};
+use crate::camera::CameraPlugin;
+use crate::campaign::CampaignPlugin;
+use crate::core::SimulationPlugin;
+use crate::cues::AudioCuesPlugin;
+use crate::difficulty::{DifficultyPlugin, HighScorePlugin};
+use crate::editor::EditorPlugin;
+use crate::effects::EffectsPlugin;
+use crate::hud::HudPlugin;
+use crate::level::{LevelAssetPlugin, LevelPlugin};
+use crate::locale::LocalePlugin;
+use crate::menu::MenuPlugin;
+use crate::obstacle::ObstaclePlugin;
+use crate::radar::RadarPlugin;
+use crate::sound::SoundPlugin;
+use crate::tuning::TuningPlugin;
+use crate::tween::TweenPlugin;
 
 pub mod config;
-mod core;
-mod bump;
+pub mod core;
+pub mod background;
+pub mod bot;
+pub mod bump;
+pub mod camera;
+pub mod campaign;
+pub mod cues;
+pub mod difficulty;
+pub mod editor;
+pub mod effects;
+pub mod balance;
+pub mod env;
+pub mod harness;
+pub mod hud;
+pub mod level;
+pub mod locale;
+pub mod obstacle;
+pub mod radar;
+pub mod sound;
+mod menu;
+mod storage;
+mod tuning;
+pub mod tween;
 
 pub fn app() -> App {
     let game_config = GameConfig::default();
@@ -19,9 +58,58 @@ pub fn app() -> App {
             fit_canvas_to_parent: true,
             ..default()
         })
+        // Tuning files are reloaded on change while developing natively
+        .insert_resource(AssetServerSettings {
+            watch_for_changes: cfg!(debug_assertions) && !cfg!(target_arch = "wasm32"),
+            ..default()
+        })
         .add_plugins(DefaultPlugins)
         .add_plugin(CorePlugin)
-        .add_plugin(BumpPlugin);
+        .add_plugin(LocalePlugin)
+        .add_plugin(BumpPlugin)
+        .add_plugin(BumpHudPlugin)
+        .add_plugin(CameraPlugin)
+        .add_plugin(BackgroundPlugin)
+        .add_plugin(HudPlugin)
+        .add_plugin(RadarPlugin)
+        .add_plugin(DifficultyPlugin)
+        .add_plugin(HighScorePlugin)
+        .add_plugin(ObstaclePlugin)
+        .add_plugin(LevelPlugin)
+        .add_plugin(LevelAssetPlugin)
+        .add_plugin(BotPlugin)
+        .add_plugin(MenuPlugin)
+        .add_plugin(EditorPlugin)
+        .add_plugin(CampaignPlugin)
+        .add_plugin(TuningPlugin)
+        .add_plugin(TweenPlugin)
+        .add_plugin(EffectsPlugin)
+        .add_plugin(SoundPlugin)
+        .add_plugin(AudioCuesPlugin);
+
+    app
+}
+
+/// Builds the game without a window, renderer or asset server.
+/// Time is not advanced on its own: drive it through `harness::Simulation`,
+/// or by updating the `Time` resource before each `App::update`.
+pub fn headless_app() -> App {
+    let mut app = App::new();
+
+    app.insert_resource(GameConfig::default())
+        .insert_resource(Time::default())
+        .add_plugins_with(MinimalPlugins, |group| {
+            group.disable::<bevy::time::TimePlugin>()
+        })
+        .add_plugin(bevy::transform::TransformPlugin)
+        .add_plugin(bevy::hierarchy::HierarchyPlugin)
+        .add_plugin(bevy::input::InputPlugin)
+        .add_plugin(SimulationPlugin)
+        .add_plugin(BumpPlugin)
+        .add_plugin(DifficultyPlugin)
+        .add_plugin(ObstaclePlugin)
+        .add_plugin(LevelPlugin)
+        .add_plugin(BotPlugin);
 
     app
 }
@@ -0,0 +1,660 @@
+//! Translations of the player-facing text. Each language has a file of messages under
+//! `assets/locales/`, written in a small subset of Fluent: `id = pattern` with indented
+//! continuation lines, `{ $variable }` placeables and `{ $count -> [one] ... *[other] ... }`
+//! selectors. Text tagged `Localized` follows the current language, and every text is drawn
+//! in the first font with a glyph for every character of the current language's messages.
+
+use std::{collections::HashMap, fmt::Display, iter::Peekable, str::Chars};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadState, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+
+use crate::{config::GameConfig, storage};
+
+/// A language the game has been translated into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocaleInfo {
+    /// Language tag, which is also the name of its file under `locales/`.
+    pub id: &'static str,
+    /// The language's name for itself, for the menu.
+    pub name: &'static str,
+    /// Fonts to try before `FALLBACK_FONTS`, for a script none of them covers.
+    pub fonts: &'static [&'static str],
+}
+
+const HIND: &str = "fonts/Hind-Regular.otf";
+const DEJAVU_SANS: &str = "fonts/DejaVuSans.ttf";
+/// Fonts tried after a language's own, in order. Hind covers Latin text, DejaVu Sans
+/// adds Greek, Cyrillic and most symbols.
+pub const FALLBACK_FONTS: &[&str] = &[HIND, DEJAVU_SANS];
+
+pub const LOCALES: &[LocaleInfo] = &[
+    LocaleInfo {
+        id: "en",
+        name: "English",
+        fonts: &[],
+    },
+    LocaleInfo {
+        id: "de",
+        name: "Deutsch",
+        fonts: &[],
+    },
+    LocaleInfo {
+        id: "es",
+        name: "Español",
+        fonts: &[],
+    },
+];
+/// Anything missing from a translation is shown in this language.
+pub const FALLBACK_LOCALE: &str = "en";
+/// Storage key the player's choice of language is saved under.
+const LOCALE_KEY: &str = "locale";
+
+/// The best of `LOCALES` for a language tag like `de-AT`: the whole tag if it's there,
+/// then just its language, then the fallback.
+pub fn negotiate(requested: &str) -> &'static LocaleInfo {
+    supported(requested).unwrap_or_else(|| locale_info(FALLBACK_LOCALE))
+}
+
+fn supported(requested: &str) -> Option<&'static LocaleInfo> {
+    let requested = requested.trim().replace('_', "-").to_lowercase();
+    let language = requested.split('-').next().unwrap_or_default();
+    LOCALES
+        .iter()
+        .find(|locale| locale.id.to_lowercase() == requested)
+        .or_else(|| LOCALES.iter().find(|locale| locale.id == language))
+}
+
+fn locale_info(id: &str) -> &'static LocaleInfo {
+    LOCALES
+        .iter()
+        .find(|locale| locale.id == id)
+        .unwrap_or(&LOCALES[0])
+}
+
+/// The language the system is set to, as a tag like `de-AT`: the first of the user's
+/// preferred languages the game has, or else their first.
+pub fn system_locale() -> Option<String> {
+    let preferred: Vec<_> = sys_locale::get_locales().collect();
+    preferred
+        .iter()
+        .find(|requested| supported(requested).is_some())
+        .or_else(|| preferred.first())
+        .cloned()
+}
+
+/// Whether `font` has a glyph for every character of `text` but whitespace.
+pub fn covers(font: &impl ab_glyph::Font, text: &str) -> bool {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .all(|c| font.glyph_id(c).0 != 0)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Element {
+    Text(String),
+    Variable(String),
+    /// Picks a variant by the variable's value: an exact match for a key first,
+    /// then its plural category, then the default.
+    Select {
+        variable: String,
+        variants: Vec<(String, Vec<Element>)>,
+        default: usize,
+    },
+}
+
+/// The messages of one language, by id.
+#[derive(Debug, Clone, Default, TypeUuid)]
+#[uuid = "c1f46aa0-b3c3-4baf-9d71-7bdac3169601"]
+pub struct Messages(HashMap<String, Vec<Element>>);
+
+impl Messages {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut messages = HashMap::new();
+        // The message being read: its id, its pattern so far and the line it started on
+        let mut current: Option<(String, String, usize)> = None;
+        let mut finish = |current: &mut Option<(String, String, usize)>| {
+            if let Some((id, source, line)) = current.take() {
+                let pattern = PatternParser::new(&source)
+                    .parse()
+                    .map_err(|error| format!("line {}: {}: {}", line, id, error))?;
+                messages.insert(id, pattern);
+            }
+            Ok::<_, String>(())
+        };
+
+        for (index, line) in source.lines().enumerate() {
+            let number = index + 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if line.starts_with(char::is_whitespace) {
+                match &mut current {
+                    Some((_, pattern, _)) => {
+                        if !pattern.is_empty() {
+                            pattern.push('\n');
+                        }
+                        pattern.push_str(line.trim());
+                    }
+                    None => {
+                        return Err(format!("line {}: indented line outside a message", number))
+                    }
+                }
+                continue;
+            }
+
+            finish(&mut current)?;
+            if line.starts_with('#') {
+                continue;
+            }
+
+            let (id, pattern) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected `id = message`", number))?;
+            let id = id.trim();
+            let valid_id = id.starts_with(|c: char| c.is_ascii_alphabetic())
+                && id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid_id {
+                return Err(format!(
+                    "line {}: `{}` is not a valid message id",
+                    number, id
+                ));
+            }
+            current = Some((id.to_string(), pattern.trim().to_string(), number));
+        }
+        finish(&mut current)?;
+
+        Ok(Messages(messages))
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.0.contains_key(id)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// All the text the messages show, leaving out what's filled in.
+    pub fn characters(&self) -> String {
+        fn write(text: &mut String, pattern: &[Element]) {
+            for element in pattern {
+                match element {
+                    Element::Text(value) => text.push_str(value),
+                    Element::Variable(_) => {}
+                    Element::Select { variants, .. } => {
+                        for (_, variant) in variants {
+                            write(text, variant);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut text = String::new();
+        for pattern in self.0.values() {
+            write(&mut text, pattern);
+        }
+        text
+    }
+
+    /// The message `id` with `args` filled in, if there is one.
+    pub fn format(&self, id: &str, args: &[(&str, &dyn Display)]) -> Option<String> {
+        let pattern = self.0.get(id)?;
+        let mut text = String::new();
+        write_pattern(&mut text, pattern, args);
+        Some(text)
+    }
+}
+
+fn write_pattern(text: &mut String, pattern: &[Element], args: &[(&str, &dyn Display)]) {
+    let arg = |name: &str| {
+        args.iter()
+            .find(|(arg, _)| *arg == name)
+            .map(|(_, value)| value.to_string())
+    };
+
+    for element in pattern {
+        match element {
+            Element::Text(value) => text.push_str(value),
+            Element::Variable(name) => match arg(name) {
+                Some(value) => text.push_str(&value),
+                None => text.push_str(&format!("{{${}}}", name)),
+            },
+            Element::Select {
+                variable,
+                variants,
+                default,
+            } => {
+                let value = arg(variable).unwrap_or_default();
+                let category = match value.parse::<f64>() {
+                    Ok(1.) => "one",
+                    _ => "other",
+                };
+                let variant = variants
+                    .iter()
+                    .find(|(key, _)| *key == value)
+                    .or_else(|| variants.iter().find(|(key, _)| key == category))
+                    .unwrap_or(&variants[*default]);
+                write_pattern(text, &variant.1, args);
+            }
+        }
+    }
+}
+
+struct PatternParser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> PatternParser<'a> {
+    fn new(source: &'a str) -> Self {
+        PatternParser {
+            chars: source.chars().peekable(),
+        }
+    }
+
+    fn parse(mut self) -> Result<Vec<Element>, String> {
+        let pattern = self.pattern(false)?;
+        match self.chars.next() {
+            Some(c) => Err(format!("unexpected `{}`", c)),
+            None => Ok(pattern),
+        }
+    }
+
+    /// Text and placeables up to the end, or up to the end of the line or the closing brace
+    /// for a selector's variant.
+    fn pattern(&mut self, in_variant: bool) -> Result<Vec<Element>, String> {
+        let mut elements = Vec::new();
+        let mut text = String::new();
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                '{' => {
+                    if !text.is_empty() {
+                        elements.push(Element::Text(std::mem::take(&mut text)));
+                    }
+                    elements.push(self.placeable()?);
+                }
+                '}' | '\n' if in_variant => break,
+                '}' => return Err("unmatched `}`".to_string()),
+                _ => {
+                    text.push(c);
+                    self.chars.next();
+                }
+            }
+        }
+
+        let text = match in_variant {
+            true => text.trim_end().to_string(),
+            false => text,
+        };
+        if !text.is_empty() {
+            elements.push(Element::Text(text));
+        }
+        Ok(elements)
+    }
+
+    fn placeable(&mut self) -> Result<Element, String> {
+        self.expect('{')?;
+        self.skip_blank();
+        let variable = self.variable()?;
+        self.skip_blank();
+
+        match self.chars.next() {
+            Some('}') => Ok(Element::Variable(variable)),
+            Some('-') => {
+                self.expect('>')?;
+                let mut variants = Vec::new();
+                let mut default = None;
+                loop {
+                    self.skip_blank();
+                    match self.chars.peek() {
+                        Some('}') => {
+                            self.chars.next();
+                            break;
+                        }
+                        Some('*') => {
+                            self.chars.next();
+                            if default.replace(variants.len()).is_some() {
+                                return Err("more than one default variant".to_string());
+                            }
+                        }
+                        None => return Err("unclosed selector".to_string()),
+                        _ => {}
+                    }
+
+                    self.expect('[')?;
+                    let mut key = String::new();
+                    loop {
+                        match self.chars.next() {
+                            Some(']') => break,
+                            Some(c) => key.push(c),
+                            None => return Err("unclosed variant key".to_string()),
+                        }
+                    }
+                    while self.chars.next_if(|c| *c == ' ').is_some() {}
+                    variants.push((key.trim().to_string(), self.pattern(true)?));
+                }
+
+                let default = default.ok_or("selector without a default variant")?;
+                Ok(Element::Select {
+                    variable,
+                    variants,
+                    default,
+                })
+            }
+            _ => Err(format!("expected `}}` or `->` after ${}", variable)),
+        }
+    }
+
+    fn variable(&mut self) -> Result<String, String> {
+        self.expect('$')?;
+        let mut name = String::new();
+        while let Some(c) = self
+            .chars
+            .next_if(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        {
+            name.push(c);
+        }
+        match name.is_empty() {
+            true => Err("expected a variable name".to_string()),
+            false => Ok(name),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("expected `{}`, found `{}`", expected, c)),
+            None => Err(format!("expected `{}`", expected)),
+        }
+    }
+
+    fn skip_blank(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+}
+
+#[derive(Default)]
+pub struct MessagesLoader;
+
+impl AssetLoader for MessagesLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let messages = Messages::parse(std::str::from_utf8(bytes)?)
+                .map_err(|error| anyhow::anyhow!("{}: {}", load_context.path().display(), error))?;
+            load_context.set_default_asset(LoadedAsset::new(messages));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ftl"]
+    }
+}
+
+/// The language the game is shown in. Switched from the title screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale(pub &'static LocaleInfo);
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale(locale_info(FALLBACK_LOCALE))
+    }
+}
+
+impl Locale {
+    /// The language after this one in `LOCALES`, wrapping round.
+    pub fn next(self) -> Self {
+        let index = LOCALES
+            .iter()
+            .position(|locale| locale == self.0)
+            .unwrap_or_default();
+        Locale(&LOCALES[(index + 1) % LOCALES.len()])
+    }
+}
+
+/// The current language's messages, with the fallback language's behind them
+/// for anything that hasn't been translated yet.
+#[derive(Debug, Clone, Default)]
+pub struct Translations {
+    messages: Messages,
+    fallback: Messages,
+}
+
+impl Translations {
+    pub fn new(messages: Messages, fallback: Messages) -> Self {
+        Translations { messages, fallback }
+    }
+
+    pub fn text(&self, id: &str) -> String {
+        self.format(id, &[])
+    }
+
+    /// All the text shown in this language, including what falls back.
+    pub fn characters(&self) -> String {
+        self.messages.characters() + &self.fallback.characters()
+    }
+
+    /// The message `id` with `args` filled in. A message missing from every language
+    /// shows its id, so it's easy to spot, but nothing is shown before any have loaded.
+    pub fn format(&self, id: &str, args: &[(&str, &dyn Display)]) -> String {
+        if self.messages.0.is_empty() && self.fallback.0.is_empty() {
+            return String::new();
+        }
+
+        self.messages
+            .format(id, args)
+            .or_else(|| self.fallback.format(id, args))
+            .unwrap_or_else(|| id.to_string())
+    }
+}
+
+/// Text whose first section is the message with this id, kept in the current language.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Localized(pub &'static str);
+
+/// The font every text is drawn in, the first of the language's fonts that covers it.
+#[derive(Debug, Default)]
+pub struct LocaleFont(pub Handle<Font>);
+
+#[derive(Default)]
+struct MessageHandles {
+    current: Handle<Messages>,
+    fallback: Handle<Messages>,
+    /// The language's own fonts then `FALLBACK_FONTS`, in the order they're tried.
+    fonts: Vec<Handle<Font>>,
+}
+
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Messages>()
+            .init_asset_loader::<MessagesLoader>()
+            .init_resource::<Locale>()
+            .init_resource::<Translations>()
+            .init_resource::<LocaleFont>()
+            .init_resource::<MessageHandles>()
+            .add_startup_system(startup_locale)
+            .add_system(load_locale)
+            .add_system(apply_messages.after(load_locale))
+            .add_system(choose_font.after(apply_messages))
+            .add_system(localize_text.after(apply_messages))
+            .add_system(localize_window_title.after(apply_messages))
+            .add_system(apply_locale_font.after(choose_font));
+    }
+}
+
+/// Starts in the language last picked, or else the one the system is set to.
+fn startup_locale(mut locale: ResMut<Locale>, game_config: Res<GameConfig>) {
+    let requested = storage::load(game_config.game_slug, LOCALE_KEY).or_else(system_locale);
+    if let Some(requested) = requested {
+        *locale = Locale(negotiate(&requested));
+    }
+    info!("Using locale {}", locale.0.id);
+}
+
+/// Loads the language's messages and font whenever it changes, and remembers the choice.
+fn load_locale(
+    locale: Res<Locale>,
+    mut handles: ResMut<MessageHandles>,
+    mut font: ResMut<LocaleFont>,
+    asset_server: Res<AssetServer>,
+    game_config: Res<GameConfig>,
+) {
+    if !locale.is_changed() {
+        return;
+    }
+
+    handles.current = asset_server.load(format!("locales/{}.ftl", locale.0.id).as_str());
+    handles.fallback = asset_server.load(format!("locales/{}.ftl", FALLBACK_LOCALE).as_str());
+    handles.fonts = locale
+        .0
+        .fonts
+        .iter()
+        .chain(
+            FALLBACK_FONTS
+                .iter()
+                .filter(|path| !locale.0.fonts.contains(path)),
+        )
+        .map(|path| asset_server.load(*path))
+        .collect();
+    // Shown until the font that covers the language is known
+    font.0 = handles.fonts[0].clone();
+
+    // Only a choice made on the title screen is saved, not the one picked at startup
+    if !locale.is_added() {
+        if let Err(error) = storage::save(game_config.game_slug, LOCALE_KEY, locale.0.id) {
+            error!("Unable to save the language: {}", error);
+        }
+    }
+}
+
+/// Picks the first of the language's fonts with a glyph for every character of its messages,
+/// once every font has loaded or failed to. When none has them all, the first that loaded.
+fn choose_font(
+    mut pending: Local<bool>,
+    mut font_events: EventReader<AssetEvent<Font>>,
+    handles: Res<MessageHandles>,
+    translations: Res<Translations>,
+    fonts: Res<Assets<Font>>,
+    asset_server: Res<AssetServer>,
+    mut font: ResMut<LocaleFont>,
+) {
+    // A font failing to load sends no event, so keep checking until they've all settled
+    *pending |= font_events.iter().count() > 0 || translations.is_changed() || handles.is_changed();
+    let settled = handles.fonts.iter().all(|handle| {
+        matches!(
+            asset_server.get_load_state(handle),
+            LoadState::Loaded | LoadState::Failed
+        )
+    });
+    if !*pending || !settled {
+        return;
+    }
+    *pending = false;
+
+    let text = translations.characters();
+    let loaded: Vec<_> = handles
+        .fonts
+        .iter()
+        .filter(|handle| fonts.contains(*handle))
+        .collect();
+    let chosen = loaded
+        .iter()
+        .find(|handle| covers(&fonts.get(**handle).unwrap().font, &text))
+        .or_else(|| {
+            warn!("No font has every character of the language's messages");
+            loaded.first()
+        });
+    if let Some(&chosen) = chosen.filter(|chosen| font.0 != ***chosen) {
+        font.0 = chosen.clone();
+    }
+}
+
+/// Swaps in the new language's messages once they've loaded, and again if their file changes.
+fn apply_messages(
+    mut asset_events: EventReader<AssetEvent<Messages>>,
+    handles: Res<MessageHandles>,
+    messages: Res<Assets<Messages>>,
+    mut translations: ResMut<Translations>,
+) {
+    let loaded = asset_events.iter().any(|event| match event {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+            *handle == handles.current || *handle == handles.fallback
+        }
+        AssetEvent::Removed { .. } => false,
+    });
+    if !loaded && !handles.is_changed() {
+        return;
+    }
+
+    let current = messages.get(&handles.current);
+    let fallback = messages.get(&handles.fallback);
+    // Hold on to the old language until the new one is there, rather than flashing ids
+    if current.is_some() || fallback.is_some() {
+        *translations = Translations::new(
+            current.cloned().unwrap_or_default(),
+            fallback.cloned().unwrap_or_default(),
+        );
+    }
+}
+
+fn localize_text(
+    mut texts: Query<(&mut Text, &Localized, ChangeTrackers<Localized>)>,
+    translations: Res<Translations>,
+) {
+    for (mut text, localized, tracker) in &mut texts {
+        if translations.is_changed() || tracker.is_changed() {
+            text.sections[0].value = translations.text(localized.0);
+        }
+    }
+}
+
+fn localize_window_title(mut windows: ResMut<Windows>, translations: Res<Translations>) {
+    if !translations.is_changed() {
+        return;
+    }
+
+    // Keep the title from the config until the messages have loaded
+    let title = translations.text("game-title");
+    if let Some(window) = windows.get_primary_mut().filter(|_| !title.is_empty()) {
+        window.set_title(title);
+    }
+}
+
+/// Draws every text in the language's font, including text spawned later.
+#[allow(clippy::type_complexity)]
+fn apply_locale_font(
+    mut texts: ParamSet<(Query<&mut Text>, Query<&mut Text, Added<Text>>)>,
+    font: Res<LocaleFont>,
+) {
+    let mut apply = |mut text: Mut<Text>| {
+        if text
+            .sections
+            .iter()
+            .any(|section| section.style.font != font.0)
+        {
+            for section in &mut text.sections {
+                section.style.font = font.0.clone();
+            }
+        }
+    };
+
+    if font.is_changed() {
+        texts.p0().iter_mut().for_each(&mut apply);
+    } else {
+        texts.p1().iter_mut().for_each(&mut apply);
+    }
+}
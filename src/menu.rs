@@ -0,0 +1,664 @@
+use std::marker::PhantomData;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    bot::{Bot, BotDifficulty},
+    bump::GameMode,
+    config::{BUTTON_COLOR, BUTTON_HOVER_COLOR, BUTTON_PRESSED_COLOR},
+    core::{event::ResetGame, player::Player, timestep::GameSpeed},
+    cues::AudioCues,
+    difficulty::Difficulty,
+    effects::EffectIntensity,
+    hud::RoutePreview,
+    level::ActiveLevel,
+    locale::{Locale, Localized, Translations},
+    radar::Minimap,
+    sound::SoundSettings,
+};
+
+/// Game speed change per button press.
+const GAME_SPEED_STEP: f32 = 0.1;
+/// Volume change per button press.
+const VOLUME_STEP: f32 = 0.1;
+/// Keys that change settings or open the campaign on the title screen instead of starting the game.
+const SETTINGS_KEYS: [KeyCode; 16] = [
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Minus,
+    KeyCode::Equals,
+    KeyCode::NumpadSubtract,
+    KeyCode::NumpadAdd,
+    KeyCode::C,
+    KeyCode::R,
+    KeyCode::V,
+    KeyCode::M,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::LBracket,
+    KeyCode::RBracket,
+    KeyCode::Semicolon,
+    KeyCode::Apostrophe,
+];
+
+#[derive(Component)]
+struct TitleScreen;
+#[derive(Component)]
+struct DifficultyText;
+#[derive(Component)]
+struct GameSpeedText;
+#[derive(Component)]
+struct RoutePreviewText;
+#[derive(Component)]
+struct EffectsText;
+#[derive(Component)]
+struct MinimapText;
+#[derive(Component)]
+struct AudioCuesText;
+#[derive(Component)]
+struct SfxVolumeText;
+#[derive(Component)]
+struct MusicVolumeText;
+#[derive(Component)]
+struct LanguageText;
+#[derive(Component, Clone, Copy)]
+enum MenuButton {
+    PreviousDifficulty,
+    NextDifficulty,
+    SlowDown,
+    SpeedUp,
+    ToggleRoutePreview,
+    CycleEffects,
+    ToggleMinimap,
+    ToggleAudioCues,
+    CycleLanguage,
+    SfxQuieter,
+    SfxLouder,
+    MusicQuieter,
+    MusicLouder,
+    Campaign,
+}
+
+/// Title screen shown over a bot playing the game, until someone presses something.
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameMode::Attract)
+            .add_startup_system(startup_title)
+            .add_system(attract_bot)
+            .add_system(menu_buttons)
+            .add_system(menu_keys)
+            .add_system(update_settings_text)
+            .add_system(update_language_text)
+            .add_system(start_game.after(menu_buttons).after(menu_keys))
+            .add_system(return_to_title)
+            .add_system(title_visibility);
+    }
+}
+
+fn startup_title(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/Hind-Regular.otf");
+    let settings_style = TextStyle {
+        font: font.clone(),
+        font_size: 32.,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::rgba(0., 0., 0., 0.4).into(),
+            ..default()
+        })
+        .insert(TitleScreen)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 96.,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(Localized("game-title"))
+                .insert(TitleScreen);
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 40.,
+                        color: Color::rgb(0.8, 0.8, 0.8),
+                    },
+                ))
+                .insert(Localized("menu-start"))
+                .insert(TitleScreen);
+
+            spawn_setting_row(
+                parent,
+                &settings_style,
+                MenuButton::PreviousDifficulty,
+                MenuButton::NextDifficulty,
+                DifficultyText,
+            );
+            spawn_setting_row(
+                parent,
+                &settings_style,
+                MenuButton::SlowDown,
+                MenuButton::SpeedUp,
+                GameSpeedText,
+            );
+            spawn_setting_row(
+                parent,
+                &settings_style,
+                MenuButton::SfxQuieter,
+                MenuButton::SfxLouder,
+                SfxVolumeText,
+            );
+            spawn_setting_row(
+                parent,
+                &settings_style,
+                MenuButton::MusicQuieter,
+                MenuButton::MusicLouder,
+                MusicVolumeText,
+            );
+            spawn_toggle_button(
+                parent,
+                &settings_style,
+                MenuButton::ToggleRoutePreview,
+                300.,
+                RoutePreviewText,
+            );
+            spawn_toggle_button(
+                parent,
+                &settings_style,
+                MenuButton::CycleEffects,
+                400.,
+                EffectsText,
+            );
+            spawn_toggle_button(
+                parent,
+                &settings_style,
+                MenuButton::ToggleMinimap,
+                300.,
+                MinimapText,
+            );
+            spawn_toggle_button(
+                parent,
+                &settings_style,
+                MenuButton::ToggleAudioCues,
+                300.,
+                AudioCuesText,
+            );
+            spawn_toggle_button(
+                parent,
+                &settings_style,
+                MenuButton::CycleLanguage,
+                300.,
+                LanguageText,
+            );
+            spawn_toggle_button(
+                parent,
+                &settings_style,
+                MenuButton::Campaign,
+                200.,
+                Localized("menu-campaign"),
+            );
+
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font,
+                        font_size: 24.,
+                        color: Color::rgb(0.6, 0.6, 0.6),
+                    },
+                ))
+                .insert(Localized("menu-keys"))
+                .insert(TitleScreen);
+        });
+}
+
+/// A button whose label is filled in through `label_marker`, such as a setting's current value.
+fn spawn_toggle_button(
+    parent: &mut ChildBuilder,
+    text_style: &TextStyle,
+    action: MenuButton,
+    width: f32,
+    label_marker: impl Component,
+) {
+    parent
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(width), Val::Px(48.)),
+                margin: UiRect::all(Val::Px(8.)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: BUTTON_COLOR.into(),
+            ..default()
+        })
+        .insert(action)
+        .insert(TitleScreen)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section("", text_style.clone()))
+                .insert(label_marker)
+                .insert(TitleScreen);
+        });
+}
+
+/// A setting's value between a button to lower it and one to raise it.
+fn spawn_setting_row(
+    parent: &mut ChildBuilder,
+    text_style: &TextStyle,
+    lower: MenuButton,
+    raise: MenuButton,
+    value_marker: impl Component,
+) {
+    let button = |parent: &mut ChildBuilder, label: &str, action: MenuButton| {
+        parent
+            .spawn_bundle(ButtonBundle {
+                style: Style {
+                    size: Size::new(Val::Px(48.), Val::Px(48.)),
+                    margin: UiRect::all(Val::Px(8.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                color: BUTTON_COLOR.into(),
+                ..default()
+            })
+            .insert(action)
+            .insert(TitleScreen)
+            .with_children(|parent| {
+                parent
+                    .spawn_bundle(TextBundle::from_section(label, text_style.clone()))
+                    .insert(TitleScreen);
+            });
+    };
+
+    parent
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                margin: UiRect::all(Val::Px(4.)),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(TitleScreen)
+        .with_children(|parent| {
+            button(parent, "<", lower);
+            parent
+                .spawn_bundle(
+                    TextBundle::from_section("", text_style.clone()).with_style(Style {
+                        size: Size::new(Val::Px(200.), Val::Auto),
+                        ..default()
+                    }),
+                )
+                .insert(value_marker)
+                .insert(TitleScreen);
+            button(parent, ">", raise);
+        });
+}
+
+/// The settings that can be changed from the title screen.
+#[derive(SystemParam)]
+struct MenuSettings<'w, 's> {
+    difficulty: ResMut<'w, Difficulty>,
+    game_speed: ResMut<'w, GameSpeed>,
+    route_preview: ResMut<'w, RoutePreview>,
+    effect_intensity: ResMut<'w, EffectIntensity>,
+    minimap: ResMut<'w, Minimap>,
+    sound_settings: ResMut<'w, SoundSettings>,
+    audio_cues: ResMut<'w, AudioCues>,
+    locale: ResMut<'w, Locale>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+fn menu_action(action: MenuButton, settings: &mut MenuSettings, game_mode: &mut GameMode) {
+    let game_speed = settings.game_speed.0;
+    let SoundSettings {
+        sfx_volume,
+        music_volume,
+    } = *settings.sound_settings;
+    match action {
+        MenuButton::PreviousDifficulty => *settings.difficulty = settings.difficulty.previous(),
+        MenuButton::NextDifficulty => *settings.difficulty = settings.difficulty.next(),
+        MenuButton::SlowDown => {
+            *settings.game_speed = GameSpeed((game_speed - GAME_SPEED_STEP).max(GameSpeed::MIN))
+        }
+        MenuButton::SpeedUp => {
+            *settings.game_speed = GameSpeed((game_speed + GAME_SPEED_STEP).min(GameSpeed::MAX))
+        }
+        MenuButton::ToggleRoutePreview => {
+            *settings.route_preview = RoutePreview(!settings.route_preview.0)
+        }
+        MenuButton::CycleEffects => *settings.effect_intensity = settings.effect_intensity.next(),
+        MenuButton::ToggleMinimap => *settings.minimap = Minimap(!settings.minimap.0),
+        MenuButton::SfxQuieter => {
+            settings.sound_settings.sfx_volume = (sfx_volume - VOLUME_STEP).max(0.)
+        }
+        MenuButton::SfxLouder => {
+            settings.sound_settings.sfx_volume = (sfx_volume + VOLUME_STEP).min(1.)
+        }
+        MenuButton::MusicQuieter => {
+            settings.sound_settings.music_volume = (music_volume - VOLUME_STEP).max(0.)
+        }
+        MenuButton::MusicLouder => {
+            settings.sound_settings.music_volume = (music_volume + VOLUME_STEP).min(1.)
+        }
+        MenuButton::ToggleAudioCues => *settings.audio_cues = AudioCues(!settings.audio_cues.0),
+        MenuButton::CycleLanguage => *settings.locale = settings.locale.next(),
+        MenuButton::Campaign => *game_mode = GameMode::LevelSelect,
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn menu_buttons(
+    mut buttons: Query<(&Interaction, &MenuButton, &mut UiColor), Changed<Interaction>>,
+    mut settings: MenuSettings,
+    mut game_mode: ResMut<GameMode>,
+) {
+    for (interaction, action, mut color) in &mut buttons {
+        *color = match interaction {
+            Interaction::Clicked => BUTTON_PRESSED_COLOR,
+            Interaction::Hovered => BUTTON_HOVER_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        }
+        .into();
+
+        if *interaction == Interaction::Clicked && *game_mode == GameMode::Attract {
+            menu_action(*action, &mut settings, &mut game_mode);
+        }
+    }
+}
+
+fn menu_keys(
+    keys: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    mut settings: MenuSettings,
+    mut game_mode: ResMut<GameMode>,
+) {
+    if *game_mode != GameMode::Attract {
+        return;
+    }
+
+    let gamepad_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(*gamepad, button_type)))
+    };
+
+    let mut actions = Vec::new();
+    if keys.just_pressed(KeyCode::Left) || gamepad_pressed(GamepadButtonType::DPadLeft) {
+        actions.push(MenuButton::PreviousDifficulty);
+    }
+    if keys.just_pressed(KeyCode::Right) || gamepad_pressed(GamepadButtonType::DPadRight) {
+        actions.push(MenuButton::NextDifficulty);
+    }
+    if keys.any_just_pressed([KeyCode::Minus, KeyCode::NumpadSubtract])
+        || gamepad_pressed(GamepadButtonType::DPadDown)
+    {
+        actions.push(MenuButton::SlowDown);
+    }
+    if keys.any_just_pressed([KeyCode::Equals, KeyCode::NumpadAdd])
+        || gamepad_pressed(GamepadButtonType::DPadUp)
+    {
+        actions.push(MenuButton::SpeedUp);
+    }
+    if keys.just_pressed(KeyCode::LBracket) {
+        actions.push(MenuButton::SfxQuieter);
+    }
+    if keys.just_pressed(KeyCode::RBracket) {
+        actions.push(MenuButton::SfxLouder);
+    }
+    if keys.just_pressed(KeyCode::Semicolon) {
+        actions.push(MenuButton::MusicQuieter);
+    }
+    if keys.just_pressed(KeyCode::Apostrophe) {
+        actions.push(MenuButton::MusicLouder);
+    }
+    if keys.just_pressed(KeyCode::R) {
+        actions.push(MenuButton::ToggleRoutePreview);
+    }
+    if keys.just_pressed(KeyCode::V) {
+        actions.push(MenuButton::CycleEffects);
+    }
+    if keys.just_pressed(KeyCode::M) {
+        actions.push(MenuButton::ToggleMinimap);
+    }
+    if keys.just_pressed(KeyCode::K) {
+        actions.push(MenuButton::ToggleAudioCues);
+    }
+    if keys.just_pressed(KeyCode::L) {
+        actions.push(MenuButton::CycleLanguage);
+    }
+    if keys.just_pressed(KeyCode::C) {
+        actions.push(MenuButton::Campaign);
+    }
+
+    for action in actions {
+        menu_action(action, &mut settings, &mut game_mode);
+    }
+}
+
+/// The title screen settings, read for display.
+#[derive(SystemParam)]
+struct ShownSettings<'w, 's> {
+    difficulty: Res<'w, Difficulty>,
+    game_speed: Res<'w, GameSpeed>,
+    route_preview: Res<'w, RoutePreview>,
+    effect_intensity: Res<'w, EffectIntensity>,
+    minimap: Res<'w, Minimap>,
+    sound_settings: Res<'w, SoundSettings>,
+    audio_cues: Res<'w, AudioCues>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+#[allow(clippy::type_complexity)]
+fn update_settings_text(
+    settings: ShownSettings,
+    translations: Res<Translations>,
+    mut texts: ParamSet<(
+        Query<&mut Text, With<DifficultyText>>,
+        Query<&mut Text, With<GameSpeedText>>,
+        Query<&mut Text, With<RoutePreviewText>>,
+        Query<&mut Text, With<EffectsText>>,
+        Query<&mut Text, With<MinimapText>>,
+        Query<&mut Text, With<SfxVolumeText>>,
+        Query<&mut Text, With<MusicVolumeText>>,
+        Query<&mut Text, With<AudioCuesText>>,
+    )>,
+) {
+    let ShownSettings {
+        difficulty,
+        game_speed,
+        route_preview,
+        effect_intensity,
+        minimap,
+        sound_settings,
+        audio_cues,
+        ..
+    } = settings;
+
+    // Everything is rewritten in the new language when it changes
+    let retranslate = translations.is_changed();
+    let state = |on: bool| match on {
+        true => translations.text("setting-on"),
+        false => translations.text("setting-off"),
+    };
+
+    if difficulty.is_changed() || retranslate {
+        for mut text in texts.p0().iter_mut() {
+            text.sections[0].value = translations.text(difficulty.message_id());
+        }
+    }
+    if game_speed.is_changed() || retranslate {
+        let percent = format!("{:.0}", game_speed.clamped() * 100.);
+        for mut text in texts.p1().iter_mut() {
+            text.sections[0].value = translations.format("menu-speed", &[("percent", &percent)]);
+        }
+    }
+    if route_preview.is_changed() || retranslate {
+        for mut text in texts.p2().iter_mut() {
+            text.sections[0].value =
+                translations.format("menu-route-preview", &[("state", &state(route_preview.0))]);
+        }
+    }
+    if effect_intensity.is_changed() || retranslate {
+        let level = translations.text(effect_intensity.message_id());
+        for mut text in texts.p3().iter_mut() {
+            text.sections[0].value = translations.format("menu-effects", &[("level", &level)]);
+        }
+    }
+    if minimap.is_changed() || retranslate {
+        for mut text in texts.p4().iter_mut() {
+            text.sections[0].value =
+                translations.format("menu-minimap", &[("state", &state(minimap.0))]);
+        }
+    }
+    if sound_settings.is_changed() || retranslate {
+        let sfx = format!("{:.0}", sound_settings.sfx_volume * 100.);
+        let music = format!("{:.0}", sound_settings.music_volume * 100.);
+        for mut text in texts.p5().iter_mut() {
+            text.sections[0].value = translations.format("menu-sound", &[("percent", &sfx)]);
+        }
+        for mut text in texts.p6().iter_mut() {
+            text.sections[0].value = translations.format("menu-music", &[("percent", &music)]);
+        }
+    }
+    if audio_cues.is_changed() || retranslate {
+        for mut text in texts.p7().iter_mut() {
+            text.sections[0].value =
+                translations.format("menu-audio-cues", &[("state", &state(audio_cues.0))]);
+        }
+    }
+}
+
+fn update_language_text(
+    locale: Res<Locale>,
+    translations: Res<Translations>,
+    mut texts: Query<&mut Text, With<LanguageText>>,
+) {
+    if !locale.is_changed() && !translations.is_changed() {
+        return;
+    }
+
+    for mut text in &mut texts {
+        text.sections[0].value = translations.format("menu-language", &[("name", &locale.0.name)]);
+    }
+}
+
+/// While on the title screen or level select the player is flown by a bot.
+fn attract_bot(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    players: Query<Entity, (With<Player>, Without<Bot>)>,
+) {
+    if !matches!(*game_mode, GameMode::Attract | GameMode::LevelSelect) {
+        return;
+    }
+
+    for entity in &players {
+        commands
+            .entity(entity)
+            .insert(Bot::new(BotDifficulty::Normal));
+    }
+}
+
+/// Everything that can be pressed to start playing.
+#[derive(SystemParam)]
+struct StartInput<'w, 's> {
+    keys: Res<'w, Input<KeyCode>>,
+    mouse: Res<'w, Input<MouseButton>>,
+    touches: Res<'w, Touches>,
+    gamepad_buttons: Res<'w, Input<GamepadButton>>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+fn start_game(
+    mut commands: Commands,
+    mut game_mode: ResMut<GameMode>,
+    mut reset_events: EventWriter<ResetGame>,
+    input: StartInput,
+    bots: Query<Entity, (With<Player>, With<Bot>)>,
+    menu_buttons: Query<&Interaction, With<MenuButton>>,
+) {
+    if *game_mode != GameMode::Attract {
+        return;
+    }
+
+    // Presses on the settings go to the settings rather than starting the game
+    let on_button = menu_buttons
+        .iter()
+        .any(|interaction| *interaction != Interaction::None);
+    let pressed = input
+        .keys
+        .get_just_pressed()
+        .any(|key| *key != KeyCode::Escape && !SETTINGS_KEYS.contains(key))
+        || (!on_button && input.mouse.get_just_pressed().next().is_some())
+        || (!on_button && input.touches.any_just_pressed())
+        || input.gamepad_buttons.get_just_pressed().any(|button| {
+            !matches!(
+                button.button_type,
+                GamepadButtonType::DPadLeft
+                    | GamepadButtonType::DPadRight
+                    | GamepadButtonType::DPadUp
+                    | GamepadButtonType::DPadDown
+            )
+        });
+    if !pressed {
+        return;
+    }
+
+    *game_mode = GameMode::Playing;
+    for entity in &bots {
+        commands.entity(entity).remove::<Bot>();
+    }
+    reset_events.send(ResetGame);
+}
+
+fn return_to_title(
+    mut game_mode: ResMut<GameMode>,
+    mut active_level: ResMut<ActiveLevel>,
+    mut reset_events: EventWriter<ResetGame>,
+    keys: Res<Input<KeyCode>>,
+) {
+    if *game_mode == GameMode::Playing && keys.just_pressed(KeyCode::Escape) {
+        *game_mode = GameMode::Attract;
+        // Back to the endless game behind the title screen
+        if active_level.0.is_some() {
+            *active_level = ActiveLevel(None);
+        }
+        reset_events.send(ResetGame);
+    }
+}
+
+fn title_visibility(
+    game_mode: Res<GameMode>,
+    mut title_screens: Query<&mut Visibility, With<TitleScreen>>,
+) {
+    if !game_mode.is_changed() {
+        return;
+    }
+
+    for mut visibility in &mut title_screens {
+        visibility.is_visible = *game_mode == GameMode::Attract;
+    }
+}
@@ -0,0 +1,645 @@
+use bevy::{math::Vec3Swizzles, prelude::*};
+use bevy_rapier2d::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::{
+    bump::{BumpSystem, GameRng, MapConfig},
+    core::{
+        collision::{barrier_group, OBSTACLE_GROUP, PLAYER_GROUP},
+        crystal::CrystalColor,
+        event::{ColorChanged, ObstacleHit, ResetGame},
+        platform::{Platform, PlatformBundle, PlatformKind},
+        player::Player,
+        timestep::{FixedUpdateAppExt, GameClock},
+    },
+};
+
+/// Half the thickness of the top and bottom borders, which the route has to stay clear of.
+const BORDER_HALF_HEIGHT: f32 = 0.5;
+const OBSTACLE_COLOR: Color = Color::rgb(0.55, 0.55, 0.6);
+const BOUNCY_COLOR: Color = Color::rgb(0.4, 0.75, 0.5);
+const ONE_WAY_COLOR: Color = Color::rgb(0.75, 0.75, 0.8);
+const CRUMBLING_COLOR: Color = Color::rgb(0.6, 0.45, 0.3);
+const CONVEYOR_COLOR: Color = Color::rgb(0.8, 0.7, 0.3);
+const DAMAGING_COLOR: Color = Color::rgb(0.8, 0.25, 0.25);
+
+/// Tuning for the obstacle generator, part of `MapConfig`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ObstacleConfig {
+    pub enabled: bool,
+    /// Horizontal distance between obstacle columns.
+    pub spacing: f32,
+    /// Height of the opening the route passes through.
+    pub min_gap: f32,
+    pub max_gap: f32,
+    /// Steepest climb or dive the route may take between two columns, in height per distance.
+    pub max_route_slope: f32,
+    pub wall_width: f32,
+    pub platform_height: f32,
+    pub moving_amplitude: f32,
+    /// Oscillations per second of moving platforms.
+    pub moving_frequency: f32,
+    /// How many wedges away from a barrier's color the player can be and still pass through it.
+    pub barrier_tolerance: i8,
+}
+
+impl Default for ObstacleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            spacing: 14.,
+            min_gap: 5.,
+            max_gap: 8.,
+            max_route_slope: 0.5,
+            wall_width: 1.,
+            platform_height: 0.5,
+            moving_amplitude: 3.,
+            moving_frequency: 0.25,
+            barrier_tolerance: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObstacleKind {
+    /// Picked by the generator for a column.
+    Column(ColumnKind),
+    /// Put there by a level rather than the generator.
+    Placed,
+}
+
+/// The obstacles the generator picks from for each column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    /// A wall from one border, leaving the rest of the column open.
+    Pillar,
+    /// Walls from both borders with a narrow opening between them.
+    Gap,
+    /// A floating ledge beside the route.
+    Platform,
+    /// A floating ledge that bobs up and down beside the route.
+    MovingPlatform,
+    /// Like `Gap`, but the walls are colored barriers that let a matching player through.
+    Barrier,
+}
+
+const COLUMN_KINDS: [ColumnKind; 5] = [
+    ColumnKind::Pillar,
+    ColumnKind::Gap,
+    ColumnKind::Platform,
+    ColumnKind::MovingPlatform,
+    ColumnKind::Barrier,
+];
+
+#[derive(Component)]
+pub struct Obstacle {
+    pub kind: ObstacleKind,
+    pub size: Vec2,
+}
+
+/// An obstacle that's only solid for players whose color is too far from its own.
+#[derive(Component)]
+pub struct Barrier(pub CrystalColor);
+
+/// A crumbling platform that has been touched, and when it will be gone.
+#[derive(Component)]
+struct Crumble {
+    triggered_at: f64,
+    despawn_at: f64,
+}
+
+/// Vertical sine motion of a moving platform, around the height it was spawned at.
+#[derive(Component)]
+struct Oscillate {
+    amplitude: f32,
+    frequency: f32,
+    start: f64,
+}
+
+/// An obstacle authored as part of a level, entering from the right edge at `time`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlacedObstacle {
+    /// Seconds after the level starts.
+    pub time: f64,
+    /// Distance past the right edge, negative to start inside the map.
+    #[serde(default)]
+    pub x_offset: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    #[serde(default)]
+    pub platform: PlatformKind,
+    /// Makes the obstacle bouncy, as with `PlatformBundle::with_restitution`.
+    #[serde(default)]
+    pub restitution: Option<f32>,
+    /// Makes the obstacle a barrier of this color.
+    #[serde(default)]
+    pub barrier: Option<CrystalColor>,
+    /// How far it bobs up and down, at the configured `moving_frequency`.
+    #[serde(default)]
+    pub amplitude: f32,
+}
+
+impl PlacedObstacle {
+    pub fn color(&self) -> Color {
+        if let Some(barrier) = self.barrier {
+            return barrier.to_color();
+        }
+        match (self.platform, self.restitution) {
+            (PlatformKind::Solid, Some(_)) => BOUNCY_COLOR,
+            (PlatformKind::Solid, None) => OBSTACLE_COLOR,
+            (PlatformKind::OneWay, _) => ONE_WAY_COLOR,
+            (PlatformKind::Crumbling { .. }, _) => CRUMBLING_COLOR,
+            (PlatformKind::Conveyor { .. }, _) => CONVEYOR_COLOR,
+            (PlatformKind::Damaging, _) => DAMAGING_COLOR,
+        }
+    }
+
+    pub(crate) fn spawn(&self, commands: &mut Commands, map_config: &MapConfig, clock: &GameClock) {
+        let mut platform = PlatformBundle::default()
+            .with_color(self.color())
+            .with_kind(self.platform);
+        if let Some(coefficient) = self.restitution {
+            platform = platform.with_restitution(coefficient);
+        }
+
+        let x = map_config.half_size().x + self.x_offset + self.width / 2.;
+        let oscillate = (self.amplitude > 0.).then_some(Oscillate {
+            amplitude: self.amplitude,
+            frequency: map_config.obstacles.moving_frequency,
+            start: clock.elapsed,
+        });
+        spawn_obstacle(
+            commands,
+            platform,
+            ObstacleKind::Placed,
+            Vec2::new(x, self.y),
+            Vec2::new(self.width, self.height),
+            oscillate,
+            self.barrier,
+        );
+    }
+}
+
+/// Where the next column goes, and the height the passable route is at.
+/// Every column leaves the route open, and the route never changes height faster than
+/// `max_route_slope` allows, so there's always a way through.
+#[derive(Default)]
+pub struct ObstacleGenerator {
+    pub distance: f32,
+    pub route_y: f32,
+}
+
+/// Procedural obstacles that scroll with the crystals, and the behavior of every `PlatformKind`.
+pub struct ObstaclePlugin;
+
+impl Plugin for ObstaclePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ObstacleGenerator>()
+            .add_fixed_event::<ObstacleHit>()
+            .add_fixed_system(reset_obstacles.before(BumpSystem::Movement))
+            // Last of everything drawing from the GameRng, for repeatable runs
+            .add_fixed_system(
+                spawn_obstacles
+                    .after(reset_obstacles)
+                    .after(BumpSystem::Scoring),
+            )
+            .add_fixed_system(obstacle_treadmill)
+            .add_fixed_system(update_player_groups.after(BumpSystem::Hazard))
+            .add_fixed_system(one_way_platforms.after(conveyor_platforms))
+            .add_fixed_system(crumble_platforms)
+            .add_fixed_system(conveyor_platforms.after(BumpSystem::Movement))
+            .add_system(barrier_tint)
+            .add_fixed_system(
+                obstacle_collision
+                    .label(BumpSystem::Hazard)
+                    .after(BumpSystem::Collision),
+            );
+    }
+}
+
+fn reset_obstacles(
+    mut commands: Commands,
+    mut reset_events: EventReader<ResetGame>,
+    mut generator: ResMut<ObstacleGenerator>,
+    obstacles: Query<Entity, With<Obstacle>>,
+) {
+    if reset_events.iter().count() == 0 {
+        return;
+    }
+
+    *generator = ObstacleGenerator::default();
+    for entity in &obstacles {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn spawn_obstacle(
+    commands: &mut Commands,
+    platform: PlatformBundle,
+    kind: ObstacleKind,
+    position: Vec2,
+    size: Vec2,
+    oscillate: Option<Oscillate>,
+    barrier: Option<CrystalColor>,
+) {
+    // The route can run right along a border, leaving nothing of the wall on that side
+    if size.y <= 0. {
+        return;
+    }
+
+    let (platform, group) = match barrier {
+        Some(barrier) => (
+            platform.with_color(barrier.to_color()),
+            barrier_group(barrier),
+        ),
+        None => (platform, OBSTACLE_GROUP),
+    };
+    let mut entity = commands.spawn_bundle(
+        platform
+            .with_position(position.x, position.y)
+            .with_size(size.x, size.y)
+            .with_rigid_body(RigidBody::KinematicVelocityBased),
+    );
+    entity
+        .insert(Obstacle { kind, size })
+        .insert(Velocity::default())
+        // Only the player bumps into obstacles, crystals drift through them
+        .insert(CollisionGroups::new(group, PLAYER_GROUP));
+    if let Some(oscillate) = oscillate {
+        entity.insert(oscillate);
+    }
+    if let Some(barrier) = barrier {
+        entity.insert(Barrier(barrier));
+    }
+}
+
+fn spawn_obstacles(
+    mut commands: Commands,
+    mut generator: ResMut<ObstacleGenerator>,
+    mut rng: ResMut<GameRng>,
+    clock: Res<GameClock>,
+    map_config: Res<MapConfig>,
+) {
+    let config = &map_config.obstacles;
+    if !config.enabled {
+        return;
+    }
+
+    generator.distance += map_config.crystal_linvel * clock.delta_seconds();
+    if generator.distance < config.spacing {
+        return;
+    }
+    generator.distance -= config.spacing;
+
+    let rng = &mut rng.0;
+    let hmap_size = map_config.half_size();
+    // Spawn just past the right edge, keeping the spacing exact whatever the step size
+    let x = hmap_size.x + config.wall_width + generator.distance;
+    let top = hmap_size.y - BORDER_HALF_HEIGHT;
+    let bottom = -top;
+
+    let gap = rng.gen_range(config.min_gap..=config.max_gap.max(config.min_gap));
+    let limit = (top - gap / 2.).max(0.);
+    let max_dy = config.max_route_slope * config.spacing;
+    let route_y = (generator.route_y + rng.gen_range(-max_dy..=max_dy)).clamp(-limit, limit);
+    generator.route_y = route_y;
+    let gap_top = route_y + gap / 2.;
+    let gap_bottom = route_y - gap / 2.;
+
+    let kind = COLUMN_KINDS[rng.gen_range(0..COLUMN_KINDS.len())];
+    let wall = |from: f32, to: f32| {
+        (
+            Vec2::new(x, (from + to) / 2.),
+            Vec2::new(config.wall_width, to - from),
+        )
+    };
+    match kind {
+        ColumnKind::Pillar => {
+            // Grow from whichever border leaves the taller wall
+            let (position, size) = if top - gap_top > gap_bottom - bottom {
+                wall(gap_top, top)
+            } else {
+                wall(bottom, gap_bottom)
+            };
+            spawn_obstacle(
+                &mut commands,
+                wall_platform(),
+                ObstacleKind::Column(kind),
+                position,
+                size,
+                None,
+                None,
+            );
+        }
+        ColumnKind::Gap | ColumnKind::Barrier => {
+            let barrier = (kind == ColumnKind::Barrier)
+                .then(|| CrystalColor::random_weighted(rng, &map_config.spawn_weights));
+            for (from, to) in [(gap_top, top), (bottom, gap_bottom)] {
+                let (position, size) = wall(from, to);
+                spawn_obstacle(
+                    &mut commands,
+                    wall_platform(),
+                    ObstacleKind::Column(kind),
+                    position,
+                    size,
+                    None,
+                    barrier,
+                );
+            }
+        }
+        ColumnKind::Platform | ColumnKind::MovingPlatform => {
+            let amplitude = match kind {
+                ColumnKind::MovingPlatform => config.moving_amplitude,
+                _ => 0.,
+            };
+            let clearance = config.platform_height / 2. + amplitude;
+            // Ledges go on the roomier side of the route, their whole range of motion clear of it
+            let (low, high) = if top - gap_top > gap_bottom - bottom {
+                (gap_top + clearance, top - clearance)
+            } else {
+                (bottom + clearance, gap_bottom - clearance)
+            };
+            if low > high {
+                return;
+            }
+
+            let width = rng.gen_range(3.0..=6.0);
+            // Still ledges come in every variety, moving ones are kept plain
+            let platform = if amplitude > 0. {
+                wall_platform()
+            } else {
+                random_ledge(rng)
+            };
+            let position = Vec2::new(x + width / 2., rng.gen_range(low..=high));
+            let oscillate = (amplitude > 0.).then(|| Oscillate {
+                amplitude,
+                frequency: config.moving_frequency,
+                start: clock.elapsed,
+            });
+            spawn_obstacle(
+                &mut commands,
+                platform,
+                ObstacleKind::Column(kind),
+                position,
+                Vec2::new(width, config.platform_height),
+                oscillate,
+                None,
+            );
+        }
+    }
+}
+
+fn wall_platform() -> PlatformBundle {
+    PlatformBundle::default().with_color(OBSTACLE_COLOR)
+}
+
+fn random_ledge<R: Rng + ?Sized>(rng: &mut R) -> PlatformBundle {
+    match rng.gen_range(0..5) {
+        0 => wall_platform(),
+        1 => PlatformBundle::default()
+            .with_color(BOUNCY_COLOR)
+            .with_restitution(1.2),
+        2 => PlatformBundle::default()
+            .with_color(ONE_WAY_COLOR)
+            .with_kind(PlatformKind::OneWay),
+        3 => PlatformBundle::default()
+            .with_color(CRUMBLING_COLOR)
+            .with_kind(PlatformKind::Crumbling { delay: 0.75 }),
+        _ => {
+            let speed = if rng.gen() { 4. } else { -4. };
+            PlatformBundle::default()
+                .with_color(CONVEYOR_COLOR)
+                .with_kind(PlatformKind::Conveyor { speed })
+        }
+    }
+}
+
+/// Obstacles scroll left with the crystals, and are removed once they're off the map.
+fn obstacle_treadmill(
+    mut commands: Commands,
+    mut obstacles: Query<(
+        Entity,
+        &Transform,
+        &mut Velocity,
+        &Obstacle,
+        Option<&Oscillate>,
+    )>,
+    clock: Res<GameClock>,
+    map_config: Res<MapConfig>,
+) {
+    let hmap_size = map_config.half_size();
+
+    for (entity, transform, mut velocity, obstacle, oscillate) in &mut obstacles {
+        if transform.translation.x + obstacle.size.x / 2. < -hmap_size.x {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let vertical = oscillate.map_or(0., |oscillate| {
+            let omega = oscillate.frequency * std::f32::consts::TAU;
+            let t = (clock.elapsed - oscillate.start) as f32;
+            oscillate.amplitude * omega * (omega * t).cos()
+        });
+        velocity.linvel = Vec2::new(-map_config.crystal_linvel, vertical);
+    }
+}
+
+/// Running into the side of an obstacle, or touching a damaging platform anywhere,
+/// knocks the color out of the player. Landing on an obstacle or bumping its underside is harmless.
+fn obstacle_collision(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut players: Query<(&Transform, &mut Player), Without<Platform>>,
+    platforms: Query<(&Transform, &Platform, Option<&Obstacle>), Without<Player>>,
+    mut hit_events: EventWriter<ObstacleHit>,
+    mut color_changed_events: EventWriter<ColorChanged>,
+) {
+    for event in collision_events.iter() {
+        if let CollisionEvent::Started(entity_a, entity_b, _) = &event {
+            let (player_entity, platform_entity) = if players.contains(*entity_a) {
+                (*entity_a, *entity_b)
+            } else if players.contains(*entity_b) {
+                (*entity_b, *entity_a)
+            } else {
+                continue;
+            };
+            let (platform_transform, platform, obstacle) = match platforms.get(platform_entity) {
+                Ok(platform) => platform,
+                Err(_) => continue,
+            };
+            let (player_transform, mut player) = match players.get_mut(player_entity) {
+                Ok(player) => player,
+                Err(_) => continue,
+            };
+
+            let offset = player_transform.translation.xy() - platform_transform.translation.xy();
+            let hit = match platform.kind {
+                PlatformKind::Damaging => true,
+                // Passing up through them is the point of one-way platforms
+                PlatformKind::OneWay => false,
+                _ => obstacle.is_some_and(|obstacle| offset.y.abs() <= obstacle.size.y / 2.),
+            };
+            if !hit {
+                continue;
+            }
+
+            hit_events.send(ObstacleHit {
+                player: player_entity,
+                obstacle: platform_entity,
+            });
+            if player.color.is_some() {
+                color_changed_events.send(ColorChanged {
+                    player: player_entity,
+                    from: player.color,
+                    to: None,
+                });
+                player.color = None;
+            }
+        }
+    }
+}
+
+fn half_height(collider: &Collider) -> f32 {
+    collider
+        .as_cuboid()
+        .map_or(0., |cuboid| cuboid.half_extents().y)
+}
+
+/// One-way platforms only hold up players that are above them.
+fn one_way_platforms(
+    players: Query<(&Transform, &Collider), With<Player>>,
+    mut platforms: Query<(&Transform, &Collider, &Platform, &mut SolverGroups), Without<Player>>,
+) {
+    for (transform, collider, platform, mut solver_groups) in &mut platforms {
+        if platform.kind != PlatformKind::OneWay {
+            continue;
+        }
+
+        let top = transform.translation.y + half_height(collider);
+        let holds = players.iter().any(|(player_transform, player_collider)| {
+            // A little slack so a player resting on top doesn't sink through
+            player_transform.translation.y - half_height(player_collider) >= top - 0.1
+        });
+        let filters = if holds { u32::MAX } else { !PLAYER_GROUP };
+        if solver_groups.filters != filters {
+            solver_groups.filters = filters;
+        }
+    }
+}
+
+/// Crumbling platforms start falling apart when first touched, fading out until they're gone.
+fn crumble_platforms(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    players: Query<(), With<Player>>,
+    mut platforms: Query<(Entity, &Platform, Option<&Crumble>, &mut Sprite)>,
+    clock: Res<GameClock>,
+) {
+    for event in collision_events.iter() {
+        if let CollisionEvent::Started(entity_a, entity_b, _) = &event {
+            let platform_entity = if players.contains(*entity_a) {
+                *entity_b
+            } else if players.contains(*entity_b) {
+                *entity_a
+            } else {
+                continue;
+            };
+
+            if let Ok((entity, platform, None, _)) = platforms.get(platform_entity) {
+                if let PlatformKind::Crumbling { delay } = platform.kind {
+                    commands.entity(entity).insert(Crumble {
+                        triggered_at: clock.elapsed,
+                        despawn_at: clock.elapsed + delay as f64,
+                    });
+                }
+            }
+        }
+    }
+
+    for (entity, _, crumble, mut sprite) in &mut platforms {
+        if let Some(crumble) = crumble {
+            if clock.elapsed >= crumble.despawn_at {
+                commands.entity(entity).despawn_recursive();
+            } else {
+                let progress = (clock.elapsed - crumble.triggered_at)
+                    / (crumble.despawn_at - crumble.triggered_at);
+                sprite.color.set_a(1. - progress as f32);
+            }
+        }
+    }
+}
+
+/// Conveyors carry along a player standing on them, only a little way while flapping as the
+/// player drifts back home. They add to the velocity after `move_player` has set it afresh for
+/// the step, so the speed can't pile up and the push still goes through collision resolution.
+fn conveyor_platforms(
+    rapier_context: Res<RapierContext>,
+    mut players: Query<(Entity, &Transform, &mut Velocity), With<Player>>,
+    platforms: Query<(Entity, &Transform, &Platform), Without<Player>>,
+) {
+    for (player_entity, player_transform, mut velocity) in &mut players {
+        for (platform_entity, platform_transform, platform) in &platforms {
+            if let PlatformKind::Conveyor { speed } = platform.kind {
+                let touching = rapier_context
+                    .contact_pair(player_entity, platform_entity)
+                    .is_some_and(|pair| pair.has_any_active_contacts());
+                if touching && player_transform.translation.y > platform_transform.translation.y {
+                    velocity.linvel.x += speed;
+                }
+            }
+        }
+    }
+}
+
+/// Whether a player of `color` passes through barriers of `barrier` color.
+pub fn can_pass(color: Option<CrystalColor>, barrier: CrystalColor, tolerance: i8) -> bool {
+    color.is_some_and(|color| color.steps_to(&barrier).abs() <= tolerance)
+}
+
+/// Keeps each player's collision filter in step with their color, so barriers they can
+/// pass through neither push them nor report collisions.
+fn update_player_groups(
+    mut players: Query<(&Player, &mut CollisionGroups, &mut SolverGroups)>,
+    map_config: Res<MapConfig>,
+) {
+    let tolerance = map_config.obstacles.barrier_tolerance;
+
+    for (player, mut collision_groups, mut solver_groups) in &mut players {
+        let passable = CrystalColor::iter()
+            .filter(|barrier| can_pass(player.color, *barrier, tolerance))
+            .fold(0, |groups, barrier| groups | barrier_group(barrier));
+        let filters = !passable;
+
+        // Only write on change, so rapier doesn't resync the collider every step
+        if collision_groups.filters != filters {
+            collision_groups.filters = filters;
+        }
+        if solver_groups.filters != filters {
+            solver_groups.filters = filters;
+        }
+    }
+}
+
+/// Barriers fade out when the player is able to pass through them.
+fn barrier_tint(
+    players: Query<&Player>,
+    mut barriers: Query<(&Barrier, &mut Sprite)>,
+    map_config: Res<MapConfig>,
+) {
+    let color = players.iter().next().and_then(|player| player.color);
+    let tolerance = map_config.obstacles.barrier_tolerance;
+
+    for (barrier, mut sprite) in &mut barriers {
+        let alpha = if can_pass(color, barrier.0, tolerance) {
+            0.25
+        } else {
+            0.9
+        };
+        if sprite.color.a() != alpha {
+            sprite.color.set_a(alpha);
+        }
+    }
+}
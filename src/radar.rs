@@ -0,0 +1,423 @@
+//! Help finding crystals the camera can't see: arrows at the edge of the screen pointing at
+//! crystals that would bring the player closer to the target, and an optional minimap of
+//! the whole map. Both hang off the `HudCamera` and are sized in logical pixels like the HUD.
+
+use std::marker::PhantomData;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_prototype_lyon::prelude::*;
+use strum::IntoEnumIterator;
+
+use crate::{
+    bump::{CurrentColor, GameMode, MapConfig, TargetColor},
+    camera::{hud_layer, CameraFollow, GameCamera, HudCamera},
+    core::{
+        crystal::{Crystal, CrystalColor},
+        player::Player,
+    },
+    hud::window_size,
+};
+
+/// Most crystals pointed at at once, nearest first.
+const MAX_ARROWS: usize = 6;
+/// How far in front of the HUD camera the arrows are drawn, just behind the colorwheel.
+const ARROW_DEPTH: f32 = -12.;
+const MINIMAP_DEPTH: f32 = -14.;
+/// Radius of a crystal's dot on the minimap, in map units.
+const MINIMAP_DOT: f32 = 0.5;
+/// Radius of the dots for crystals that help, which stand out from the rest.
+const MINIMAP_HELPFUL_DOT: f32 = 0.9;
+
+/// Whether the minimap is shown. Off unless turned on in the menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Minimap(pub bool);
+
+/// Where an arrow goes for a crystal at `relative` to the middle of a view `half_view` across,
+/// kept `inset` inside its edges. `None` if the crystal is in view and needs no arrow.
+pub fn edge_arrow(relative: Vec2, half_view: Vec2, inset: f32) -> Option<Vec2> {
+    if relative.x.abs() <= half_view.x && relative.y.abs() <= half_view.y {
+        return None;
+    }
+
+    // Pull it straight back toward the middle until it's inside the inset edges
+    let bounds = (half_view - Vec2::splat(inset)).max(Vec2::ZERO);
+    let scale = (bounds.x / relative.x.abs()).min(bounds.y / relative.y.abs());
+    Some(relative * scale)
+}
+
+/// How many world units one logical pixel of the game camera's view spans.
+fn world_per_pixel(projection: &OrthographicProjection, window_size: Vec2) -> f32 {
+    (projection.top - projection.bottom) * projection.scale / window_size.y
+}
+
+/// One of a fixed set of arrows, reused each frame for whichever crystals need them.
+#[derive(Component, Default)]
+struct OffscreenArrow {
+    /// The color it's drawn in, so it's only redrawn when that changes.
+    color: Option<CrystalColor>,
+}
+/// Drawn in map units, `minimap_layout` scales it to `HudConfig::minimap_width` pixels.
+#[derive(Component)]
+struct MinimapRoot;
+/// Anything drawn on the minimap, shown and hidden together.
+#[derive(Component)]
+struct MinimapPart;
+/// The dots of every crystal of one color, as a single shape.
+#[derive(Component)]
+struct MinimapDots(CrystalColor);
+/// The map with its border platforms.
+#[derive(Component)]
+struct MinimapMap;
+#[derive(Component)]
+struct MinimapPlayer;
+/// Outline of what the camera sees.
+#[derive(Component)]
+struct MinimapView;
+
+pub struct RadarPlugin;
+
+impl Plugin for RadarPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Minimap>()
+            // Like the colorwheel, these hang off the HUD camera, which is only there after `Startup`
+            .add_startup_system_to_stage(StartupStage::PostStartup, startup_radar)
+            .add_system(offscreen_arrows.after(CameraFollow))
+            .add_system(minimap_layout)
+            .add_system(minimap_map_update)
+            .add_system(minimap_update)
+            .add_system(minimap_view_update.after(CameraFollow))
+            .add_system(minimap_visibility);
+    }
+}
+
+fn startup_radar(
+    mut commands: Commands,
+    cameras: Query<Entity, With<HudCamera>>,
+    map_config: Res<MapConfig>,
+) {
+    let mut entities = Vec::new();
+
+    // Drawn a unit long pointing right, `offscreen_arrows` turns and sizes them
+    for _ in 0..MAX_ARROWS {
+        let arrow = commands
+            .spawn_bundle(GeometryBuilder::build_as(
+                &shapes::Polygon {
+                    points: vec![
+                        Vec2::new(0.5, 0.),
+                        Vec2::new(-0.5, 0.4),
+                        Vec2::new(-0.3, 0.),
+                        Vec2::new(-0.5, -0.4),
+                    ],
+                    closed: true,
+                },
+                DrawMode::Outlined {
+                    fill_mode: bevy_prototype_lyon::prelude::FillMode::color(Color::WHITE),
+                    outline_mode: StrokeMode::new(Color::WHITE, 0.1),
+                },
+                Transform::from_xyz(0., 0., ARROW_DEPTH),
+            ))
+            .insert(Visibility { is_visible: false })
+            .insert(OffscreenArrow::default())
+            .insert(hud_layer())
+            .id();
+        entities.push(arrow);
+    }
+
+    let minimap = commands
+        .spawn()
+        .insert(MinimapRoot)
+        .insert(Transform::from_xyz(0., 0., MINIMAP_DEPTH))
+        .insert(GlobalTransform::default())
+        .insert(Visibility::default())
+        .insert(ComputedVisibility::default())
+        .insert(hud_layer())
+        .with_children(|parent| {
+            // The map with its border platforms
+            parent
+                .spawn_bundle(GeometryBuilder::build_as(
+                    &shapes::Rectangle {
+                        extents: map_config.map_size + Vec2::ONE,
+                        origin: shapes::RectangleOrigin::Center,
+                    },
+                    DrawMode::Outlined {
+                        fill_mode: bevy_prototype_lyon::prelude::FillMode::color(Color::rgba(
+                            0., 0., 0., 0.6,
+                        )),
+                        outline_mode: StrokeMode::new(Color::rgba(1., 1., 1., 0.5), 0.3),
+                    },
+                    Transform::from_xyz(0., 0., 0.),
+                ))
+                .insert(Visibility { is_visible: false })
+                .insert(MinimapPart)
+                .insert(MinimapMap)
+                .insert(hud_layer());
+
+            for color in CrystalColor::iter() {
+                parent
+                    .spawn_bundle(GeometryBuilder::new().build(
+                        DrawMode::Fill(bevy_prototype_lyon::prelude::FillMode::color(
+                            color.to_color(),
+                        )),
+                        Transform::from_xyz(0., 0., 1.),
+                    ))
+                    .insert(Visibility { is_visible: false })
+                    .insert(MinimapPart)
+                    .insert(MinimapDots(color))
+                    .insert(hud_layer());
+            }
+
+            parent
+                .spawn_bundle(GeometryBuilder::build_as(
+                    &shapes::Rectangle {
+                        extents: Vec2::ONE,
+                        origin: shapes::RectangleOrigin::Center,
+                    },
+                    DrawMode::Stroke(StrokeMode::new(Color::rgba(1., 1., 1., 0.7), 0.25)),
+                    Transform::from_xyz(0., 0., 2.),
+                ))
+                .insert(Visibility { is_visible: false })
+                .insert(MinimapPart)
+                .insert(MinimapView)
+                .insert(hud_layer());
+
+            parent
+                .spawn_bundle(GeometryBuilder::build_as(
+                    &shapes::Circle {
+                        radius: 0.9,
+                        ..default()
+                    },
+                    DrawMode::Outlined {
+                        fill_mode: bevy_prototype_lyon::prelude::FillMode::color(Color::WHITE),
+                        outline_mode: StrokeMode::new(Color::BLACK, 0.3),
+                    },
+                    Transform::from_xyz(0., 0., 3.),
+                ))
+                .insert(Visibility { is_visible: false })
+                .insert(MinimapPart)
+                .insert(MinimapPlayer)
+                .insert(hud_layer());
+        })
+        .id();
+    entities.push(minimap);
+
+    for camera in &cameras {
+        commands.entity(camera).push_children(&entities);
+    }
+}
+
+/// Decides which crystals would bring the player closer to the target.
+#[derive(SystemParam)]
+pub(crate) struct HelpfulColors<'w, 's> {
+    map_config: Res<'w, MapConfig>,
+    current_color: Res<'w, CurrentColor>,
+    target_color: Res<'w, TargetColor>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+impl HelpfulColors<'_, '_> {
+    pub(crate) fn helps(&self, color: CrystalColor) -> bool {
+        self.map_config
+            .combine_rule
+            .progress(self.current_color.0, color, self.target_color.0)
+            > 0
+    }
+}
+
+/// Points at the nearest crystals that would bring the player closer to the target,
+/// from the edge of the screen they're past.
+#[allow(clippy::type_complexity)]
+fn offscreen_arrows(
+    mut arrows: Query<(
+        &mut OffscreenArrow,
+        &mut Transform,
+        &mut DrawMode,
+        &mut Visibility,
+    )>,
+    cameras: Query<
+        (&Transform, &OrthographicProjection),
+        (With<GameCamera>, Without<OffscreenArrow>),
+    >,
+    crystals: Query<(&Transform, &Crystal), Without<OffscreenArrow>>,
+    windows: Res<Windows>,
+    map_config: Res<MapConfig>,
+    helpful: HelpfulColors,
+    game_mode: Res<GameMode>,
+) {
+    let (camera, projection) = match cameras.get_single() {
+        Ok(camera) => camera,
+        Err(_) => return,
+    };
+    let window_size = match window_size(&windows) {
+        Some(window_size) => window_size,
+        None => return,
+    };
+    // Crystals are found in the game camera's view, the arrows drawn in the HUD's pixels
+    let pixel = world_per_pixel(projection, window_size);
+    let half_view = window_size / 2.;
+    let hud_config = &map_config.hud;
+    let size = hud_config.arrow_size * hud_config.scale;
+    let inset = hud_config.margin() + size;
+    let camera_position = camera.translation.truncate();
+
+    // The editor places crystals by hand and has no use for being pointed at them
+    let mut targets: Vec<_> = match *game_mode {
+        GameMode::Editing => Vec::new(),
+        _ => crystals
+            .iter()
+            .filter(|(_, crystal)| !crystal.collected && helpful.helps(crystal.crystal_color))
+            .filter_map(|(transform, crystal)| {
+                let relative = (transform.translation.truncate() - camera_position) / pixel;
+                edge_arrow(relative, half_view, inset)
+                    .map(|position| (relative.length_squared(), position, crystal.crystal_color))
+            })
+            .collect(),
+    };
+    targets.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut targets = targets.into_iter();
+    for (mut arrow, mut transform, mut draw_mode, mut visibility) in &mut arrows {
+        let (position, color) = match targets.next() {
+            Some((_, position, color)) => (position, color),
+            None => {
+                visibility.is_visible = false;
+                continue;
+            }
+        };
+
+        transform.translation = position.extend(ARROW_DEPTH);
+        transform.rotation = Quat::from_rotation_z(position.y.atan2(position.x));
+        transform.scale = Vec3::new(size, size, 1.);
+        if arrow.color != Some(color) {
+            *draw_mode = DrawMode::Outlined {
+                fill_mode: bevy_prototype_lyon::prelude::FillMode::color(color.to_color()),
+                outline_mode: StrokeMode::new(Color::WHITE, 0.1),
+            };
+            arrow.color = Some(color);
+        }
+        visibility.is_visible = true;
+    }
+}
+
+/// Keeps the minimap centered along the bottom of the window, whatever its size.
+fn minimap_layout(
+    mut minimaps: Query<&mut Transform, With<MinimapRoot>>,
+    windows: Res<Windows>,
+    map_config: Res<MapConfig>,
+) {
+    let window_size = match window_size(&windows) {
+        Some(window_size) => window_size,
+        None => return,
+    };
+
+    // Pixels per map unit
+    let hud_config = &map_config.hud;
+    let map_extent = map_config.map_size + Vec2::ONE;
+    let unit = hud_config.minimap_width * hud_config.scale / map_extent.x;
+    let center_y = -window_size.y / 2. + hud_config.margin() + map_extent.y * unit / 2.;
+    for mut transform in &mut minimaps {
+        transform.translation = Vec2::new(0., center_y).extend(MINIMAP_DEPTH);
+        transform.scale = Vec3::new(unit, unit, 1.);
+    }
+}
+
+/// Redraws the map when it changes size.
+fn minimap_map_update(mut maps: Query<&mut Path, With<MinimapMap>>, map_config: Res<MapConfig>) {
+    if !map_config.is_changed() {
+        return;
+    }
+
+    for mut path in &mut maps {
+        *path = ShapePath::build_as(&shapes::Rectangle {
+            extents: map_config.map_size + Vec2::ONE,
+            origin: shapes::RectangleOrigin::Center,
+        });
+    }
+}
+
+/// Redraws the crystals on the minimap.
+fn minimap_update(
+    mut dots: Query<(&MinimapDots, &mut Path)>,
+    crystals: Query<(&Transform, &Crystal), Without<MinimapPart>>,
+    helpful: HelpfulColors,
+    minimap: Res<Minimap>,
+) {
+    if !minimap.0 {
+        return;
+    }
+
+    let mut positions = vec![Vec::new(); CrystalColor::iter().count()];
+    for (transform, crystal) in &crystals {
+        if !crystal.collected {
+            positions[crystal.crystal_color as usize].push(transform.translation.truncate());
+        }
+    }
+
+    for (dots, mut path) in &mut dots {
+        let radius = match helpful.helps(dots.0) {
+            true => MINIMAP_HELPFUL_DOT,
+            false => MINIMAP_DOT,
+        };
+        let mut shape = ShapePath::new();
+        for center in &positions[dots.0 as usize] {
+            shape = shape.add(&shapes::Circle {
+                radius,
+                center: *center,
+            });
+        }
+        *path = shape.build();
+    }
+}
+
+/// Moves the player and the camera's view on the minimap.
+#[allow(clippy::type_complexity)]
+fn minimap_view_update(
+    mut player_dots: Query<
+        &mut Transform,
+        (With<MinimapPlayer>, With<MinimapPart>, Without<MinimapView>),
+    >,
+    mut views: Query<(&mut Transform, &mut Path), (With<MinimapView>, With<MinimapPart>)>,
+    players: Query<&Transform, (With<Player>, Without<MinimapPart>)>,
+    cameras: Query<(&Transform, &OrthographicProjection), (With<GameCamera>, Without<MinimapPart>)>,
+    minimap: Res<Minimap>,
+) {
+    if !minimap.0 {
+        return;
+    }
+
+    if let Some(player) = players.iter().next() {
+        for mut transform in &mut player_dots {
+            transform.translation = player.translation.truncate().extend(3.);
+        }
+    }
+
+    if let Ok((camera, projection)) = cameras.get_single() {
+        let view = Vec2::new(
+            projection.right - projection.left,
+            projection.top - projection.bottom,
+        ) * projection.scale;
+        for (mut transform, mut path) in &mut views {
+            transform.translation = camera.translation.truncate().extend(2.);
+            *path = ShapePath::build_as(&shapes::Rectangle {
+                extents: view,
+                origin: shapes::RectangleOrigin::Center,
+            });
+        }
+    }
+}
+
+/// Shows the minimap when it's turned on, except in the editor where it would cover the level.
+fn minimap_visibility(
+    mut parts: Query<&mut Visibility, With<MinimapPart>>,
+    minimap: Res<Minimap>,
+    game_mode: Res<GameMode>,
+) {
+    if !minimap.is_changed() && !game_mode.is_changed() {
+        return;
+    }
+
+    let visible = minimap.0 && *game_mode != GameMode::Editing;
+    for mut visibility in &mut parts {
+        visibility.is_visible = visible;
+    }
+}
@@ -0,0 +1,338 @@
+//! Procedural sound. The colorwheel's twelve wedges are the twelve semitones of an octave:
+//! collecting a crystal plucks its note, reaching the target plays an arpeggio on the
+//! target's note, and a low drone follows the player's color. Everything is synthesized
+//! into sample buffers at startup, so there are no sound assets to load.
+
+use bevy::{audio::AudioSink, prelude::*};
+use strum::IntoEnumIterator;
+
+use crate::{
+    bump::{CurrentColor, GameMode},
+    core::{
+        crystal::CrystalColor,
+        event::{CrystalCollected, TargetReached},
+    },
+    tween::Tween,
+};
+
+pub const SAMPLE_RATE: u32 = 44_100;
+/// Orange's note, the A below middle C.
+const BASE_PITCH: f32 = 220.;
+const TONE_TIME: f32 = 0.35;
+/// Seconds each note of the target arpeggio rings for, and between the start of one and the next.
+const CHORD_NOTE_TIME: f32 = 0.8;
+const CHORD_STAGGER: f32 = 0.09;
+/// Length of the drone's loop. Its notes are tuned so they fit it exactly and loop without a click.
+const DRONE_LOOP_TIME: f32 = 4.;
+/// Seconds one drone takes to fade into the next when the player's color changes.
+const DRONE_FADE_TIME: f32 = 0.8;
+/// Seconds a tone takes to reach full volume, short enough to sound plucked without clicking.
+const ATTACK_TIME: f32 = 0.005;
+
+/// The frequency of `color`'s note, shifted by whole octaves.
+pub fn pitch(color: CrystalColor, octave: i32) -> f32 {
+    BASE_PITCH * 2f32.powf(color as usize as f32 / 12. + octave as f32)
+}
+
+/// Loudness of a plucked note `t` seconds in: a quick attack that decays away to silence
+/// by `duration`.
+fn pluck_envelope(t: f32, duration: f32) -> f32 {
+    (t / ATTACK_TIME).min(1.) * (-5. * t / duration).exp() * (1. - t / duration)
+}
+
+/// A sine with a little of the notes above mixed in so it isn't bare, between -1 and 1.
+fn rich_wave(phase: f32) -> f32 {
+    (phase.sin() + 0.3 * (2. * phase).sin() + 0.1 * (3. * phase).sin()) / 1.4
+}
+
+/// A plucked note.
+pub fn tone(frequency: f32, duration: f32) -> Vec<f32> {
+    glide(frequency, frequency, duration)
+}
+
+/// A plucked note that slides from one frequency to another, evenly in pitch.
+pub fn glide(from: f32, to: f32, duration: f32) -> Vec<f32> {
+    let count = (duration * SAMPLE_RATE as f32) as usize;
+    let mut phase = 0f32;
+    (0..count)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let value = 0.5 * pluck_envelope(t, duration) * rich_wave(phase);
+            let frequency = from * (to / from).powf(t / duration);
+            phase = (phase + 2. * std::f32::consts::PI * frequency / SAMPLE_RATE as f32)
+                % (2. * std::f32::consts::PI);
+            value
+        })
+        .collect()
+}
+
+/// `parts` one after another, each starting `gap` seconds after the last one ends.
+pub fn sequence(parts: &[Vec<f32>], gap: f32) -> Vec<f32> {
+    let gap = vec![0.; (gap * SAMPLE_RATE as f32) as usize];
+    let mut samples = Vec::new();
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            samples.extend_from_slice(&gap);
+        }
+        samples.extend_from_slice(part);
+    }
+    samples
+}
+
+/// `frequencies` played one after another, `stagger` seconds apart, each ringing for `duration`.
+pub fn arpeggio(frequencies: &[f32], duration: f32, stagger: f32) -> Vec<f32> {
+    let offset = (stagger * SAMPLE_RATE as f32) as usize;
+    let count = (duration * SAMPLE_RATE as f32) as usize + offset * (frequencies.len().max(1) - 1);
+    let mut samples = vec![0.; count];
+    // Quieter per note the more there are, so they don't clip when they ring together
+    let gain = 1. / (frequencies.len().max(1) as f32).sqrt();
+
+    for (i, frequency) in frequencies.iter().enumerate() {
+        for (sample, value) in samples[i * offset..]
+            .iter_mut()
+            .zip(tone(*frequency, duration))
+        {
+            *sample += value * gain;
+        }
+    }
+    for sample in &mut samples {
+        *sample = sample.clamp(-1., 1.);
+    }
+    samples
+}
+
+/// A soft hum on `frequency` and its fifth that can be looped seamlessly.
+/// The notes are nudged to the nearest frequencies that fit `duration` a whole number of times.
+pub fn drone(frequency: f32, duration: f32) -> Vec<f32> {
+    let count = (duration * SAMPLE_RATE as f32) as usize;
+    let duration = count as f32 / SAMPLE_RATE as f32;
+    // An even number of cycles, so the fifth's cycles come out whole too
+    let cycles = ((frequency * duration / 2.).round() * 2.).max(2.);
+    let root = cycles / duration;
+    let fifth = root * 1.5;
+
+    (0..count)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let tau = 2. * std::f32::consts::PI;
+            // Swells twice a loop
+            let swell = 0.8 + 0.2 * (tau * 2. * t / duration).sin();
+            let wave = (tau * root * t).sin()
+                + 0.5 * (tau * fifth * t).sin()
+                + 0.25 * (tau * 2. * root * t).sin();
+            0.25 * swell * wave / 1.75
+        })
+        .collect()
+}
+
+/// Places mono samples in the stereo field, from -1 hard left to 1 hard right,
+/// sounding as loud wherever they are.
+pub fn pan(samples: &[f32], position: f32) -> Vec<[f32; 2]> {
+    let angle = (position.clamp(-1., 1.) + 1.) * std::f32::consts::FRAC_PI_4;
+    let (left, right) = (angle.cos(), angle.sin());
+    samples
+        .iter()
+        .map(|sample| [sample * left, sample * right])
+        .collect()
+}
+
+/// Packs mono samples between -1 and 1 into a 16 bit PCM WAV file.
+pub fn wav(samples: &[f32]) -> Vec<u8> {
+    pcm_wav(1, samples)
+}
+
+/// Packs stereo frames of samples between -1 and 1 into a 16 bit PCM WAV file.
+pub fn stereo_wav(frames: &[[f32; 2]]) -> Vec<u8> {
+    pcm_wav(2, frames.concat().as_slice())
+}
+
+/// `samples` holds `channels` interleaved channels.
+fn pcm_wav(channels: u16, samples: &[f32]) -> Vec<u8> {
+    let data_size = samples.len() as u32 * 2;
+    let block_size = channels * 2;
+    let mut bytes = Vec::with_capacity(44 + data_size as usize);
+
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&channels.to_le_bytes());
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * block_size as u32).to_le_bytes());
+    bytes.extend_from_slice(&block_size.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        let value = (sample.clamp(-1., 1.) * i16::MAX as f32) as i16;
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes
+}
+
+/// Volumes between 0 and 1, set on the title screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoundSettings {
+    pub sfx_volume: f32,
+    pub music_volume: f32,
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        SoundSettings {
+            sfx_volume: 0.8,
+            music_volume: 0.5,
+        }
+    }
+}
+
+/// The synthesized sounds, indexed by `CrystalColor`.
+struct Sounds {
+    tones: Vec<Handle<AudioSource>>,
+    chords: Vec<Handle<AudioSource>>,
+    drones: Vec<Handle<AudioSource>>,
+}
+
+/// A drone that is playing, fading in or out.
+struct DroneVoice {
+    color: CrystalColor,
+    sink: Handle<AudioSink>,
+    volume: Tween<f32>,
+}
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoundSettings>()
+            .add_startup_system(startup_sounds)
+            .add_system(collection_sound)
+            .add_system(target_sound)
+            .add_system(update_drone);
+    }
+}
+
+fn startup_sounds(mut commands: Commands, mut audio_sources: ResMut<Assets<AudioSource>>) {
+    let mut add = |samples: Vec<f32>| {
+        audio_sources.add(AudioSource {
+            bytes: wav(&samples).into(),
+        })
+    };
+
+    let mut sounds = Sounds {
+        tones: Vec::new(),
+        chords: Vec::new(),
+        drones: Vec::new(),
+    };
+    for color in CrystalColor::iter() {
+        let root = pitch(color, 0);
+        sounds.tones.push(add(tone(root, TONE_TIME)));
+        // A major arpeggio up to the octave
+        let notes = [0., 4., 7., 12.].map(|semitones| root * 2f32.powf(semitones / 12.));
+        sounds
+            .chords
+            .push(add(arpeggio(&notes, CHORD_NOTE_TIME, CHORD_STAGGER)));
+        sounds
+            .drones
+            .push(add(drone(pitch(color, -2), DRONE_LOOP_TIME)));
+    }
+    commands.insert_resource(sounds);
+}
+
+/// Sound effects are for the player, the bot playing behind the menus stays quiet.
+pub(crate) fn sfx_audible(game_mode: GameMode) -> bool {
+    matches!(game_mode, GameMode::Playing | GameMode::Testing)
+}
+
+fn collection_sound(
+    mut collected_events: EventReader<CrystalCollected>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    settings: Res<SoundSettings>,
+    game_mode: Res<GameMode>,
+) {
+    for event in collected_events.iter() {
+        if sfx_audible(*game_mode) {
+            audio.play_with_settings(
+                sounds.tones[event.crystal_color as usize].clone(),
+                PlaybackSettings::ONCE.with_volume(settings.sfx_volume),
+            );
+        }
+    }
+}
+
+fn target_sound(
+    mut target_reached_events: EventReader<TargetReached>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    settings: Res<SoundSettings>,
+    game_mode: Res<GameMode>,
+) {
+    for event in target_reached_events.iter() {
+        if sfx_audible(*game_mode) {
+            audio.play_with_settings(
+                sounds.chords[event.color as usize].clone(),
+                PlaybackSettings::ONCE.with_volume(settings.sfx_volume),
+            );
+        }
+    }
+}
+
+/// Crossfades the drone to the player's color whenever it changes, and silences it
+/// while the player has none.
+fn update_drone(
+    mut voices: Local<Vec<DroneVoice>>,
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    sounds: Res<Sounds>,
+    settings: Res<SoundSettings>,
+    current_color: Res<CurrentColor>,
+    time: Res<Time>,
+) {
+    let now = time.seconds_since_startup();
+
+    // The voice that isn't fading out, if any
+    let playing = voices
+        .iter()
+        .find(|voice| voice.volume.to > 0.)
+        .map(|voice| voice.color);
+    if playing != current_color.0 {
+        for voice in voices.iter_mut() {
+            if voice.volume.to > 0. {
+                voice.volume.retarget(0., now);
+            }
+        }
+        if let Some(color) = current_color.0 {
+            let sink = audio.play_with_settings(
+                sounds.drones[color as usize].clone(),
+                PlaybackSettings::LOOP.with_volume(0.),
+            );
+            voices.push(DroneVoice {
+                color,
+                sink: audio_sinks.get_handle(sink),
+                volume: Tween::new(0., 1., now, DRONE_FADE_TIME),
+            });
+        }
+    }
+
+    // Sinks only show up once the audio output has started them
+    for voice in voices.iter() {
+        if let Some(sink) = audio_sinks.get(&voice.sink) {
+            sink.set_volume(voice.volume.value(now) * settings.music_volume);
+        }
+    }
+    voices.retain(|voice| {
+        let faded_out = voice.volume.to == 0. && voice.volume.is_finished(now);
+        if faded_out {
+            if let Some(sink) = audio_sinks.get(&voice.sink) {
+                sink.stop();
+            }
+        }
+        !faded_out
+    });
+}
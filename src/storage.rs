@@ -0,0 +1,59 @@
+//! Small text files that outlive the game, like campaign progress.
+//! Natively they go in the user's data directory, on the web in the browser's local storage.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+
+/// Reads what was last saved under `key`, if anything.
+pub fn load(game_slug: &str, key: &str) -> Option<String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::fs::read_to_string(file_path(game_slug, key)).ok()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        local_storage()?
+            .get_item(&storage_key(game_slug, key))
+            .ok()
+            .flatten()
+    }
+}
+
+pub fn save(game_slug: &str, key: &str, value: &str) -> anyhow::Result<()> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let path = file_path(game_slug, key);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, value)?;
+        Ok(())
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        local_storage()
+            .ok_or_else(|| anyhow::anyhow!("Local storage is unavailable"))?
+            .set_item(&storage_key(game_slug, key), value)
+            .map_err(|error| anyhow::anyhow!("Unable to write local storage: {:?}", error))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn file_path(game_slug: &str, key: &str) -> PathBuf {
+    let data_dir = std::env::var_os("APPDATA")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("XDG_DATA_HOME").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .unwrap_or_default();
+    data_dir.join(game_slug).join(format!("{}.ron", key))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn storage_key(game_slug: &str, key: &str) -> String {
+    format!("{}.{}", game_slug, key)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
@@ -0,0 +1,73 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    utils::BoxedFuture,
+};
+
+use crate::{bump::MapConfig, difficulty::BaseMapConfig};
+
+pub const DEFAULT_TUNING_PATH: &str = "tuning/default.tuning.ron";
+
+#[derive(Default)]
+pub struct MapConfigLoader;
+
+impl AssetLoader for MapConfigLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let map_config = ron::de::from_bytes::<MapConfig>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(map_config));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+struct TuningHandle(Handle<MapConfig>);
+
+/// Loads the `BaseMapConfig` resource from a tuning asset, and reapplies it whenever the file changes.
+/// Everything applies live, `BumpPlugin` and the other plugins follow changes to `MapConfig`.
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<MapConfig>()
+            .init_asset_loader::<MapConfigLoader>()
+            .add_startup_system(load_tuning)
+            .add_system(apply_tuning);
+    }
+}
+
+fn load_tuning(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TuningHandle(asset_server.load(DEFAULT_TUNING_PATH)));
+}
+
+fn apply_tuning(
+    mut asset_events: EventReader<AssetEvent<MapConfig>>,
+    tuning_handle: Option<Res<TuningHandle>>,
+    tunings: Res<Assets<MapConfig>>,
+    mut base_map_config: ResMut<BaseMapConfig>,
+) {
+    let tuning_handle = match tuning_handle {
+        Some(tuning_handle) => tuning_handle,
+        None => return,
+    };
+
+    for event in asset_events.iter() {
+        if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = event {
+            if *handle != tuning_handle.0 {
+                continue;
+            }
+            if let Some(tuning) = tunings.get(handle) {
+                info!("Applying tuning from {}", DEFAULT_TUNING_PATH);
+                *base_map_config = BaseMapConfig(tuning.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Small tweens for things that should change over a moment instead of instantly.
+//! A `Tween<T>` only knows how far along it is; `TweenPlugin` applies the ones on
+//! `Transform` and `DrawMode`, anything else samples its tween with `Tween::value`.
+
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+/// Values that can be blended between two endpoints.
+pub trait Lerp: Clone {
+    fn lerp(&self, to: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+/// Blends through the hues in between, the short way round the color circle.
+impl Lerp for Color {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        let from = self.as_hsla_f32();
+        let to = to.as_hsla_f32();
+        // A gray has no hue of its own, so it takes the other end's
+        let (from_hue, to_hue) = match (from[1] > 0., to[1] > 0.) {
+            (false, true) => (to[0], to[0]),
+            (true, false) => (from[0], from[0]),
+            _ => (from[0], to[0]),
+        };
+        let hue_delta = (to_hue - from_hue + 540.) % 360. - 180.;
+
+        Color::hsla(
+            (from_hue + hue_delta * t).rem_euclid(360.),
+            from[1].lerp(&to[1], t),
+            from[2].lerp(&to[2], t),
+            from[3].lerp(&to[3], t),
+        )
+    }
+}
+
+/// Rotations turn the short way round.
+impl Lerp for Transform {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        Transform {
+            translation: self.translation.lerp(to.translation, t),
+            rotation: self.rotation.slerp(to.rotation, t),
+            scale: self.scale.lerp(to.scale, t),
+        }
+    }
+}
+
+/// Blends the colors and line widths. Different kinds of draw mode can't be blended,
+/// so those switch over at the end.
+impl Lerp for DrawMode {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        let fill = |from: &FillMode, to: &FillMode| FillMode {
+            color: from.color.lerp(&to.color, t),
+            ..*to
+        };
+        let stroke = |from: &StrokeMode, to: &StrokeMode| {
+            let mut mode = *to;
+            mode.color = from.color.lerp(&to.color, t);
+            mode.options.line_width = from.options.line_width.lerp(&to.options.line_width, t);
+            mode
+        };
+
+        match (self, to) {
+            (DrawMode::Fill(from), DrawMode::Fill(to)) => DrawMode::Fill(fill(from, to)),
+            (DrawMode::Stroke(from), DrawMode::Stroke(to)) => DrawMode::Stroke(stroke(from, to)),
+            (
+                DrawMode::Outlined {
+                    fill_mode: from_fill,
+                    outline_mode: from_outline,
+                },
+                DrawMode::Outlined {
+                    fill_mode: to_fill,
+                    outline_mode: to_outline,
+                },
+            ) => DrawMode::Outlined {
+                fill_mode: fill(from_fill, to_fill),
+                outline_mode: stroke(from_outline, to_outline),
+            },
+            _ if t < 1. => *self,
+            _ => *to,
+        }
+    }
+}
+
+/// How progress through a tween speeds up and slows down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ease {
+    Linear,
+    /// Starts and ends gently.
+    SmoothStep,
+    /// Starts fast and settles into the end.
+    Out,
+}
+
+impl Ease {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        match self {
+            Ease::Linear => t,
+            Ease::SmoothStep => t * t * (3. - 2. * t),
+            Ease::Out => 1. - (1. - t).powi(3),
+        }
+    }
+}
+
+/// What a tween does once it reaches the end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    /// Stays at the end.
+    Once,
+    /// Goes back to the start and forth again, forever.
+    PingPong,
+}
+
+/// Blends from one value to another over `duration` seconds of `Time`,
+/// starting at `started_at` seconds since startup.
+#[derive(Component, Debug, Clone)]
+pub struct Tween<T: Lerp + Send + Sync + 'static> {
+    pub from: T,
+    pub to: T,
+    pub started_at: f64,
+    pub duration: f32,
+    pub ease: Ease,
+    pub repeat: Repeat,
+}
+
+impl<T: Lerp + Send + Sync + 'static> Tween<T> {
+    pub fn new(from: T, to: T, started_at: f64, duration: f32) -> Self {
+        Tween {
+            from,
+            to,
+            started_at,
+            duration,
+            ease: Ease::SmoothStep,
+            repeat: Repeat::Once,
+        }
+    }
+
+    /// A tween that is already at `value`, ready to be retargeted.
+    pub fn at(value: T, duration: f32) -> Self {
+        Tween::new(value.clone(), value, f64::NEG_INFINITY, duration)
+    }
+
+    pub fn with_ease(mut self, ease: Ease) -> Self {
+        self.ease = ease;
+        self
+    }
+
+    pub fn with_repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// How far between `from` and `to` the tween is at `now`, after easing.
+    pub fn progress(&self, now: f64) -> f32 {
+        if self.duration <= 0. {
+            return 1.;
+        }
+
+        let t = ((now - self.started_at) / self.duration as f64).max(0.);
+        let t = match self.repeat {
+            Repeat::Once => t.min(1.),
+            Repeat::PingPong => 1. - (t % 2. - 1.).abs(),
+        };
+        self.ease.apply(t as f32)
+    }
+
+    pub fn value(&self, now: f64) -> T {
+        self.from.lerp(&self.to, self.progress(now))
+    }
+
+    pub fn is_finished(&self, now: f64) -> bool {
+        self.repeat == Repeat::Once && now - self.started_at >= self.duration as f64
+    }
+
+    /// Heads for `to` from wherever the tween is at `now`, so changing course mid-way doesn't jump.
+    pub fn retarget(&mut self, to: T, now: f64) {
+        self.from = self.value(now);
+        self.to = to;
+        self.started_at = now;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub struct TweenSystem;
+
+/// Applies `Tween<Transform>` and `Tween<DrawMode>` to their entities every frame.
+pub struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(animate::<Transform>.label(TweenSystem))
+            .add_system(animate::<DrawMode>.label(TweenSystem));
+    }
+}
+
+fn animate<T: Component + Lerp + Send + Sync + 'static>(
+    mut tweened: Query<(&mut T, &Tween<T>)>,
+    time: Res<Time>,
+) {
+    let now = time.seconds_since_startup();
+    for (mut value, tween) in &mut tweened {
+        *value = tween.value(now);
+    }
+}
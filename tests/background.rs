@@ -0,0 +1,35 @@
+use colorwheel_bump::background::layer_offset;
+
+#[test]
+fn layer_offset_stays_within_a_tile() {
+    for step in 0..200 {
+        let scroll = step as f32 * 0.37;
+        let offset = layer_offset(-scroll * 2., scroll, 0.3, 5.);
+        assert!(
+            (0. ..=5.).contains(&offset),
+            "offset {} at step {}",
+            offset,
+            step
+        );
+    }
+}
+
+#[test]
+fn near_layer_scrolls_with_the_map_and_ignores_the_camera() {
+    let before = layer_offset(0., 0.2, 1., 1.);
+    let after = layer_offset(3., 0.5, 1., 1.);
+    // Drawn further left by exactly the distance scrolled
+    assert!((after - before - 0.3).abs() < 1e-5);
+}
+
+#[test]
+fn far_layer_scrolls_slower_and_follows_the_camera() {
+    let near = layer_offset(0., 3., 1., 10.) - layer_offset(0., 2., 1., 10.);
+    let far = layer_offset(0., 3., 0.3, 10.) - layer_offset(0., 2., 0.3, 10.);
+    assert!((near - 1.).abs() < 1e-5);
+    assert!((far - 0.3).abs() < 1e-5);
+
+    // Moving the camera right drags the far layer some of the way with it
+    let dragged = layer_offset(2., 10., 0.3, 10.) - layer_offset(0., 10., 0.3, 10.);
+    assert!((dragged + 1.4).abs() < 1e-5);
+}
@@ -0,0 +1,69 @@
+use colorwheel_bump::{
+    balance::{run_game, RunStats, Summary},
+    bot::BotDifficulty,
+    bump::MapConfig,
+    difficulty::Difficulty,
+};
+
+fn run_with_score(score: u32) -> RunStats {
+    RunStats {
+        score,
+        collected: score * 2,
+        wasted: score / 2,
+        time_to_target: vec![2.; score as usize],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn summary_takes_means_and_percentiles() {
+    let runs: Vec<_> = (0..=10).map(run_with_score).collect();
+
+    let summary = Summary::from_runs(&runs);
+
+    assert_eq!(summary.runs, 11);
+    assert_eq!(summary.score_mean, 5.);
+    assert_eq!(summary.score_p10, 1);
+    assert_eq!(summary.score_p50, 5);
+    assert_eq!(summary.score_p90, 9);
+    assert_eq!(summary.time_to_target_mean, 2.);
+    assert_eq!(summary.collected_mean, 10.);
+}
+
+#[test]
+fn summary_of_no_targets_has_no_time_to_target() {
+    let summary = Summary::from_runs(&[run_with_score(0)]);
+
+    assert_eq!(summary.runs, 1);
+    assert!(summary.time_to_target_mean.is_nan());
+    assert_eq!(Summary::from_runs(&[]).runs, 0);
+}
+
+#[test]
+fn bot_runs_are_summarized_consistently() {
+    let map_config = MapConfig::default();
+    let runs: Vec<_> = (0..2)
+        .map(|seed| {
+            run_game(
+                &map_config,
+                Difficulty::Normal,
+                BotDifficulty::Hard,
+                seed,
+                20.,
+            )
+        })
+        .collect();
+
+    for run in &runs {
+        assert!(run.time_to_target.len() <= run.score as usize);
+        assert!(run.wasted <= run.collected);
+    }
+    // The same seed plays the same game
+    let replay = run_game(&map_config, Difficulty::Normal, BotDifficulty::Hard, 0, 20.);
+    assert_eq!(replay.score, runs[0].score);
+    assert_eq!(replay.collected, runs[0].collected);
+
+    let summary = Summary::from_runs(&runs);
+    assert_eq!(summary.runs, 2);
+    assert!(summary.score_p10 <= summary.score_p50 && summary.score_p50 <= summary.score_p90);
+}
@@ -0,0 +1,37 @@
+use colorwheel_bump::camera::{clamp_view, smooth_damp};
+
+#[test]
+fn smooth_damp_settles_without_overshooting() {
+    let mut position = 0.;
+    let mut velocity = 0.;
+    let mut previous = position;
+
+    for _ in 0..120 {
+        position = smooth_damp(position, 10., &mut velocity, 0.2, 1. / 60.);
+        assert!(position <= 10., "overshot to {}", position);
+        assert!(position >= previous, "went backwards to {}", position);
+        previous = position;
+    }
+
+    assert!((position - 10.).abs() < 0.01, "stopped at {}", position);
+}
+
+#[test]
+fn smooth_damp_handles_long_frames() {
+    let mut velocity = 0.;
+    let position = smooth_damp(0., 10., &mut velocity, 0.1, 1.);
+
+    assert!(position <= 10.);
+    assert!(position > 9.);
+}
+
+#[test]
+fn view_stays_inside_the_borders() {
+    // A view 20 units across on a map from -50 to 50
+    assert_eq!(clamp_view(0., 10., -50., 50.), 0.);
+    assert_eq!(clamp_view(45., 10., -50., 50.), 40.);
+    assert_eq!(clamp_view(-48., 10., -50., 50.), -40.);
+
+    // Too big to fit, so it's centered
+    assert_eq!(clamp_view(3., 60., -50., 50.), 0.);
+}
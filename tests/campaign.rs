@@ -0,0 +1,96 @@
+use colorwheel_bump::{
+    campaign::{CampaignProgress, CAMPAIGN_LEVELS},
+    difficulty::Difficulty,
+    level::{Level, LevelFinished, LevelOutcome, LoseReason, StarGoals},
+};
+
+fn won(time: f64, crystals: u32) -> LevelFinished {
+    LevelFinished {
+        outcome: LevelOutcome::Won,
+        time,
+        score: 3,
+        crystals,
+    }
+}
+
+#[test]
+fn campaign_levels_parse() {
+    for path in CAMPAIGN_LEVELS {
+        let file = format!("{}/assets/{}", env!("CARGO_MANIFEST_DIR"), path);
+        let text = std::fs::read_to_string(&file).unwrap();
+        let level = Level::from_ron(&text).unwrap_or_else(|error| panic!("{}: {}", path, error));
+        assert!(!level.targets.is_empty(), "{} has no targets", path);
+    }
+}
+
+#[test]
+fn stars_for_score_time_and_efficiency() {
+    let goals = StarGoals {
+        par_time: Some(30.),
+        par_crystals: Some(10),
+        ..Default::default()
+    };
+
+    assert_eq!(goals.rate(&won(20., 8)), 3);
+    assert_eq!(goals.rate(&won(40., 8)), 2);
+    assert_eq!(goals.rate(&won(20., 12)), 2);
+    assert_eq!(goals.rate(&won(40., 12)), 1);
+
+    // Every goal is a star, and winning is worth one even with none met
+    let goals = StarGoals {
+        par_score: Some(5),
+        ..goals
+    };
+    let high_score = LevelFinished {
+        score: 6,
+        ..won(20., 8)
+    };
+    assert_eq!(goals.rate(&high_score), 3);
+    assert_eq!(goals.rate(&won(20., 8)), 2);
+    assert_eq!(goals.rate(&won(40., 8)), 1);
+    assert_eq!(goals.rate(&won(40., 12)), 1);
+
+    let lost = LevelFinished {
+        outcome: LevelOutcome::Lost(LoseReason::TimeUp),
+        ..won(20., 8)
+    };
+    assert_eq!(goals.rate(&lost), 0);
+}
+
+#[test]
+fn winning_unlocks_the_next_level() {
+    let mut progress = CampaignProgress::default();
+    assert!(progress.is_unlocked(CAMPAIGN_LEVELS, 0));
+    assert!(!progress.is_unlocked(CAMPAIGN_LEVELS, 1));
+
+    progress.complete(CAMPAIGN_LEVELS[0], 1, &won(50., 20), Difficulty::Normal);
+
+    assert!(progress.is_unlocked(CAMPAIGN_LEVELS, 1));
+    assert!(!progress.is_unlocked(CAMPAIGN_LEVELS, 2));
+}
+
+#[test]
+fn records_keep_the_best_run() {
+    let mut progress = CampaignProgress::default();
+    let path = CAMPAIGN_LEVELS[0];
+
+    assert!(progress.complete(path, 2, &won(30., 8), Difficulty::Hard));
+    assert!(!progress.complete(path, 1, &won(40., 12), Difficulty::Relaxed));
+    assert!(progress.complete(path, 2, &won(25., 12), Difficulty::Relaxed));
+
+    let record = progress.record(path).unwrap();
+    assert_eq!(record.stars, 2);
+    assert_eq!(record.best_time, 25.);
+    assert_eq!(record.difficulty, Difficulty::Hard);
+    assert_eq!(progress.total_stars(), 2);
+}
+
+#[test]
+fn progress_saves_and_loads() {
+    let mut progress = CampaignProgress::default();
+    progress.complete(CAMPAIGN_LEVELS[0], 3, &won(20., 5), Difficulty::Insane);
+
+    let loaded = CampaignProgress::from_ron(&progress.to_ron().unwrap()).unwrap();
+
+    assert_eq!(loaded, progress);
+}
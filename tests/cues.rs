@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+use colorwheel_bump::cues::{obstacle_ahead, ping_steps, PAN_STEPS, PITCH_STEPS, WARNING_RANGE};
+
+#[test]
+fn level_crystal_straight_ahead_pings_from_the_middle() {
+    assert_eq!(
+        ping_steps(Vec2::ZERO, 32.),
+        (PAN_STEPS / 2, PITCH_STEPS / 2)
+    );
+}
+
+#[test]
+fn pings_pan_toward_the_crystal_and_rise_above_the_player() {
+    let (pan_step, pitch_step) = ping_steps(Vec2::new(10., 8.), 32.);
+    assert!(pan_step > PAN_STEPS / 2);
+    assert!(pitch_step > PITCH_STEPS / 2);
+
+    let (pan_step, pitch_step) = ping_steps(Vec2::new(-10., -8.), 32.);
+    assert!(pan_step < PAN_STEPS / 2);
+    assert!(pitch_step < PITCH_STEPS / 2);
+}
+
+#[test]
+fn far_crystals_get_the_outermost_pings() {
+    assert_eq!(
+        ping_steps(Vec2::new(500., 100.), 32.),
+        (PAN_STEPS - 1, PITCH_STEPS - 1)
+    );
+    assert_eq!(ping_steps(Vec2::new(-500., -100.), 32.), (0, 0));
+}
+
+#[test]
+fn obstacles_ahead_in_line_are_warned_of() {
+    let wall = Vec2::new(1., 6.);
+    assert_eq!(obstacle_ahead(Vec2::new(5.5, 2.), wall), Some(5.));
+    // Behind the player, too far ahead, or above their head
+    assert_eq!(obstacle_ahead(Vec2::new(-3., 0.), wall), None);
+    assert_eq!(
+        obstacle_ahead(Vec2::new(WARNING_RANGE + 1., 0.), wall),
+        None
+    );
+    assert_eq!(obstacle_ahead(Vec2::new(3., 6.), wall), None);
+}
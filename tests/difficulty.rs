@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use colorwheel_bump::{
+    bump::{BorderKind, MapConfig},
+    core::crystal::{Crystal, Placed, SpawnWeights},
+    difficulty::{BaseMapConfig, Difficulty, HighScores},
+    harness::Simulation,
+};
+
+fn crystal_count(sim: &mut Simulation) -> usize {
+    let mut crystals = sim
+        .world_mut()
+        .query_filtered::<&Crystal, Without<Placed>>();
+    crystals.iter(sim.world()).count()
+}
+
+#[test]
+fn normal_plays_the_tuning_as_is() {
+    let base = MapConfig::default();
+    let normal = Difficulty::Normal.apply(&base);
+
+    assert_eq!(normal.crystal_count, base.crystal_count);
+    assert_eq!(normal.crystal_linvel, base.crystal_linvel);
+    assert_eq!(normal.spawn_weights, base.spawn_weights);
+}
+
+#[test]
+fn presets_scale_and_override_the_tuning() {
+    let base = MapConfig {
+        spawn_weights: SpawnWeights {
+            primary: 0.6,
+            secondary: 0.3,
+            tertiary: 0.1,
+        },
+        ..default()
+    };
+
+    let relaxed = Difficulty::Relaxed.apply(&base);
+    assert_eq!(relaxed.crystal_count, 40);
+    assert!(relaxed.crystal_linvel < base.crystal_linvel);
+    assert_eq!(relaxed.spawn_weights, SpawnWeights::PRIMARY_ONLY);
+    assert_eq!(relaxed.border, BorderKind::Bouncy);
+
+    let insane = Difficulty::Insane.apply(&base);
+    assert!(insane.crystal_count < base.crystal_count);
+    assert!(insane.player_gravity > base.player_gravity);
+    assert_eq!(insane.border, BorderKind::Damaging);
+}
+
+#[test]
+fn changing_difficulty_applies_to_a_running_game() {
+    let mut sim = Simulation::new();
+    sim.step();
+    assert_eq!(crystal_count(&mut sim), 32);
+
+    *sim.world_mut().resource_mut::<Difficulty>() = Difficulty::Insane;
+    sim.step_frames(2);
+
+    let expected = Difficulty::Insane.apply(&sim.world().resource::<BaseMapConfig>().0);
+    let map_config = sim.world().resource::<MapConfig>();
+    assert_eq!(map_config.crystal_count, expected.crystal_count);
+    assert_eq!(map_config.border, BorderKind::Damaging);
+    assert_eq!(crystal_count(&mut sim), expected.crystal_count);
+}
+
+#[test]
+fn high_scores_are_kept_per_difficulty() {
+    let mut high_scores = HighScores::default();
+    assert!(!high_scores.submit(Difficulty::Normal, 0));
+    assert!(high_scores.submit(Difficulty::Normal, 5));
+    assert!(!high_scores.submit(Difficulty::Normal, 3));
+    assert!(high_scores.submit(Difficulty::Insane, 2));
+
+    assert_eq!(high_scores.best(Difficulty::Normal), Some(5));
+    assert_eq!(high_scores.best(Difficulty::Insane), Some(2));
+    assert_eq!(high_scores.best(Difficulty::Relaxed), None);
+
+    let saved = high_scores.to_ron().unwrap();
+    assert_eq!(HighScores::from_ron(&saved).unwrap(), high_scores);
+}
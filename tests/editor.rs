@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use colorwheel_bump::{
+    bump::MapConfig,
+    core::crystal::CrystalColor,
+    editor::{EditorItem, EditorTool, LevelEditor},
+    level::Level,
+};
+
+#[test]
+fn placed_crystals_arrive_where_they_were_placed() {
+    let map_config = MapConfig::default();
+    let mut editor = LevelEditor::default();
+    editor.time = 10.;
+    editor.place(Vec2::new(5., 3.), &map_config);
+
+    let (_, position, crystal) = editor.crystals(&map_config).next().unwrap();
+    assert!(position.distance(Vec2::new(5., 3.)) < 0.01);
+    assert_eq!(crystal.color, CrystalColor::Red);
+
+    // Earlier on the timeline it hasn't entered the map yet
+    editor.time = 0.;
+    assert_eq!(editor.crystals(&map_config).count(), 0);
+}
+
+#[test]
+fn crystals_in_the_same_column_share_a_wave() {
+    let map_config = MapConfig::default();
+    let mut editor = LevelEditor::default();
+    editor.time = 10.;
+    editor.place(Vec2::new(5., 3.), &map_config);
+    editor.place(Vec2::new(5., -3.), &map_config);
+
+    assert_eq!(editor.level.waves.len(), 1);
+    assert_eq!(editor.level.waves[0].crystals.len(), 2);
+}
+
+#[test]
+fn undo_and_redo_edits() {
+    let map_config = MapConfig::default();
+    let mut editor = LevelEditor::default();
+    editor.tool = EditorTool::Barrier;
+    editor.place(Vec2::new(0., 0.), &map_config);
+    editor.push_target(CrystalColor::Blue);
+
+    assert!(editor.undo());
+    assert!(editor.level.targets.is_empty());
+    assert_eq!(editor.level.obstacles.len(), 1);
+    assert!(editor.undo());
+    assert!(editor.level.obstacles.is_empty());
+    assert!(!editor.undo());
+
+    assert!(editor.redo());
+    assert_eq!(editor.level.obstacles.len(), 1);
+    assert_eq!(editor.level.obstacles[0].barrier, Some(CrystalColor::Red));
+}
+
+#[test]
+fn right_click_finds_and_removes_items() {
+    let map_config = MapConfig::default();
+    let mut editor = LevelEditor::default();
+    editor.tool = EditorTool::Platform;
+    editor.place(Vec2::new(2., 4.), &map_config);
+
+    let item = editor.item_at(Vec2::new(3., 4.), &map_config);
+    assert_eq!(item, Some(EditorItem::Obstacle(0)));
+    editor.remove(item.unwrap());
+    assert!(editor.level.obstacles.is_empty());
+}
+
+#[test]
+fn playtest_starts_from_the_timeline_position() {
+    let map_config = MapConfig::default();
+    let mut editor = LevelEditor::default();
+    editor.time = 10.;
+    editor.place(Vec2::new(5., 3.), &map_config);
+    editor.time = 20.;
+    editor.place(Vec2::new(40., 0.), &map_config);
+
+    editor.time = 15.;
+    let level = editor.playtest_level(Vec2::new(-2., 1.), &map_config);
+
+    assert_eq!(level.start_position, Some((-2., 1.)));
+    // The first crystal is already on the map, the second is still to come
+    assert_eq!(level.waves.len(), 2);
+    assert_eq!(level.waves[0].time, 0.);
+    assert!(level.waves[1].time > 0.);
+}
+
+#[test]
+fn saved_levels_load_back() {
+    let map_config = MapConfig::default();
+    let mut editor = LevelEditor::default();
+    editor.time = 4.;
+    editor.place(Vec2::new(0., 2.), &map_config);
+    editor.push_target(CrystalColor::Orange);
+    editor.toggle_start_color(CrystalColor::Red);
+
+    let level = Level::from_ron(&editor.level.to_ron().unwrap()).unwrap();
+
+    assert_eq!(level.targets, vec![CrystalColor::Orange]);
+    assert_eq!(level.start_color, Some(CrystalColor::Red));
+    assert_eq!(level.waves.len(), 1);
+    assert_eq!(editor.file_name(), "untitled.level.ron");
+}
+
+#[test]
+fn renaming_names_the_saved_file() {
+    let mut editor = LevelEditor::default();
+    editor.rename("  Crystal Rush 2! ");
+    assert_eq!(editor.level.name, "Crystal Rush 2!");
+    assert_eq!(editor.file_name(), "crystal-rush-2.level.ron");
+
+    // Blank names are ignored, and renaming can be undone
+    editor.rename(" ");
+    assert_eq!(editor.level.name, "Crystal Rush 2!");
+    assert!(editor.undo());
+    assert_eq!(editor.file_name(), "untitled.level.ron");
+}
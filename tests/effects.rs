@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+use colorwheel_bump::{
+    bump::{GameRng, MapConfig},
+    camera::{CameraPlugin, GameCamera},
+    core::{crystal::Crystal, player::Player},
+    effects::{EffectIntensity, EffectsPlugin, Particle, ScreenShake, Shockwave},
+    harness::Simulation,
+    headless_app,
+};
+
+fn effects_sim(intensity: EffectIntensity) -> Simulation {
+    let mut app = headless_app();
+    app.insert_resource(GameRng::from_seed(1))
+        .add_plugin(EffectsPlugin)
+        .insert_resource(intensity);
+    Simulation::from_app(app)
+}
+
+fn count<T: Component>(sim: &mut Simulation) -> usize {
+    let mut query = sim.world_mut().query_filtered::<(), With<T>>();
+    query.iter(sim.world()).count()
+}
+
+/// Drops a crystal right on top of the player and lets it be collected.
+fn collect_a_crystal(sim: &mut Simulation) {
+    let player_position = sim.player_position();
+    let mut crystals = sim
+        .world_mut()
+        .query_filtered::<&mut Transform, With<Crystal>>();
+    if let Some(mut transform) = crystals.iter_mut(sim.world_mut()).next() {
+        transform.translation.x = player_position.x;
+        transform.translation.y = player_position.y;
+    }
+    sim.step_frames(5);
+}
+
+/// Reaches the target, and runs another frame for the effects to see it.
+fn reach_the_target(sim: &mut Simulation) {
+    let target = sim.target_color();
+    sim.set_player_color(Some(target));
+    sim.step_frames(2);
+}
+
+#[test]
+fn reduced_motion_turns_effects_off() {
+    assert_eq!(EffectIntensity::ReducedMotion.scale(), 0.);
+    assert!(EffectIntensity::Low.scale() < EffectIntensity::Full.scale());
+}
+
+#[test]
+fn intensity_cycles_through_every_setting() {
+    let mut intensity = EffectIntensity::default();
+    let mut seen = vec![intensity];
+    for _ in 0..2 {
+        intensity = intensity.next();
+        seen.push(intensity);
+    }
+
+    assert_eq!(intensity.next(), EffectIntensity::default());
+    assert!(seen.contains(&EffectIntensity::ReducedMotion));
+    assert!(seen.contains(&EffectIntensity::Low));
+    assert!(seen.contains(&EffectIntensity::Full));
+}
+
+#[test]
+fn trauma_is_capped() {
+    let mut shake = ScreenShake::default();
+    shake.add(0.6);
+    shake.add(0.6);
+
+    assert_eq!(shake.trauma, 1.);
+}
+
+#[test]
+fn collecting_a_crystal_bursts_into_particles() {
+    let mut sim = effects_sim(EffectIntensity::Full);
+    assert_eq!(count::<Particle>(&mut sim), 0);
+
+    collect_a_crystal(&mut sim);
+
+    assert!(count::<Particle>(&mut sim) > 0);
+}
+
+#[test]
+fn reaching_the_target_sends_a_shockwave() {
+    let mut sim = effects_sim(EffectIntensity::Full);
+
+    reach_the_target(&mut sim);
+
+    assert_eq!(count::<Shockwave>(&mut sim), 1);
+    assert!(sim.world().resource::<ScreenShake>().trauma > 0.);
+}
+
+#[test]
+fn lower_intensity_shakes_less() {
+    let mut full = effects_sim(EffectIntensity::Full);
+    let mut low = effects_sim(EffectIntensity::Low);
+    reach_the_target(&mut full);
+    reach_the_target(&mut low);
+
+    let full_trauma = full.world().resource::<ScreenShake>().trauma;
+    let low_trauma = low.world().resource::<ScreenShake>().trauma;
+    assert!(low_trauma > 0.);
+    assert!(low_trauma < full_trauma);
+}
+
+#[test]
+fn reduced_motion_turns_everything_off() {
+    let mut sim = effects_sim(EffectIntensity::ReducedMotion);
+
+    collect_a_crystal(&mut sim);
+    reach_the_target(&mut sim);
+
+    assert_eq!(count::<Particle>(&mut sim), 0);
+    assert_eq!(count::<Shockwave>(&mut sim), 0);
+    assert_eq!(sim.world().resource::<ScreenShake>().trauma, 0.);
+}
+
+#[test]
+fn shaking_never_shows_past_the_borders() {
+    let mut app = headless_app();
+    app.insert_resource(GameRng::from_seed(1))
+        .add_plugin(CameraPlugin)
+        .add_plugin(EffectsPlugin);
+    let mut sim = Simulation::from_app(app);
+
+    // Without a window the projection is never fitted, so give it the view of a 16:9 one
+    let mut projections = sim
+        .world_mut()
+        .query_filtered::<&mut OrthographicProjection, With<GameCamera>>();
+    for mut projection in projections.iter_mut(sim.world_mut()) {
+        projection.left = -18.;
+        projection.right = 18.;
+        projection.bottom = -10.;
+        projection.top = 10.;
+    }
+    let mut players = sim
+        .world_mut()
+        .query_filtered::<&mut Transform, With<Player>>();
+    for mut transform in players.iter_mut(sim.world_mut()) {
+        transform.translation.y = 14.;
+    }
+
+    // Flap against the top border while the camera is shaken as hard as it goes
+    let top = sim.world().resource::<MapConfig>().half_size().y + 0.5;
+    let mut cameras = sim
+        .world_mut()
+        .query_filtered::<(&Transform, &OrthographicProjection), With<GameCamera>>();
+    for _ in 0..120 {
+        sim.world_mut().resource_mut::<ScreenShake>().trauma = 1.;
+        sim.set_movement(Vec2::Y);
+        sim.step();
+
+        let (transform, projection) = cameras.single(sim.world());
+        let view_top = transform.translation.y + projection.top * projection.scale;
+        assert!(view_top <= top + 0.001, "view reaches {}", view_top);
+    }
+}
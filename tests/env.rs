@@ -0,0 +1,83 @@
+use colorwheel_bump::{
+    core::crystal::CrystalColor,
+    env::{Action, BumpEnv, EnvConfig},
+};
+use strum::IntoEnumIterator;
+
+/// Flaps in a fixed rhythm so runs are comparable without a policy.
+fn scripted_action(step: u32) -> Action {
+    Action {
+        flap: step % 3 == 0,
+        horizontal: 0.,
+    }
+}
+
+#[test]
+fn same_seed_replays_the_same_episode() {
+    let config = EnvConfig {
+        max_steps: 200,
+        ..Default::default()
+    };
+    let mut a = BumpEnv::new(config.clone());
+    let mut b = BumpEnv::new(config);
+
+    assert_eq!(a.reset(7), b.reset(7));
+    for step in 0..200 {
+        let (obs_a, reward_a, done_a) = a.step(scripted_action(step));
+        let (obs_b, reward_b, done_b) = b.step(scripted_action(step));
+        assert_eq!(obs_a, obs_b);
+        assert_eq!(reward_a, reward_b);
+        assert_eq!(done_a, done_b);
+    }
+}
+
+#[test]
+fn episodes_end_after_max_steps() {
+    let mut env = BumpEnv::new(EnvConfig {
+        max_steps: 10,
+        ..Default::default()
+    });
+    env.reset(1);
+
+    let done: Vec<bool> = (0..10)
+        .map(|step| env.step(scripted_action(step)).2)
+        .collect();
+
+    assert!(done[..9].iter().all(|done| !done));
+    assert!(done[9]);
+}
+
+#[test]
+fn observations_have_a_fixed_size() {
+    let config = EnvConfig::default();
+    let mut env = BumpEnv::new(config.clone());
+    let observation = env.reset(3);
+
+    assert_eq!(observation.crystals.len(), config.nearest_crystals);
+    assert_eq!(
+        observation.to_features(config.nearest_crystals).len(),
+        8 + 6 * config.nearest_crystals
+    );
+}
+
+#[test]
+fn reaching_the_target_is_rewarded_once() {
+    let mut env = BumpEnv::new(EnvConfig {
+        frame_skip: 1,
+        ..Default::default()
+    });
+    env.reset(5);
+    let target = env.simulation_mut().target_color();
+    let neighbor = CrystalColor::iter()
+        .find(|color| color.steps_to(&target).abs() == 1)
+        .unwrap();
+    env.simulation_mut().set_player_color(Some(neighbor));
+    env.step(Action::default());
+
+    // The step to the target must not also be judged against the target it picks next
+    env.simulation_mut().set_player_color(Some(target));
+    let (observation, reward, _) = env.step(Action::default());
+
+    assert_ne!(observation.target_color, target);
+    assert_eq!(reward, 1.);
+}
@@ -0,0 +1,161 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use colorwheel_bump::{
+    bump::{BumpSystem, GameRng},
+    core::{
+        event::{CrystalCollected, ResetGame},
+        timestep::{FixedTimestepPlugin, FixedUpdateAppExt, GameClock, HitStop, FIXED_TIMESTEP},
+    },
+    harness::Simulation,
+};
+
+/// Stand-in for a gameplay system: moves at a constant speed using the game clock.
+#[derive(Default)]
+struct Distance(f32);
+
+fn advance(mut distance: ResMut<Distance>, clock: Res<GameClock>) {
+    distance.0 += 0.6 * clock.delta_seconds();
+}
+
+/// Renders `seconds` worth of frames at `frame_rate`, returning the simulation state.
+fn simulate(frame_rate: f64, seconds: f64) -> (u64, f32) {
+    simulate_with_hit_stop(frame_rate, seconds, 0.)
+}
+
+/// Same as `simulate`, holding the fixed steps for the first `hit_stop` seconds.
+fn simulate_with_hit_stop(frame_rate: f64, seconds: f64, hit_stop: f64) -> (u64, f32) {
+    let mut app = App::new();
+    let time = Time::default();
+    let start = time.startup();
+    app.insert_resource(time)
+        .init_resource::<Distance>()
+        .add_plugin(FixedTimestepPlugin)
+        .insert_resource(HitStop(hit_stop))
+        .add_fixed_system(advance);
+
+    let frames = (seconds * frame_rate).round() as u32;
+    for frame in 0..=frames {
+        let now = start + Duration::from_secs_f64(frame as f64 / frame_rate);
+        app.world.resource_mut::<Time>().update_with_instant(now);
+        app.update();
+    }
+
+    let ticks = app.world.resource::<GameClock>().ticks;
+    let distance = app.world.resource::<Distance>().0;
+    (ticks, distance)
+}
+
+#[test]
+fn simulation_is_independent_of_frame_rate() {
+    let seconds = 3.;
+    let (reference_ticks, reference_distance) = simulate(60., seconds);
+    assert!(
+        (reference_ticks as f64 - seconds / FIXED_TIMESTEP).abs() <= 1.,
+        "expected ~{} ticks, got {}",
+        seconds / FIXED_TIMESTEP,
+        reference_ticks
+    );
+
+    for frame_rate in [24., 30., 75., 144., 240.] {
+        let (ticks, distance) = simulate(frame_rate, seconds);
+
+        // Accumulated float error may shift the last step into the next frame, never more.
+        assert!(
+            (ticks as i64 - reference_ticks as i64).abs() <= 1,
+            "{} Hz ran {} ticks, 60 Hz ran {}",
+            frame_rate,
+            ticks,
+            reference_ticks
+        );
+
+        // Every step advances the simulation by exactly the same amount.
+        let per_tick = distance / ticks as f32;
+        let reference_per_tick = reference_distance / reference_ticks as f32;
+        assert!((per_tick - reference_per_tick).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn hit_stop_holds_the_simulation() {
+    let (ticks, _) = simulate_with_hit_stop(60., 1., 0.5);
+
+    // Time spent stopped is dropped, not caught up on afterwards
+    assert!(
+        (ticks as f64 - 0.5 / FIXED_TIMESTEP).abs() <= 2.,
+        "expected ~{} ticks, got {}",
+        0.5 / FIXED_TIMESTEP,
+        ticks
+    );
+}
+
+/// Crystals collected so far, counted on the fixed timestep.
+#[derive(Default)]
+struct Pickups(usize);
+
+fn count_pickups(
+    mut collected_events: EventReader<CrystalCollected>,
+    mut pickups: ResMut<Pickups>,
+) {
+    pickups.0 += collected_events.iter().count();
+}
+
+/// Where the player was, the score and the crystals collected every sixth of a second.
+/// Plays the same input at any `frame_rate`: flapping for half of every second,
+/// reaching the target once and restarting the game between steps.
+fn play(frame_rate: f64) -> Vec<(Vec2, u32, usize)> {
+    let mut app = colorwheel_bump::headless_app();
+    app.insert_resource(GameRng::from_seed(7))
+        .init_resource::<Pickups>()
+        .add_fixed_system(count_pickups.after(BumpSystem::Collision));
+    let mut sim = Simulation::from_app(app);
+
+    let frames_per_sample = (frame_rate / 6.).round() as u32;
+    let mut samples = Vec::new();
+    for sample in 0..48 {
+        match sample % 6 {
+            0 => sim.press(KeyCode::Space),
+            3 => sim.release(KeyCode::Space),
+            _ => {}
+        }
+        if sample == 12 {
+            let target = sim.target_color();
+            sim.set_player_color(Some(target));
+        }
+        if sample == 30 {
+            sim.world_mut().send_event(ResetGame);
+        }
+
+        for _ in 0..frames_per_sample {
+            sim.step_with_frame_time(1. / frame_rate);
+        }
+        let pickups = sim.world().resource::<Pickups>().0;
+        samples.push((sim.player_position(), sim.score(), pickups));
+    }
+    samples
+}
+
+#[test]
+fn the_game_plays_the_same_at_any_frame_rate() {
+    let reference = play(60.);
+    // Reached the target, then restarted from nothing
+    assert!(reference[29].1 > 0);
+    assert_eq!(reference[30].1, 0);
+    assert!(reference.last().unwrap().2 > 0, "no crystals collected");
+
+    for frame_rate in [30., 144.] {
+        let samples = play(frame_rate);
+        for (sample, (expected, actual)) in reference.iter().zip(&samples).enumerate() {
+            assert!(
+                (expected.0 - actual.0).length() < 1e-3
+                    && expected.1 == actual.1
+                    && expected.2 == actual.2,
+                "{} Hz differs after {:.2}s: {:?}, 60 Hz {:?}",
+                frame_rate,
+                (sample + 1) as f64 / 6.,
+                actual,
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+use colorwheel_bump::{
+    bot::BotDifficulty,
+    bump::CurrentColor,
+    core::{
+        crystal::{Crystal, CrystalColor},
+        event::TargetReached,
+        player::Player,
+    },
+    harness::Simulation,
+};
+
+#[test]
+fn player_falls_without_input() {
+    let mut sim = Simulation::new();
+    let start = sim.player_position();
+
+    sim.step_frames(30);
+
+    assert!(sim.player_position().y < start.y);
+}
+
+#[test]
+fn jumping_lifts_the_player() {
+    let mut sim = Simulation::new();
+    let start = sim.player_position();
+
+    sim.press(KeyCode::Space);
+    sim.step_frames(30);
+
+    assert!(sim.player_position().y > start.y);
+}
+
+#[test]
+fn player_is_returned_to_the_map() {
+    let mut sim = Simulation::new();
+
+    let mut players = sim
+        .world_mut()
+        .query_filtered::<&mut Transform, With<Player>>();
+    for mut transform in players.iter_mut(sim.world_mut()) {
+        transform.translation.y = 1000.;
+    }
+    sim.step_frames(2);
+
+    assert!(sim.player_position().y.abs() < 16.);
+}
+
+#[test]
+fn touching_a_crystal_colors_the_player() {
+    let mut sim = Simulation::new();
+    sim.world_mut().resource_mut::<CurrentColor>().0 = None;
+    let player_position = sim.player_position();
+
+    // Drop a red crystal right on top of the player
+    let mut crystals = sim.world_mut().query::<(&mut Transform, &mut Crystal)>();
+    if let Some((mut transform, mut crystal)) = crystals.iter_mut(sim.world_mut()).next() {
+        transform.translation.x = player_position.x;
+        transform.translation.y = player_position.y;
+        crystal.crystal_color = CrystalColor::Red;
+    }
+    sim.step_frames(5);
+
+    assert!(sim.current_color().is_some());
+}
+
+#[test]
+fn reaching_the_target_scores() {
+    let mut sim = Simulation::new();
+    let target = sim.target_color();
+    sim.set_player_color(Some(target));
+
+    sim.step();
+
+    assert_eq!(sim.score(), 1);
+    assert_ne!(sim.target_color(), target);
+    assert_eq!(sim.current_color(), Some(target));
+
+    let reached = sim.world().resource::<Events<TargetReached>>();
+    assert_eq!(reached.get_reader().iter(reached).count(), 1);
+}
+
+#[test]
+fn bot_scores_over_time() {
+    let mut sim = Simulation::new();
+    sim.add_bot(BotDifficulty::Hard);
+
+    // Three minutes of play
+    sim.step_frames(60 * 180);
+
+    assert!(sim.score() > 0, "bot never reached a target");
+}
+
+#[test]
+fn streaks_multiply_the_score() {
+    let mut sim = Simulation::with_seed(1);
+    for _ in 0..3 {
+        let target = sim.target_color();
+        sim.set_player_color(Some(target));
+        sim.step();
+    }
+    assert_eq!(sim.score(), 1 + 2 + 3);
+
+    // Losing the color starts the streak over
+    sim.set_player_color(None);
+    sim.step();
+    let target = sim.target_color();
+    sim.set_player_color(Some(target));
+    sim.step();
+    assert_eq!(sim.score(), 1 + 2 + 3 + 1);
+}
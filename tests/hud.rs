@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+use colorwheel_bump::{
+    core::crystal::{CombineRule, CrystalColor},
+    hud::HudCorner,
+};
+
+#[test]
+fn corners_keep_their_margin() {
+    let window = Vec2::new(800., 600.);
+    let half_extent = Vec2::splat(50.);
+
+    assert_eq!(
+        HudCorner::TopRight.center(window, 10., half_extent),
+        Vec2::new(340., 240.)
+    );
+    assert_eq!(
+        HudCorner::TopLeft.center(window, 10., half_extent),
+        Vec2::new(-340., 240.)
+    );
+    assert_eq!(
+        HudCorner::BottomLeft.center(window, 10., half_extent),
+        Vec2::new(-340., -240.)
+    );
+    assert_eq!(
+        HudCorner::BottomRight.center(window, 10., half_extent),
+        Vec2::new(340., -240.)
+    );
+}
+
+#[test]
+fn route_takes_the_short_way_round() {
+    assert_eq!(
+        CrystalColor::Orange.route_to(&CrystalColor::Yellow),
+        vec![CrystalColor::YellowOrange, CrystalColor::Yellow]
+    );
+    assert_eq!(
+        CrystalColor::Orange.route_to(&CrystalColor::Red),
+        vec![CrystalColor::RedOrange, CrystalColor::Red]
+    );
+    assert!(CrystalColor::Blue.route_to(&CrystalColor::Blue).is_empty());
+}
+
+#[test]
+fn crystals_advance_or_regress() {
+    let from = Some(CrystalColor::Orange);
+    let target = CrystalColor::Yellow;
+
+    assert_eq!(
+        CombineRule::Step.progress(from, CrystalColor::Green, target),
+        1
+    );
+    assert_eq!(
+        CombineRule::Step.progress(from, CrystalColor::Red, target),
+        -1
+    );
+    assert_eq!(
+        CombineRule::DoubleStep.progress(from, CrystalColor::Green, target),
+        2
+    );
+    assert_eq!(
+        CombineRule::Adopt.progress(from, CrystalColor::Blue, target),
+        -2
+    );
+    assert_eq!(
+        CombineRule::Step.progress(from, CrystalColor::Orange, target),
+        0
+    );
+    assert!(CombineRule::Step.progress(None, CrystalColor::Blue, target) > 0);
+}
@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use colorwheel_bump::{
+    bump::MapConfig,
+    core::{
+        crystal::{Crystal, CrystalColor, Placed},
+        timestep::FIXED_TIMESTEP,
+    },
+    harness::Simulation,
+    level::{ActiveLevel, Level, LevelOutcome, LevelState, LoseReason},
+};
+
+const LEVEL: &str = r#"(
+    name: "Test",
+    start_color: Some(Red),
+    targets: [Orange, Yellow],
+    waves: [
+        (time: 2.0, crystals: [(y: 3.0, color: Yellow)]),
+        (time: 1.0, crystals: [(y: 0.0, color: Orange), (y: -3.0, color: Orange, kind: Fast)]),
+    ],
+    lose: (time_limit: Some(5.0)),
+)"#;
+
+fn seconds(seconds: f64) -> u32 {
+    (seconds / FIXED_TIMESTEP).ceil() as u32
+}
+
+fn placed_crystals(sim: &mut Simulation) -> usize {
+    let mut crystals = sim
+        .world_mut()
+        .query_filtered::<(), (With<Crystal>, With<Placed>)>();
+    crystals.iter(sim.world()).count()
+}
+
+fn outcome(sim: &Simulation) -> Option<LevelOutcome> {
+    sim.world().resource::<LevelState>().outcome
+}
+
+#[test]
+fn level_parses_and_sorts_waves() {
+    let level = Level::from_ron(LEVEL).unwrap();
+
+    assert_eq!(
+        level.targets,
+        vec![CrystalColor::Orange, CrystalColor::Yellow]
+    );
+    assert_eq!(level.waves[0].time, 1.0);
+    assert_eq!(level.waves[1].time, 2.0);
+}
+
+#[test]
+fn level_replaces_random_crystals() {
+    let mut sim = Simulation::with_level(1, Level::from_ron(LEVEL).unwrap());
+    sim.step();
+
+    let mut crystals = sim.world_mut().query_filtered::<(), With<Crystal>>();
+    assert_eq!(crystals.iter(sim.world()).count(), 0);
+    assert_eq!(sim.current_color(), Some(CrystalColor::Red));
+    assert_eq!(sim.target_color(), CrystalColor::Orange);
+}
+
+#[test]
+fn leaving_a_level_brings_back_random_crystals() {
+    let mut sim = Simulation::with_level(1, Level::from_ron(LEVEL).unwrap());
+    sim.step_frames(seconds(1.5));
+    assert!(placed_crystals(&mut sim) > 0);
+
+    sim.world_mut().resource_mut::<ActiveLevel>().0 = None;
+    sim.step_frames(2);
+
+    let crystal_count = sim.world().resource::<MapConfig>().crystal_count;
+    let mut crystals = sim
+        .world_mut()
+        .query_filtered::<(), (With<Crystal>, Without<Placed>)>();
+    assert_eq!(crystals.iter(sim.world()).count(), crystal_count);
+    assert_eq!(placed_crystals(&mut sim), 0);
+}
+
+#[test]
+fn waves_spawn_on_schedule() {
+    let mut sim = Simulation::with_level(1, Level::from_ron(LEVEL).unwrap());
+
+    sim.step_frames(seconds(0.5));
+    assert_eq!(placed_crystals(&mut sim), 0);
+
+    sim.step_frames(seconds(0.75));
+    assert_eq!(placed_crystals(&mut sim), 2);
+
+    sim.step_frames(seconds(1.));
+    assert_eq!(placed_crystals(&mut sim), 3);
+}
+
+#[test]
+fn targets_follow_the_sequence_until_won() {
+    let mut sim = Simulation::with_level(1, Level::from_ron(LEVEL).unwrap());
+    sim.step();
+
+    sim.set_player_color(Some(CrystalColor::Orange));
+    sim.step();
+    assert_eq!(sim.target_color(), CrystalColor::Yellow);
+    assert_eq!(outcome(&sim), None);
+
+    sim.set_player_color(Some(CrystalColor::Yellow));
+    sim.step();
+    assert_eq!(outcome(&sim), Some(LevelOutcome::Won));
+}
+
+#[test]
+fn running_out_of_time_loses() {
+    let mut sim = Simulation::with_level(1, Level::from_ron(LEVEL).unwrap());
+
+    sim.step_frames(seconds(5.5));
+
+    assert_eq!(outcome(&sim), Some(LevelOutcome::Lost(LoseReason::TimeUp)));
+}
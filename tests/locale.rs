@@ -0,0 +1,137 @@
+use ab_glyph::FontRef;
+use colorwheel_bump::locale::{
+    covers, negotiate, Locale, Messages, Translations, FALLBACK_FONTS, LOCALES,
+};
+
+#[test]
+fn messages_fill_in_their_variables() {
+    let messages = Messages::parse("hud-hits = Hits { $hits }/{ $max }").unwrap();
+    assert_eq!(
+        messages.format("hud-hits", &[("hits", &2), ("max", &5)]),
+        Some("Hits 2/5".to_string())
+    );
+    assert_eq!(messages.format("hud-falls", &[]), None);
+}
+
+#[test]
+fn indented_lines_continue_a_message_and_comments_are_skipped() {
+    let source = "# The help\nhelp =\n    First line\n    second line\n\n## Section\nnext = Next";
+    let messages = Messages::parse(source).unwrap();
+    assert_eq!(
+        messages.format("help", &[]),
+        Some("First line\nsecond line".to_string())
+    );
+    assert_eq!(messages.format("next", &[]), Some("Next".to_string()));
+}
+
+#[test]
+fn selectors_pick_by_plural_category_after_an_exact_key() {
+    let source = "steps =\n    { $steps ->\n        [0] No steps\n        [one] One step\n       *[other] { $steps } steps\n    }";
+    let messages = Messages::parse(source).unwrap();
+    let steps = |count: usize| messages.format("steps", &[("steps", &count)]).unwrap();
+    assert_eq!(steps(0), "No steps");
+    assert_eq!(steps(1), "One step");
+    assert_eq!(steps(4), "4 steps");
+}
+
+#[test]
+fn malformed_messages_are_rejected_with_their_line() {
+    let error = Messages::parse("ok = Fine\nbroken = { $x").unwrap_err();
+    assert!(error.starts_with("line 2"), "{}", error);
+    assert!(Messages::parse("no-default = { $x ->\n    [one] One\n    }").is_err());
+    assert!(Messages::parse("    stray indented line").is_err());
+}
+
+#[test]
+fn missing_messages_fall_back_then_show_their_id() {
+    let translations = Translations::new(
+        Messages::parse("greeting = Hallo").unwrap(),
+        Messages::parse("greeting = Hello\nfarewell = Bye").unwrap(),
+    );
+    assert_eq!(translations.text("greeting"), "Hallo");
+    assert_eq!(translations.text("farewell"), "Bye");
+    assert_eq!(translations.text("unknown"), "unknown");
+    assert_eq!(Translations::default().text("greeting"), "");
+}
+
+#[test]
+fn requested_languages_are_matched_by_tag_then_language() {
+    assert_eq!(negotiate("de").id, "de");
+    assert_eq!(negotiate("de-AT").id, "de");
+    assert_eq!(negotiate("es_MX").id, "es");
+    assert_eq!(negotiate("ES").id, "es");
+    assert_eq!(negotiate("fr-FR").id, "en");
+    assert_eq!(negotiate("").id, "en");
+}
+
+#[test]
+fn messages_list_their_text_without_variables() {
+    let messages = Messages::parse(
+        "hits = Treffer { $hits }\nsteps = { $n ->\n    [one] Schritt\n   *[other] Schritte\n    }",
+    )
+    .unwrap();
+    let characters = messages.characters();
+    for text in ["Treffer", "Schritt", "Schritte"] {
+        assert!(characters.contains(text), "{}", characters);
+    }
+    assert!(!characters.contains("hits"));
+}
+
+#[test]
+fn fonts_are_chosen_by_the_characters_they_cover() {
+    let load = |path: &str| {
+        std::fs::read(format!(
+            "{}/assets/fonts/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            path
+        ))
+        .unwrap()
+    };
+    let (hind, dejavu) = (load("Hind-Regular.otf"), load("DejaVuSans.ttf"));
+    let hind = FontRef::try_from_slice(&hind).unwrap();
+    let dejavu = FontRef::try_from_slice(&dejavu).unwrap();
+
+    assert!(covers(&hind, "Größe\nSchritte"));
+    assert!(!covers(&hind, "Привет"));
+    assert!(covers(&dejavu, "Привет"));
+}
+
+#[test]
+fn switching_language_visits_every_locale() {
+    let mut locale = Locale::default();
+    for _ in 0..LOCALES.len() {
+        locale = locale.next();
+    }
+    assert_eq!(locale, Locale::default());
+}
+
+#[test]
+fn every_locale_translates_every_english_message() {
+    let load = |id: &str| {
+        let path = format!("{}/assets/locales/{}.ftl", env!("CARGO_MANIFEST_DIR"), id);
+        Messages::parse(&std::fs::read_to_string(&path).unwrap())
+            .unwrap_or_else(|error| panic!("{}: {}", path, error))
+    };
+    let english = load("en");
+
+    for locale in LOCALES {
+        let messages = load(locale.id);
+        for id in english.ids() {
+            assert!(messages.contains(id), "{} is missing {}", locale.id, id);
+        }
+    }
+}
+
+#[test]
+fn every_locale_has_a_font_for_all_its_text() {
+    let assets = format!("{}/assets", env!("CARGO_MANIFEST_DIR"));
+    for locale in LOCALES {
+        let source = std::fs::read_to_string(format!("{}/locales/{}.ftl", assets, locale.id));
+        let text = Messages::parse(&source.unwrap()).unwrap().characters();
+        let covered = locale.fonts.iter().chain(FALLBACK_FONTS).any(|path| {
+            let data = std::fs::read(format!("{}/{}", assets, path)).unwrap();
+            covers(&FontRef::try_from_slice(&data).unwrap(), &text)
+        });
+        assert!(covered, "no font covers {}", locale.id);
+    }
+}
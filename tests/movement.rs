@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+use colorwheel_bump::{
+    bump::{GameMode, MovementMode},
+    core::player::Player,
+    harness::Simulation,
+};
+
+fn home_x(sim: &mut Simulation) -> f32 {
+    let mut players = sim.world_mut().query::<&Player>();
+    players.single(sim.world()).home_x
+}
+
+/// Steers right and flaps now and then to stay off the floor, for `frames` frames.
+fn steer_right(sim: &mut Simulation, frames: u32) {
+    for frame in 0..frames {
+        let flap = if frame % 10 == 0 { 1. } else { 0. };
+        sim.set_movement(Vec2::new(1., flap));
+        sim.step();
+    }
+}
+
+#[test]
+fn flapping_keeps_the_player_at_home() {
+    let mut sim = Simulation::with_seed(1);
+    let home = home_x(&mut sim);
+
+    steer_right(&mut sim, 120);
+
+    assert!((sim.player_position().x - home).abs() < 0.1);
+}
+
+#[test]
+fn free_movement_steers_sideways() {
+    let mut sim = Simulation::with_seed(1);
+    *sim.world_mut().resource_mut::<MovementMode>() = MovementMode::Free;
+    let home = home_x(&mut sim);
+
+    steer_right(&mut sim, 60);
+
+    assert!(sim.player_position().x > home + 3.);
+}
+
+#[test]
+fn movement_mode_only_toggles_while_playing() {
+    let mut sim = Simulation::with_seed(1);
+    *sim.world_mut().resource_mut::<GameMode>() = GameMode::Attract;
+    sim.press(KeyCode::F);
+    sim.step();
+    sim.release(KeyCode::F);
+    sim.step();
+    assert_eq!(*sim.world().resource::<MovementMode>(), MovementMode::Flap);
+
+    *sim.world_mut().resource_mut::<GameMode>() = GameMode::Playing;
+    sim.press(KeyCode::F);
+    sim.step();
+    assert_eq!(*sim.world().resource::<MovementMode>(), MovementMode::Free);
+}
+
+#[test]
+fn switching_back_to_flapping_drifts_home() {
+    let mut sim = Simulation::with_seed(1);
+    *sim.world_mut().resource_mut::<MovementMode>() = MovementMode::Free;
+    let home = home_x(&mut sim);
+    steer_right(&mut sim, 60);
+    assert!(sim.player_position().x > home + 3.);
+
+    sim.press(KeyCode::F);
+    sim.step();
+    sim.release(KeyCode::F);
+    assert_eq!(*sim.world().resource::<MovementMode>(), MovementMode::Flap);
+    steer_right(&mut sim, 120);
+
+    assert!((sim.player_position().x - home).abs() < 0.1);
+}
@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+use colorwheel_bump::{
+    bump::{GameRng, MapConfig},
+    core::{
+        crystal::CrystalColor, platform::PlatformKind, player::Player, timestep::FIXED_TIMESTEP,
+    },
+    difficulty::BaseMapConfig,
+    harness::Simulation,
+    level::{ActiveLevel, Level, LevelState},
+    obstacle::{ObstacleConfig, ObstacleGenerator, PlacedObstacle},
+};
+
+fn seconds(seconds: f64) -> u32 {
+    (seconds / FIXED_TIMESTEP).ceil() as u32
+}
+
+/// A level with nothing but one obstacle, reaching the player's column at `x` at the start.
+fn obstacle_level(obstacle: PlacedObstacle, start_color: Option<CrystalColor>) -> Level {
+    let half_width = MapConfig::default().half_size().x;
+    Level {
+        name: "Obstacle".to_string(),
+        start_color,
+        obstacles: vec![PlacedObstacle {
+            x_offset: obstacle.x_offset - half_width - obstacle.width / 2.,
+            ..obstacle
+        }],
+        ..default()
+    }
+}
+
+/// A wall from border to border, centered at `x`.
+fn wall(x: f32) -> PlacedObstacle {
+    PlacedObstacle {
+        time: 0.,
+        x_offset: x,
+        y: 0.,
+        width: 1.,
+        height: 40.,
+        platform: PlatformKind::Solid,
+        restitution: None,
+        barrier: None,
+        amplitude: 0.,
+    }
+}
+
+/// The player above a platform of `kind` on a map that doesn't scroll, so it stays put.
+fn platform_sim(kind: PlatformKind, start_color: Option<CrystalColor>) -> Simulation {
+    let platform = PlacedObstacle {
+        y: -3.,
+        width: 6.,
+        height: 0.5,
+        platform: kind,
+        ..wall(0.)
+    };
+    let level = Level {
+        start_position: Some((0., 0.)),
+        ..obstacle_level(platform, start_color)
+    };
+    let still = MapConfig {
+        crystal_linvel: 0.,
+        obstacles: ObstacleConfig {
+            enabled: false,
+            ..default()
+        },
+        ..default()
+    };
+
+    let mut app = colorwheel_bump::headless_app();
+    app.insert_resource(GameRng::from_seed(1))
+        .insert_resource(BaseMapConfig(still))
+        .insert_resource(ActiveLevel(Some(level)));
+    Simulation::from_app(app)
+}
+
+#[test]
+fn route_never_climbs_faster_than_the_slope_allows() {
+    let mut sim = Simulation::with_seed(3);
+    let config = sim.world().resource::<MapConfig>().obstacles.clone();
+    assert!(config.enabled);
+    let max_step = config.max_route_slope * config.spacing;
+
+    let mut route_y = sim.world().resource::<ObstacleGenerator>().route_y;
+    let mut columns = 0;
+    for _ in 0..seconds(120.) {
+        let distance = sim.world().resource::<ObstacleGenerator>().distance;
+        sim.step();
+        let generator = sim.world().resource::<ObstacleGenerator>();
+        // The distance wraps around whenever a column is spawned
+        if generator.distance < distance {
+            assert!((generator.route_y - route_y).abs() <= max_step + 1e-4);
+            route_y = generator.route_y;
+            columns += 1;
+        }
+    }
+
+    assert!(columns > 20);
+}
+
+#[test]
+fn walls_push_a_flapping_player_along() {
+    let mut sim = Simulation::with_level(1, obstacle_level(wall(4.), Some(CrystalColor::Red)));
+    let home = sim.player_position().x;
+
+    sim.step_frames(seconds(2.));
+
+    assert!(sim.player_position().x < home - 5.);
+    assert!(sim.world().resource::<LevelState>().hits > 0);
+    assert_eq!(sim.current_color(), None);
+}
+
+#[test]
+fn pushed_players_drift_back_home() {
+    let mut sim = Simulation::with_level(1, obstacle_level(wall(4.), None));
+    let home = sim.player_position().x;
+    sim.step_frames(seconds(1.5));
+    assert!(sim.player_position().x < home - 2.);
+
+    // Long after the wall has scrolled off the map
+    sim.step_frames(seconds(12.));
+
+    assert!((sim.player_position().x - home).abs() < 0.1);
+}
+
+#[test]
+fn barriers_let_a_matching_player_through() {
+    let barrier = PlacedObstacle {
+        barrier: Some(CrystalColor::Red),
+        ..wall(4.)
+    };
+    let mut sim = Simulation::with_level(1, obstacle_level(barrier, Some(CrystalColor::Red)));
+    let home = sim.player_position().x;
+
+    sim.step_frames(seconds(2.));
+
+    assert!((sim.player_position().x - home).abs() < 0.1);
+    assert_eq!(sim.world().resource::<LevelState>().hits, 0);
+    assert_eq!(sim.current_color(), Some(CrystalColor::Red));
+}
+
+#[test]
+fn barriers_push_a_player_of_another_color() {
+    let barrier = PlacedObstacle {
+        barrier: Some(CrystalColor::Red),
+        ..wall(4.)
+    };
+    let mut sim = Simulation::with_level(1, obstacle_level(barrier, Some(CrystalColor::Blue)));
+    let home = sim.player_position().x;
+
+    sim.step_frames(seconds(2.));
+
+    assert!(sim.player_position().x < home - 5.);
+    assert!(sim.world().resource::<LevelState>().hits > 0);
+}
+
+fn teleport_player(sim: &mut Simulation, position: Vec2) {
+    let mut players = sim
+        .world_mut()
+        .query_filtered::<&mut Transform, With<Player>>();
+    for mut transform in players.iter_mut(sim.world_mut()) {
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
+
+#[test]
+fn one_way_platforms_hold_from_above_and_let_through_from_below() {
+    let mut sim = platform_sim(PlatformKind::OneWay, None);
+    sim.step_frames(seconds(2.));
+    assert!(sim.player_position().y > -3.);
+
+    teleport_player(&mut sim, Vec2::new(0., -8.));
+    let mut highest = f32::MIN;
+    for _ in 0..seconds(2.) {
+        sim.set_movement(Vec2::Y);
+        sim.step();
+        highest = highest.max(sim.player_position().y);
+    }
+    assert!(highest > -2.);
+}
+
+#[test]
+fn crumbling_platforms_give_way_after_being_touched() {
+    let mut sim = platform_sim(PlatformKind::Crumbling { delay: 0.75 }, None);
+    sim.step_frames(seconds(1.));
+    assert!(sim.player_position().y > -3.);
+
+    sim.step_frames(seconds(2.));
+    assert!(sim.player_position().y < -4.);
+}
+
+#[test]
+fn conveyors_carry_the_player_along() {
+    let mut sim = platform_sim(PlatformKind::Conveyor { speed: 4. }, None);
+    sim.step_frames(seconds(3.));
+
+    let position = sim.player_position();
+    assert!(position.y > -3.);
+    assert!(position.x > 0.5);
+}
+
+#[test]
+fn damaging_platforms_knock_the_color_out() {
+    let mut sim = platform_sim(PlatformKind::Damaging, Some(CrystalColor::Red));
+    sim.step_frames(seconds(2.));
+
+    assert!(sim.world().resource::<LevelState>().hits > 0);
+    assert_eq!(sim.current_color(), None);
+}
@@ -0,0 +1,27 @@
+use bevy::prelude::*;
+use colorwheel_bump::radar::edge_arrow;
+
+#[test]
+fn no_arrow_for_crystals_in_view() {
+    let half_view = Vec2::new(18., 10.5);
+    assert_eq!(edge_arrow(Vec2::new(5., -3.), half_view, 1.), None);
+    assert_eq!(edge_arrow(Vec2::new(-18., 10.5), half_view, 1.), None);
+}
+
+#[test]
+fn arrow_sits_on_the_inset_edge_toward_the_crystal() {
+    let half_view = Vec2::new(18., 10.5);
+
+    // Straight above, so it's on the top edge
+    assert_eq!(
+        edge_arrow(Vec2::new(0., 14.), half_view, 1.),
+        Some(Vec2::new(0., 9.5))
+    );
+
+    // Far off to the lower left, it's pulled back along the same line
+    let relative = Vec2::new(-40., -20.);
+    let arrow = edge_arrow(relative, half_view, 1.).unwrap();
+    assert!((arrow.x + 17.).abs() < 1e-5, "arrow at {}", arrow);
+    assert!(arrow.y >= -9.5 && arrow.y < 0.);
+    assert!(arrow.normalize().abs_diff_eq(relative.normalize(), 1e-5));
+}
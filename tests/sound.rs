@@ -0,0 +1,94 @@
+use colorwheel_bump::{
+    core::crystal::CrystalColor,
+    sound::{arpeggio, drone, glide, pan, pitch, stereo_wav, tone, wav, SAMPLE_RATE},
+};
+
+#[test]
+fn neighbouring_wedges_are_a_semitone_apart() {
+    let semitone = 2f32.powf(1. / 12.);
+    let ratio = pitch(CrystalColor::YellowOrange, 0) / pitch(CrystalColor::Orange, 0);
+    assert!((ratio - semitone).abs() < 1e-4);
+
+    // Round the wheel and back to orange is an octave up
+    let ratio = pitch(CrystalColor::Orange, 1) / pitch(CrystalColor::RedOrange, 0);
+    assert!((ratio - semitone).abs() < 1e-4);
+    assert!((pitch(CrystalColor::Orange, -2) - 55.).abs() < 1e-3);
+}
+
+#[test]
+fn tone_fades_in_and_out_without_clipping() {
+    let samples = tone(440., 0.3);
+    assert_eq!(samples.len(), (0.3 * SAMPLE_RATE as f32) as usize);
+    assert!(samples.iter().all(|sample| sample.abs() <= 1.));
+    assert!(samples[0].abs() < 1e-3);
+    assert!(samples.last().unwrap().abs() < 1e-3);
+    assert!(samples.iter().any(|sample| sample.abs() > 0.1));
+}
+
+#[test]
+fn arpeggio_staggers_its_notes() {
+    let samples = arpeggio(&[220., 277.18, 329.63], 0.5, 0.1);
+    let expected = (0.5 * SAMPLE_RATE as f32) as usize + 2 * (0.1 * SAMPLE_RATE as f32) as usize;
+    assert_eq!(samples.len(), expected);
+    assert!(samples.iter().all(|sample| sample.abs() <= 1.));
+}
+
+#[test]
+fn drone_loops_without_a_jump() {
+    let samples = drone(pitch(CrystalColor::Blue, -2), 4.);
+    let largest_step = samples
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).abs())
+        .fold(0., f32::max);
+
+    // Wrapping from the end back to the start is no bigger a step than any other
+    let wrap = (samples[0] - samples[samples.len() - 1]).abs();
+    assert!(
+        wrap <= largest_step * 1.01,
+        "wrap {} step {}",
+        wrap,
+        largest_step
+    );
+}
+
+#[test]
+fn wav_has_a_pcm_header() {
+    let bytes = wav(&[0., 1., -1.]);
+    assert_eq!(bytes.len(), 44 + 3 * 2);
+    assert_eq!(&bytes[0..4], b"RIFF");
+    assert_eq!(&bytes[8..12], b"WAVE");
+    assert_eq!(&bytes[36..40], b"data");
+    assert_eq!(u32::from_le_bytes(bytes[40..44].try_into().unwrap()), 6);
+    assert_eq!(i16::from_le_bytes([bytes[46], bytes[47]]), i16::MAX);
+}
+
+#[test]
+fn glide_ends_at_its_target_pitch() {
+    // Count zero crossings in the last stretch to estimate the frequency there
+    let samples = glide(200., 400., 1.);
+    let tail = &samples[samples.len() - SAMPLE_RATE as usize / 10..];
+    let crossings = tail
+        .windows(2)
+        .filter(|pair| pair[0] < 0. && pair[1] >= 0.)
+        .count();
+    assert!((37..=41).contains(&crossings), "{} crossings", crossings);
+}
+
+#[test]
+fn pan_keeps_the_loudness_even() {
+    for position in [-1., -0.3, 0., 0.6, 1.] {
+        let [left, right] = pan(&[1.], position)[0];
+        assert!((left * left + right * right - 1.).abs() < 1e-5);
+    }
+    let [left, right] = pan(&[1.], -1.)[0];
+    assert!((left - 1.).abs() < 1e-5 && right.abs() < 1e-5);
+}
+
+#[test]
+fn stereo_wav_interleaves_its_channels() {
+    let bytes = stereo_wav(&[[1., -1.]]);
+    assert_eq!(bytes.len(), 44 + 4);
+    assert_eq!(u16::from_le_bytes([bytes[22], bytes[23]]), 2);
+    assert_eq!(i16::from_le_bytes([bytes[44], bytes[45]]), i16::MAX);
+    assert_eq!(i16::from_le_bytes([bytes[46], bytes[47]]), -i16::MAX);
+}
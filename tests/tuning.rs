@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use colorwheel_bump::{
+    bump::{BorderKind, MapConfig},
+    core::{
+        crystal::{Crystal, Placed},
+        platform::Platform,
+        player::Player,
+    },
+    difficulty::BaseMapConfig,
+    harness::Simulation,
+    hud::HudCorner,
+};
+
+#[test]
+fn default_tuning_parses() {
+    let tuning: MapConfig =
+        ron::from_str(include_str!("../assets/tuning/default.tuning.ron")).unwrap();
+
+    assert_eq!(tuning.map_size, Vec2::new(100., 32.));
+    assert_eq!(tuning.crystal_count, 32);
+    assert_eq!(tuning.border, BorderKind::Solid);
+    assert_eq!(tuning.hud.wheel_corner, HudCorner::TopRight);
+    assert_eq!(tuning.hud.wheel_radius, 64.);
+}
+
+#[test]
+fn hud_layout_is_tuned_with_the_rest() {
+    let tuning: MapConfig = ron::from_str("(hud: (wheel_corner: BottomLeft, scale: 1.5))").unwrap();
+    let defaults = MapConfig::default();
+
+    assert_eq!(tuning.hud.wheel_corner, HudCorner::BottomLeft);
+    assert_eq!(tuning.hud.scale, 1.5);
+    assert_eq!(tuning.hud.margin, defaults.hud.margin);
+}
+
+#[test]
+fn missing_fields_fall_back_to_defaults() {
+    let tuning: MapConfig = ron::from_str("(crystal_count: 5, player_gravity: 2.0)").unwrap();
+    let defaults = MapConfig::default();
+
+    assert_eq!(tuning.crystal_count, 5);
+    assert_eq!(tuning.player_gravity, 2.);
+    assert_eq!(tuning.map_size, defaults.map_size);
+    assert_eq!(tuning.crystal_linvel, defaults.crystal_linvel);
+}
+
+#[test]
+fn tuning_applies_to_a_running_game() {
+    let mut sim = Simulation::new();
+    sim.step();
+
+    let tuning = MapConfig {
+        map_size: Vec2::new(60., 20.),
+        crystal_count: 10,
+        player_gravity: 1.,
+        border: BorderKind::Bouncy,
+        ..default()
+    };
+    sim.world_mut().insert_resource(BaseMapConfig(tuning));
+    sim.step_frames(2);
+
+    assert_eq!(
+        sim.world().resource::<MapConfig>().map_size,
+        Vec2::new(60., 20.)
+    );
+
+    let mut crystals = sim
+        .world_mut()
+        .query_filtered::<&Crystal, Without<Placed>>();
+    assert_eq!(crystals.iter(sim.world()).count(), 10);
+
+    let mut players = sim
+        .world_mut()
+        .query_filtered::<&GravityScale, With<Player>>();
+    assert_eq!(players.single(sim.world()).0, 1.);
+
+    // The borders moved to the new edges, grew to the new width and bounce
+    let mut platforms = sim
+        .world_mut()
+        .query::<(&Transform, &Collider, &Restitution, &Platform)>();
+    let borders: Vec<_> = platforms
+        .iter(sim.world())
+        .filter(|(transform, ..)| transform.translation.y.abs() == 10.)
+        .collect();
+    assert_eq!(borders.len(), 2);
+    for (_, collider, restitution, _) in borders {
+        assert_eq!(
+            collider.as_cuboid().unwrap().half_extents(),
+            Vec2::new(30.5, 0.5)
+        );
+        assert_eq!(restitution.coefficient, 1.);
+    }
+}
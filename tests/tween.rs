@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use colorwheel_bump::tween::{Ease, Lerp, Repeat, Tween};
+
+#[test]
+fn tween_reaches_its_end_and_stays() {
+    let tween = Tween::<f32>::new(0., 10., 1., 2.).with_ease(Ease::Linear);
+
+    assert_eq!(tween.value(0.), 0.);
+    assert_eq!(tween.value(2.), 5.);
+    assert_eq!(tween.value(3.), 10.);
+    assert_eq!(tween.value(10.), 10.);
+    assert!(!tween.is_finished(2.));
+    assert!(tween.is_finished(3.));
+}
+
+#[test]
+fn ping_pong_goes_back_and_forth() {
+    let tween = Tween::<f32>::new(0., 1., 0., 1.)
+        .with_ease(Ease::Linear)
+        .with_repeat(Repeat::PingPong);
+
+    assert_eq!(tween.value(0.5), 0.5);
+    assert_eq!(tween.value(1.), 1.);
+    assert_eq!(tween.value(1.5), 0.5);
+    assert_eq!(tween.value(2.), 0.);
+    assert!(!tween.is_finished(100.));
+}
+
+#[test]
+fn retargeting_starts_from_where_it_is() {
+    let mut tween = Tween::<f32>::new(0., 10., 0., 1.).with_ease(Ease::Linear);
+
+    tween.retarget(0., 0.5);
+
+    assert_eq!(tween.value(0.5), 5.);
+    assert_eq!(tween.value(1.), 2.5);
+    assert_eq!(tween.value(1.5), 0.);
+}
+
+#[test]
+fn colors_blend_the_short_way_round() {
+    let red = Color::hsl(350., 1., 0.5);
+    let orange = Color::hsl(30., 1., 0.5);
+
+    let [hue, ..] = red.lerp(&orange, 0.5).as_hsla_f32();
+    assert!((hue - 10.).abs() < 0.5, "hue was {}", hue);
+
+    // Black has no hue, so only the lightness changes
+    let [hue, _, lightness, _] = Color::BLACK.lerp(&orange, 0.5).as_hsla_f32();
+    assert!((hue - 30.).abs() < 0.5, "hue was {}", hue);
+    assert!((lightness - 0.25).abs() < 0.01);
+}
+
+#[test]
+fn easing_keeps_the_ends() {
+    for ease in [Ease::Linear, Ease::SmoothStep, Ease::Out] {
+        assert_eq!(ease.apply(0.), 0.);
+        assert_eq!(ease.apply(1.), 1.);
+        assert!(ease.apply(0.5) > 0. && ease.apply(0.5) < 1.);
+    }
+}